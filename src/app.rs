@@ -1,28 +1,72 @@
-use iced::widget::{button, column, container, row, text, Space};
-use iced::{Alignment, Background, Element, Length, Task, Theme};
+use iced::widget::{button, column, container, row, scrollable, text, Space};
+use iced::{event, time, window, Alignment, Background, Element, Event, Length, Subscription, Task, Theme};
 
-use crate::message::Message;
-use crate::importing::{DuplicateEntry, ImportState, ImportStatus, ImportStep, NewHireRecord};
+use crate::message::{Message, ProfileMessage, ScreenProfile};
+use crate::importing::history::{roll_back_run, ImportSource};
+use crate::importing::incidents::IncidentProfile;
+use crate::importing::csv_format::{CsvFormat, DetectedFormat};
+use crate::importing::mapping::{ColumnMapping, MappingEditor};
+use crate::importing::policy::PresetDecisions;
+use crate::importing::profile::{ImportProfile, ProfileField};
+use crate::importing::queue::{ImportQueue, QueuedStatus, RepeatDecision};
+use crate::importing::snapshot::MissingTicket;
+use crate::importing::{
+    CsvReadOptions, CsvReadOutcome, DecisionOutcome, DuplicateDecision, DuplicateEntry, ImportCancel, ImportEvent, ImportKind, ImportState, ImportStatus,
+    MergeSelection, NewHireProfile, PreparedImport, RejectedRow,
+};
+use crate::data::state_durations::StateTimeGroup;
 use crate::reports::it_lead_time::ItLeadTimeReport;
+use crate::reports::state_time_heatmap::StateTimeHeatmapReport;
+use crate::screens::history::HistoryState;
+use crate::screens::import::{ImportView, ProfileImport};
+use crate::screens::reports::{CustomFieldState, StateTimeState, TicketLoadState};
+use crate::screens::settings::{PolicyEditor, SettingsState};
 use crate::screens::Page;
 use crate::theme::{
     ACCENT, DRAWER_BG, DRAWER_ITEM_BG, DRAWER_TEXT_ACTIVE, DRAWER_TEXT_INACTIVE,
 };
 use lucide_icons::iced::{
-    icon_chart_line, icon_house, icon_panel_left_close, icon_panel_left_open, icon_plus,
+    icon_chart_line, icon_history, icon_house, icon_panel_left_close, icon_panel_left_open, icon_plus,
+    icon_settings,
 };
-use std::collections::VecDeque;
 use std::path::PathBuf;
 
+const WATCH_LOG_LIMIT: usize = 20;
+
 pub struct App {
     theme: Theme,
     current_page: Page,
     sidebar_collapsed: bool,
     db_path: PathBuf,
     import_state: ImportState,
-    import_queue: VecDeque<NewHireRecord>,
-    pending_duplicates: VecDeque<DuplicateEntry>,
-    decision_queue: VecDeque<(NewHireRecord, bool)>,
+    new_hires: ProfileImport<NewHireProfile>,
+    incidents: ProfileImport<IncidentProfile>,
+    import_source: Option<ImportSource>,
+    import_sheet: Option<String>,
+    pending_sync_cursor: Option<String>,
+    /// Saved once none of the run's duplicates wait for a decision.
+    held_sync_cursor: Option<(i64, String)>,
+    import_queue: ImportQueue,
+    streaming_run: Option<i64>,
+    files_hovered: bool,
+    watch_scanning: bool,
+    watch_log: Vec<String>,
+    import_streaming: bool,
+    import_cancel: Option<ImportCancel>,
+    preview_enabled: bool,
+    lenient_import: bool,
+    full_snapshot: bool,
+    snapshot_numbers: Option<Vec<i64>>,
+    missing_tickets: Vec<MissingTicket>,
+    /// Deleting `missing_tickets` is logged with this run.
+    missing_run: Option<i64>,
+    delete_reason: String,
+    csv_format: CsvFormat,
+    detected_format: Option<DetectedFormat>,
+    pending_import_path: Option<PathBuf>,
+    applying_decisions: bool,
+    history: HistoryState,
+    settings: SettingsState,
     report_series: Vec<(f32, f32)>,
     report_loading: bool,
     report_error: Option<String>,
@@ -32,7 +76,7 @@ pub struct App {
     report_employee_counts: Vec<(String, f32)>,
     report_employee_loading: bool,
     report_employee_error: Option<String>,
-    report_heatmap: Option<(Vec<String>, Vec<String>, Vec<Vec<f32>>)>,
+    report_heatmap: Option<crate::data::heatmap_site_employee::SiteEmployeeGrid>,
     report_heatmap_loading: bool,
     report_heatmap_error: Option<String>,
     report_radar_metrics: Vec<(String, f32)>,
@@ -41,6 +85,9 @@ pub struct App {
     report_breach_rate: Option<(f32, f32)>,
     report_breach_loading: bool,
     report_breach_error: Option<String>,
+    ticket_load: TicketLoadState,
+    custom_fields: CustomFieldState,
+    state_time: StateTimeState,
 }
 
 impl App {
@@ -50,6 +97,10 @@ impl App {
         let db_path = directories::ProjectDirs::from("com", "woodgrain", "sw-reports")
             .map(|dirs| dirs.data_dir().join("sw_reports.sqlite"))
             .unwrap_or_else(|| PathBuf::from("sw_reports.sqlite"));
+        let prepare_database = Task::perform(
+            crate::importing::migrations::prepare_database(db_path.clone()),
+            Message::DatabasePrepared,
+        );
         (
             Self {
                 theme,
@@ -57,9 +108,32 @@ impl App {
                 sidebar_collapsed: true,
                 db_path,
                 import_state: ImportState::new(),
-                import_queue: VecDeque::new(),
-                pending_duplicates: VecDeque::new(),
-                decision_queue: VecDeque::new(),
+                new_hires: ProfileImport::default(),
+                incidents: ProfileImport::default(),
+                import_source: None,
+                import_sheet: None,
+                pending_sync_cursor: None,
+                held_sync_cursor: None,
+                import_queue: ImportQueue::default(),
+                streaming_run: None,
+                files_hovered: false,
+                watch_scanning: false,
+                watch_log: Vec::new(),
+                import_streaming: false,
+                import_cancel: None,
+                preview_enabled: false,
+                lenient_import: false,
+                full_snapshot: false,
+                snapshot_numbers: None,
+                missing_tickets: Vec::new(),
+                missing_run: None,
+                delete_reason: String::new(),
+                csv_format: CsvFormat::default(),
+                detected_format: None,
+                pending_import_path: None,
+                applying_decisions: false,
+                history: HistoryState::default(),
+                settings: SettingsState::default(),
                 report_series: Vec::new(),
                 report_loading: false,
                 report_error: None,
@@ -78,12 +152,39 @@ impl App {
                 report_breach_rate: None,
                 report_breach_loading: false,
                 report_breach_error: None,
+                ticket_load: TicketLoadState::default(),
+                custom_fields: CustomFieldState::default(),
+                state_time: StateTimeState::default(),
             },
-            Task::none(),
+            prepare_database,
         )
     }
 
     pub fn update(&mut self, message: Message) -> Task<Message> {
+        let task = self.handle_message(message);
+        self.settle_queued_file();
+        Task::batch([task, self.start_next_queued_file()])
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        let file_drops = event::listen_with(|event, _status, _window| match event {
+            Event::Window(window::Event::FileHovered(_)) => Some(Message::FilesHovered),
+            Event::Window(window::Event::FilesHoveredLeft) => Some(Message::FilesHoverLeft),
+            Event::Window(window::Event::FileDropped(path)) => Some(Message::FileDropped(path)),
+            _ => None,
+        });
+
+        if self.settings.watch_folder.is_none() {
+            return file_drops;
+        }
+
+        Subscription::batch([
+            file_drops,
+            time::every(crate::importing::watch::POLL_INTERVAL).map(|_| Message::WatchTick),
+        ])
+    }
+
+    fn handle_message(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::ToggleSidebar => {
                 self.sidebar_collapsed = !self.sidebar_collapsed;
@@ -91,6 +192,30 @@ impl App {
             }
             Message::Navigate(page) => {
                 self.current_page = page;
+                if page == Page::Import && self.import_state.status.is_ready() {
+                    return Task::batch([
+                        self.load_pending_duplicates::<NewHireProfile>(),
+                        self.load_pending_duplicates::<IncidentProfile>(),
+                    ]);
+                }
+                if page == Page::History {
+                    self.history.selected_run = None;
+                    self.history.confirm_rollback = None;
+                    self.history.message = None;
+                    return self.load_history();
+                }
+                if page == Page::Settings {
+                    self.settings.message = None;
+                    self.settings.reset_timestamp_inputs();
+                    return Task::batch(vec![
+                        self.load_policies::<NewHireProfile>(),
+                        self.load_policies::<IncidentProfile>(),
+                        Task::perform(
+                            crate::importing::api::load_api_settings(self.db_path.clone()),
+                            Message::ApiSettingsLoaded,
+                        ),
+                    ]);
+                }
                 if page == Page::Reports {
                     self.report_loading = true;
                     self.report_error = None;
@@ -104,6 +229,8 @@ impl App {
                     self.report_radar_error = None;
                     self.report_breach_loading = true;
                     self.report_breach_error = None;
+                    self.ticket_load.start_loading();
+                    self.state_time.start_loading();
 
                     Task::batch(vec![
                         Task::perform(
@@ -140,85 +267,528 @@ impl App {
                             ),
                             Message::ReportBreachRateLoaded,
                         ),
+                        Task::perform(
+                            crate::reports::open_tickets_bar::OpenTicketsBarReport::load(
+                                self.db_path.clone(),
+                            ),
+                            Message::ReportOpenTicketsLoaded,
+                        ),
+                        Task::perform(
+                            crate::reports::ticket_flow_line::TicketFlowLineReport::load(
+                                self.db_path.clone(),
+                            ),
+                            Message::ReportTicketFlowLoaded,
+                        ),
+                        Task::perform(
+                            crate::reports::backlog_category_bar::BacklogCategoryBarReport::load(
+                                self.db_path.clone(),
+                            ),
+                            Message::ReportBacklogLoaded,
+                        ),
+                        Task::perform(
+                            crate::data::custom_fields::load_keys(self.db_path.clone()),
+                            Message::ReportCustomKeysLoaded,
+                        ),
+                        Task::perform(
+                            StateTimeHeatmapReport::load(self.db_path.clone(), StateTimeGroup::Site),
+                            |result| Message::ReportStateTimeLoaded(StateTimeGroup::Site, result),
+                        ),
+                        Task::perform(
+                            StateTimeHeatmapReport::load(
+                                self.db_path.clone(),
+                                StateTimeGroup::Assignee,
+                            ),
+                            |result| Message::ReportStateTimeLoaded(StateTimeGroup::Assignee, result),
+                        ),
                     ])
                 } else {
                     Task::none()
                 }
             }
-            Message::Noop => Task::none(),
-            Message::StartImport => {
-                let file = rfd::FileDialog::new()
-                    .add_filter("CSV", &["csv"])
-                    .pick_file();
+            Message::StartImport => self.queue_picked_files(ImportKind::NewHires),
+            Message::StartIncidentImport => self.queue_picked_files(ImportKind::Incidents),
+            Message::NewHires(message) => self.handle_profile_message(message),
+            Message::Incidents(message) => self.handle_profile_message(message),
+            Message::DatabasePrepared(result) => match result {
+                Ok(()) => Task::batch(vec![
+                    Task::perform(
+                        crate::importing::watch::load_watch_folder(self.db_path.clone()),
+                        Message::WatchFolderLoaded,
+                    ),
+                    Task::perform(
+                        crate::importing::timestamps::load_timestamp_settings(
+                            self.db_path.clone(),
+                        ),
+                        Message::TimestampSettingsLoaded,
+                    ),
+                ]),
+                Err(err) => {
+                    self.import_state.status = ImportStatus::Error;
+                    self.import_state.message = Some(err);
+                    Task::none()
+                }
+            },
+            Message::WatchFolderLoaded(result) => match result {
+                Ok(folder) => {
+                    self.settings.watch_folder = folder;
+                    self.scan_watch_folder()
+                }
+                Err(err) => {
+                    self.watch_log.push(err);
+                    Task::none()
+                }
+            },
+            Message::WatchTick => self.scan_watch_folder(),
+            Message::WatchFolderScanned(result) => {
+                self.watch_scanning = false;
+                let stamp = chrono::Local::now().format("%H:%M");
+                let mut duplicates = 0;
+                match result {
+                    Ok(files) => {
+                        duplicates = files.iter().map(|file| file.duplicates()).sum();
+                        self.watch_log.extend(
+                            files
+                                .iter()
+                                .map(|file| format!("{stamp} {}", file.describe())),
+                        );
+                    }
+                    Err(err) => self.watch_log.push(format!("{stamp} {err}")),
+                }
+                let overflow = self.watch_log.len().saturating_sub(WATCH_LOG_LIMIT);
+                self.watch_log.drain(..overflow);
+
+                if duplicates > 0
+                    && self.current_page == Page::Import
+                    && self.import_state.status.is_ready()
+                {
+                    return self.load_pending_duplicates::<NewHireProfile>();
+                }
+                Task::none()
+            }
+            Message::ChooseWatchFolder => match rfd::FileDialog::new().pick_folder() {
+                Some(folder) => Task::perform(
+                    crate::importing::watch::save_watch_folder(self.db_path.clone(), Some(folder)),
+                    Message::WatchFolderSaved,
+                ),
+                None => Task::none(),
+            },
+            Message::ClearWatchFolder => Task::perform(
+                crate::importing::watch::save_watch_folder(self.db_path.clone(), None),
+                Message::WatchFolderSaved,
+            ),
+            Message::WatchFolderSaved(result) => match result {
+                Ok(folder) => {
+                    self.settings.message = Some(match &folder {
+                        Some(folder) => format!("Watching {} for new CSV files.", folder.display()),
+                        None => "Stopped watching for new files.".to_owned(),
+                    });
+                    self.settings.watch_folder = folder;
+                    self.scan_watch_folder()
+                }
+                Err(err) => {
+                    self.settings.message = Some(err);
+                    Task::none()
+                }
+            },
+            Message::FilesHovered => {
+                self.files_hovered = true;
+                Task::none()
+            }
+            Message::FilesHoverLeft => {
+                self.files_hovered = false;
+                Task::none()
+            }
+            // The window reports each dropped file on its own.
+            Message::FileDropped(path) => {
+                self.files_hovered = false;
+                self.current_page = Page::Import;
 
-                if let Some(path) = file {
-                    return self.start_import_with_path(path);
+                if !crate::importing::is_supported_file(&path) {
+                    let message = format!(
+                        "{} is not a CSV or Excel file and was not imported.",
+                        path.display()
+                    );
+                    if self.import_state.status.is_ready() {
+                        self.import_state.set_error(message);
+                    } else {
+                        self.import_state.set_message(message);
+                    }
+                    return Task::none();
                 }
 
+                self.import_queue.push(path, ImportKind::NewHires);
+                Task::none()
+            }
+            Message::RemoveQueuedFile(index) => {
+                self.import_queue.remove(index);
+                Task::none()
+            }
+            Message::ClearFinishedFiles => {
+                self.import_queue.clear_finished();
                 Task::none()
             }
-            Message::ImportPrepared(result) => match result {
-                Ok(records) => {
-                    let total = records.len();
-                    self.import_queue = VecDeque::from(records);
-                    self.pending_duplicates.clear();
-                    self.decision_queue.clear();
-                    self.import_state.start(total);
-                    self.import_state.set_message("Processing records...".to_owned());
+            Message::StartApiSync => {
+                if !self.import_state.status.is_ready() || self.import_queue.is_busy() {
+                    self.import_state
+                        .set_message("Finish the queued imports before syncing.".to_owned());
+                    return Task::none();
+                }
+                if self.watch_scanning {
+                    self.import_state.set_message(
+                        "Wait for the watched folder import to finish before syncing.".to_owned(),
+                    );
+                    return Task::none();
+                }
 
-                    if total == 0 {
+                self.reset_import_setup();
+                self.import_state.status = ImportStatus::Loading;
+                self.import_state
+                    .set_message("Fetching tickets from the SolarWinds API...".to_owned());
+                Task::perform(
+                    crate::importing::api::sync_tickets(
+                        self.db_path.clone(),
+                        self.read_options(),
+                    ),
+                    Message::ApiFetched,
+                )
+            }
+            Message::ApiFetched(result) => match result {
+                Ok(fetch) => {
+                    if fetch.outcome.records.is_empty() && fetch.outcome.rejected.is_empty() {
                         self.import_state.status = ImportStatus::Done;
                         self.import_state
-                            .set_message("No data rows found.".to_owned());
+                            .set_message("No tickets changed since the last sync.".to_owned());
                         return Task::none();
                     }
 
-                    self.process_next_record()
+                    self.import_source = Some(fetch.source);
+                    self.import_sheet = None;
+                    self.pending_sync_cursor = fetch.cursor;
+                    self.handle_read_outcome::<NewHireProfile>(fetch.outcome)
                 }
                 Err(err) => {
                     self.import_state.set_error(err);
                     Task::none()
                 }
             },
-            Message::ProcessedRecord(result) => match result {
-                Ok(step) => self.handle_import_step(step),
-                Err(err) => {
-                    self.import_state.set_error(err);
-                    Task::none()
+            Message::SyncCursorSaved(result) => {
+                match result {
+                    Ok(cursor) => self.settings.api.last_updated_at = cursor,
+                    Err(err) => self.import_state.set_message(err),
                 }
-            },
-            Message::DecideDuplicate { number, overwrite } => {
-                if let Some(index) = self
-                    .pending_duplicates
+                Task::none()
+            }
+            Message::TogglePreview(enabled) => {
+                self.preview_enabled = enabled;
+                Task::none()
+            }
+            Message::ToggleLenient(enabled) => {
+                self.lenient_import = enabled;
+                Task::none()
+            }
+            Message::ToggleFullSnapshot(enabled) => {
+                self.full_snapshot = enabled;
+                Task::none()
+            }
+            Message::MissingTicketsLoaded(result) => {
+                match result {
+                    Ok(missing) => self.missing_tickets = missing,
+                    Err(err) => self.import_state.set_message(err),
+                }
+                Task::none()
+            }
+            Message::DeleteReasonChanged(reason) => {
+                self.delete_reason = reason;
+                Task::none()
+            }
+            Message::SoftDeleteMissing => {
+                let Some(run_id) = self.missing_run else {
+                    return Task::none();
+                };
+                let numbers = self
+                    .missing_tickets
                     .iter()
-                    .position(|entry| entry.record.number == number)
-                {
-                    if let Some(record) = self.pending_duplicates.remove(index) {
-                        self.import_state
-                            .pending_duplicates
-                            .retain(|pending| pending.number != number);
-                        self.decision_queue.push_back((record.record, overwrite));
-                        return self.process_next_decision();
+                    .map(|ticket| ticket.number)
+                    .collect();
+                Task::perform(
+                    crate::importing::snapshot::soft_delete(
+                        self.db_path.clone(),
+                        run_id,
+                        numbers,
+                        self.delete_reason.clone(),
+                    ),
+                    Message::MissingDeleted,
+                )
+            }
+            Message::DismissMissing => {
+                self.missing_tickets.clear();
+                Task::none()
+            }
+            Message::MissingDeleted(result) => {
+                match result {
+                    Ok(deleted) => {
+                        self.missing_tickets.clear();
+                        self.delete_reason.clear();
+                        self.import_state.set_message(format!(
+                            "{deleted} missing ticket(s) removed from reports."
+                        ));
                     }
+                    Err(err) => self.import_state.set_message(err),
                 }
+                Task::none()
+            }
+            Message::CsvEncodingSelected(choice) => {
+                self.csv_format.encoding = choice.0;
+                self.reread_unmapped_csv()
+            }
+            Message::CsvDelimiterSelected(choice) => {
+                self.csv_format.delimiter = choice.0;
+                self.reread_unmapped_csv()
+            }
+            Message::ExportRejected => {
+                let file = rfd::FileDialog::new()
+                    .add_filter("CSV", &["csv"])
+                    .set_file_name("rejected_rows.csv")
+                    .save_file();
 
+                match file {
+                    Some(path) => Task::perform(
+                        crate::importing::export_rejected_rows(
+                            path,
+                            self.import_state.rejected_headers.clone(),
+                            self.import_state.rejected_rows.clone(),
+                        ),
+                        Message::RejectedExported,
+                    ),
+                    None => Task::none(),
+                }
+            }
+            Message::RejectedExported(result) => {
+                match result {
+                    Ok(path) => self.import_state.set_message(format!(
+                        "Rejected rows exported to {}.",
+                        path.display()
+                    )),
+                    Err(err) => self.import_state.set_message(err),
+                }
                 Task::none()
             }
-            Message::DecideAll { overwrite } => {
-                while let Some(entry) = self.pending_duplicates.pop_front() {
-                    self.decision_queue.push_back((entry.record, overwrite));
+            Message::CancelImport => {
+                if let Some(cancel) = &self.import_cancel {
+                    cancel.cancel();
+                    self.import_queue.cancel_waiting();
+                    self.import_state
+                        .set_message("Cancelling import...".to_owned());
                 }
-
-                self.import_state.pending_duplicates.clear();
-                self.process_next_decision()
+                Task::none()
+            }
+            Message::HistoryLoaded(result) => {
+                self.history.loading = false;
+                match result {
+                    Ok(runs) => {
+                        self.history.runs = runs;
+                        self.history.error = None;
+                    }
+                    Err(err) => {
+                        self.history.runs.clear();
+                        self.history.error = Some(err);
+                    }
+                }
+                Task::none()
+            }
+            Message::SelectImportRun(run_id) => {
+                self.history.confirm_rollback = None;
+                Task::perform(
+                    crate::importing::history::load_run_records(self.db_path.clone(), run_id),
+                    Message::ImportRunRecordsLoaded,
+                )
+            }
+            Message::ImportRunRecordsLoaded(result) => {
+                match result {
+                    Ok(selected) => {
+                        self.history.selected_run = Some(selected);
+                        self.history.error = None;
+                    }
+                    Err(err) => {
+                        self.history.selected_run = None;
+                        self.history.error = Some(err);
+                    }
+                }
+                Task::none()
+            }
+            Message::RequestRollback(run_id) => {
+                self.history.confirm_rollback = Some(run_id);
+                self.history.message = None;
+                Task::none()
+            }
+            Message::CancelRollback => {
+                self.history.confirm_rollback = None;
+                Task::none()
+            }
+            Message::ConfirmRollback => match self.history.confirm_rollback.take() {
+                Some(run_id) => {
+                    self.history.message = Some(format!("Rolling back run #{run_id}..."));
+                    let incidents = self
+                        .history
+                        .runs
+                        .iter()
+                        .any(|run| run.id == run_id && run.is_incident_export());
+                    let db_path = self.db_path.clone();
+                    if incidents {
+                        Task::perform(
+                            roll_back_run::<IncidentProfile>(db_path, run_id),
+                            Message::RollbackFinished,
+                        )
+                    } else {
+                        Task::perform(
+                            roll_back_run::<NewHireProfile>(db_path, run_id),
+                            Message::RollbackFinished,
+                        )
+                    }
+                }
+                None => Task::none(),
+            },
+            Message::RollbackFinished(result) => {
+                if let Ok(outcome) = &result
+                    && self
+                        .held_sync_cursor
+                        .as_ref()
+                        .is_some_and(|(run_id, _)| *run_id == outcome.run_id)
+                {
+                    self.held_sync_cursor = None;
+                }
+                // Deleting tickets missing from a run that no longer stands
+                // could not be rolled back with it.
+                if let Ok(outcome) = &result
+                    && self.missing_run == Some(outcome.run_id)
+                {
+                    self.missing_tickets.clear();
+                    self.missing_run = None;
+                }
+                self.history.message = Some(match result {
+                    Ok(outcome) => format!(
+                        "Rolled back run #{}: {} ticket(s) removed, {} restored.",
+                        outcome.run_id, outcome.removed, outcome.restored
+                    ),
+                    Err(err) => err,
+                });
+                self.history.selected_run = None;
+                self.history.timeline = None;
+                self.load_history()
+            }
+            Message::TimelineNumberChanged(input) => {
+                self.history.timeline_input = input;
+                Task::none()
+            }
+            Message::ShowTimeline => match self.history.timeline_input.trim().parse::<i64>() {
+                Ok(number) => self.load_timeline(number),
+                Err(_) => {
+                    self.history.timeline = None;
+                    self.history.timeline_error = Some("Enter a ticket number.".to_owned());
+                    Task::none()
+                }
+            },
+            Message::ShowTicketTimeline(number) => {
+                self.history.timeline_input = number.to_string();
+                self.load_timeline(number)
+            }
+            Message::TimelineLoaded(result) => {
+                match result {
+                    Ok(timeline) => {
+                        self.history.timeline = Some(timeline);
+                        self.history.timeline_error = None;
+                    }
+                    Err(err) => {
+                        self.history.timeline = None;
+                        self.history.timeline_error = Some(err);
+                    }
+                }
+                Task::none()
+            }
+            Message::ApiSettingsLoaded(result) => {
+                match result {
+                    Ok(api) => self.settings.api = api,
+                    Err(err) => self.settings.message = Some(err),
+                }
+                Task::none()
+            }
+            Message::ApiBaseUrlChanged(base_url) => {
+                self.settings.api.base_url = base_url;
+                Task::none()
+            }
+            Message::ApiTokenChanged(token) => {
+                self.settings.api.token = token;
+                Task::none()
+            }
+            Message::SaveApiSettings => {
+                self.settings.message = Some("Saving API settings...".to_owned());
+                Task::perform(
+                    crate::importing::api::save_api_settings(
+                        self.db_path.clone(),
+                        self.settings.api.clone(),
+                    ),
+                    Message::ApiSettingsSaved,
+                )
+            }
+            Message::ApiSettingsSaved(result) => {
+                self.settings.message = Some(match result {
+                    Ok(_) => "API settings saved.".to_owned(),
+                    Err(err) => err,
+                });
+                Task::none()
+            }
+            Message::ResetApiSync => {
+                self.held_sync_cursor = None;
+                Task::perform(
+                    crate::importing::api::save_sync_cursor(self.db_path.clone(), None),
+                    Message::SyncCursorSaved,
+                )
+            }
+            Message::TimestampSettingsLoaded(result) => {
+                match result {
+                    Ok(timestamps) => {
+                        self.settings.timestamps = timestamps;
+                        self.settings.reset_timestamp_inputs();
+                    }
+                    Err(err) => self.settings.message = Some(err),
+                }
+                Task::none()
+            }
+            Message::TimestampTimezoneChanged(timezone) => {
+                self.settings.timezone_input = timezone;
+                Task::none()
+            }
+            Message::TimestampFormatsChanged(formats) => {
+                self.settings.formats_input = formats;
+                Task::none()
             }
-            Message::DecisionApplied(result) => match result {
-                Ok(step) => self.handle_import_step(step),
+            Message::SaveTimestampSettings => match self.settings.timestamp_draft() {
+                Ok(timestamps) => {
+                    self.settings.message = Some("Saving timestamp settings...".to_owned());
+                    Task::perform(
+                        crate::importing::timestamps::save_timestamp_settings(
+                            self.db_path.clone(),
+                            timestamps,
+                        ),
+                        Message::TimestampSettingsSaved,
+                    )
+                }
                 Err(err) => {
-                    self.import_state.set_error(err);
+                    self.settings.message = Some(err);
                     Task::none()
                 }
             },
+            Message::TimestampSettingsSaved(result) => {
+                self.settings.message = Some(match result {
+                    Ok(timestamps) => {
+                        self.settings.timestamps = timestamps;
+                        self.settings.reset_timestamp_inputs();
+                        "Timestamp settings saved.".to_owned()
+                    }
+                    Err(err) => err,
+                });
+                Task::none()
+            }
             Message::ReportSeriesLoaded(result) => {
                 self.report_loading = false;
                 match result {
@@ -303,6 +873,102 @@ impl App {
                 }
                 Task::none()
             }
+            Message::ReportOpenTicketsLoaded(result) => {
+                self.ticket_load.open_loading = false;
+                match result {
+                    Ok(points) => {
+                        self.ticket_load.open_tickets = points;
+                        self.ticket_load.open_error = None;
+                    }
+                    Err(err) => {
+                        self.ticket_load.open_tickets.clear();
+                        self.ticket_load.open_error = Some(err);
+                    }
+                }
+                Task::none()
+            }
+            Message::ReportTicketFlowLoaded(result) => {
+                self.ticket_load.flow_loading = false;
+                match result {
+                    Ok(points) => {
+                        self.ticket_load.ticket_flow = points;
+                        self.ticket_load.flow_error = None;
+                    }
+                    Err(err) => {
+                        self.ticket_load.ticket_flow.clear();
+                        self.ticket_load.flow_error = Some(err);
+                    }
+                }
+                Task::none()
+            }
+            Message::ReportStateTimeLoaded(group, result) => {
+                self.state_time.finish_loading(group, result);
+                Task::none()
+            }
+            Message::ReportCustomKeysLoaded(result) => match result {
+                Ok(keys) => {
+                    let state = &mut self.custom_fields;
+                    if !state.group_by.as_ref().is_some_and(|key| keys.contains(key)) {
+                        state.group_by = keys.first().cloned();
+                    }
+                    if !state.filter_key.as_ref().is_some_and(|key| keys.contains(key)) {
+                        state.filter_key = None;
+                    }
+                    state.keys = keys;
+                    self.load_custom_field_report()
+                }
+                Err(err) => {
+                    self.custom_fields.keys.clear();
+                    self.custom_fields.error = Some(err);
+                    Task::none()
+                }
+            },
+            Message::ReportCustomFieldLoaded(result) => {
+                self.custom_fields.loading = false;
+                match result {
+                    Ok(points) => {
+                        self.custom_fields.points = points;
+                        self.custom_fields.error = None;
+                    }
+                    Err(err) => {
+                        self.custom_fields.points.clear();
+                        self.custom_fields.error = Some(err);
+                    }
+                }
+                Task::none()
+            }
+            Message::CustomGroupByChanged(key) => {
+                self.custom_fields.group_by = Some(key);
+                self.load_custom_field_report()
+            }
+            Message::CustomFilterKeyChanged(key) => {
+                self.custom_fields.filter_key = Some(key);
+                self.load_custom_field_report()
+            }
+            Message::CustomFilterValueChanged(value) => {
+                self.custom_fields.filter_value = value;
+                Task::none()
+            }
+            Message::ApplyCustomFilter => self.load_custom_field_report(),
+            Message::ClearCustomFilter => {
+                self.custom_fields.filter_key = None;
+                self.custom_fields.filter_value.clear();
+                self.load_custom_field_report()
+            }
+            Message::ReportBacklogLoaded(result) => {
+                self.ticket_load.backlog_loading = false;
+                match result {
+                    Ok(points) => {
+                        self.ticket_load.backlog = points;
+                        self.ticket_load.backlog_error = None;
+                    }
+                    Err(err) => {
+                        self.ticket_load.backlog.clear();
+                        self.ticket_load.backlog_error = Some(err);
+                    }
+                }
+                Task::none()
+            }
         }
     }
 
@@ -343,7 +1009,13 @@ impl App {
                 }
             });
 
-        let pages = [Page::Import, Page::Home, Page::Reports]
+        let pages = [
+            Page::Import,
+            Page::History,
+            Page::Home,
+            Page::Reports,
+            Page::Settings,
+        ]
             .into_iter()
             .map(|page| self.sidebar_button(page));
 
@@ -368,8 +1040,10 @@ impl App {
         let label = page.label();
         let icon = match page {
             Page::Import => icon_plus(),
+            Page::History => icon_history(),
             Page::Home => icon_house(),
             Page::Reports => icon_chart_line(),
+            Page::Settings => icon_settings(),
         }
         .size(18)
         .style(move |_| iced::widget::text::Style {
@@ -430,105 +1104,1038 @@ impl App {
 
     fn content_view<'a>(&'a self) -> Element<'a, Message> {
         match self.current_page {
-            Page::Import => crate::screens::import::view(&self.import_state),
+            Page::Import => crate::screens::import::view(ImportView {
+                import_state: &self.import_state,
+                new_hires: &self.new_hires,
+                incidents: &self.incidents,
+                preview_enabled: self.preview_enabled,
+                lenient_import: self.lenient_import,
+                full_snapshot: self.full_snapshot,
+                missing_tickets: &self.missing_tickets,
+                delete_reason: &self.delete_reason,
+                csv_format: self.csv_format,
+                detected_format: self.detected_format,
+                files_hovered: self.files_hovered,
+                import_queue: &self.import_queue,
+                watch_folder: self.settings.watch_folder.as_deref(),
+                watch_log: &self.watch_log,
+            }),
+            Page::History => crate::screens::history::view(&self.history),
+            Page::Settings => crate::screens::settings::view(&self.settings),
             Page::Home => crate::screens::home::view(self.sidebar_collapsed),
-            Page::Reports => crate::screens::reports::view(
-                self.sidebar_collapsed,
-                self.report_loading,
-                self.report_error.as_deref(),
-                &self.report_series,
-                self.report_state_loading,
-                self.report_state_error.as_deref(),
-                &self.report_state_counts,
-                self.report_employee_loading,
-                self.report_employee_error.as_deref(),
-                &self.report_employee_counts,
-                self.report_heatmap_loading,
-                self.report_heatmap_error.as_deref(),
-                self.report_heatmap.as_ref(),
-                self.report_radar_loading,
-                self.report_radar_error.as_deref(),
-                &self.report_radar_metrics,
-                self.report_breach_loading,
-                self.report_breach_error.as_deref(),
-                self.report_breach_rate,
-            ),
+            Page::Reports => scrollable(column![
+                crate::screens::reports::view(
+                    self.sidebar_collapsed,
+                    self.report_loading,
+                    self.report_error.as_deref(),
+                    &self.report_series,
+                    self.report_state_loading,
+                    self.report_state_error.as_deref(),
+                    &self.report_state_counts,
+                    self.report_employee_loading,
+                    self.report_employee_error.as_deref(),
+                    &self.report_employee_counts,
+                    self.report_heatmap_loading,
+                    self.report_heatmap_error.as_deref(),
+                    self.report_heatmap.as_ref(),
+                    self.report_radar_loading,
+                    self.report_radar_error.as_deref(),
+                    &self.report_radar_metrics,
+                    self.report_breach_loading,
+                    self.report_breach_error.as_deref(),
+                    self.report_breach_rate,
+                ),
+                crate::screens::reports::custom_field_view(&self.custom_fields),
+                crate::screens::reports::state_time_view(&self.state_time),
+                crate::screens::reports::ticket_load_view(&self.ticket_load),
+            ])
+            .into(),
         }
     }
 
-    fn start_import_with_path(&mut self, path: std::path::PathBuf) -> Task<Message> {
-        self.import_state.status = ImportStatus::Loading;
-        self.import_state.set_message("Reading CSV...".to_owned());
-        Task::perform(crate::importing::read_new_hire_csv(path), Message::ImportPrepared)
+    fn load_custom_field_report(&mut self) -> Task<Message> {
+        let Some(group_by) = self.custom_fields.group_by.clone() else {
+            self.custom_fields.points.clear();
+            return Task::none();
+        };
+
+        self.custom_fields.loading = true;
+        self.custom_fields.error = None;
+        Task::perform(
+            crate::reports::custom_field_bar::CustomFieldBarReport::load(
+                self.db_path.clone(),
+                group_by,
+                self.custom_fields.filter(),
+            ),
+            Message::ReportCustomFieldLoaded,
+        )
     }
 
-    fn process_next_record(&mut self) -> Task<Message> {
-        if let Some(record) = self.import_queue.pop_front() {
-            let db_path = self.db_path.clone();
-            return Task::perform(
-                crate::importing::process_record(db_path, record),
-                Message::ProcessedRecord,
-            );
-        }
+    fn load_history(&mut self) -> Task<Message> {
+        self.history.loading = true;
+        self.history.error = None;
+        Task::perform(
+            crate::importing::history::load_runs(self.db_path.clone()),
+            Message::HistoryLoaded,
+        )
+    }
 
-        if self.pending_duplicates.is_empty() {
-            self.import_state.status = ImportStatus::Done;
-            self.import_state
-                .set_message("Import complete.".to_owned());
-        } else {
-            self.import_state.status = ImportStatus::AwaitingDecision;
-            self.import_state.set_message(format!(
-                "{} duplicate record(s) need review.",
-                self.pending_duplicates.len()
-            ));
+    fn load_timeline(&mut self, number: i64) -> Task<Message> {
+        self.history.timeline_error = None;
+        Task::perform(
+            crate::importing::history::load_timeline(self.db_path.clone(), number),
+            Message::TimelineLoaded,
+        )
+    }
+
+    fn queue_picked_files(&mut self, kind: ImportKind) -> Task<Message> {
+        let mut supported = vec!["csv"];
+        supported.extend(crate::importing::spreadsheet::EXTENSIONS);
+        let files = rfd::FileDialog::new()
+            .add_filter("CSV or Excel", &supported)
+            .add_filter("CSV", &["csv"])
+            .add_filter("Excel", &crate::importing::spreadsheet::EXTENSIONS)
+            .pick_files();
+
+        for path in files.unwrap_or_default() {
+            self.import_queue.push(path, kind);
         }
+
         Task::none()
     }
 
-    fn handle_import_step(&mut self, step: ImportStep) -> Task<Message> {
-        match step {
-            ImportStep::Inserted => {
-                self.import_state.inserted += 1;
-                self.process_next_record()
-            }
-            ImportStep::Updated => {
-                self.import_state.updated += 1;
-                self.process_next_decision()
+    fn handle_profile_message<P: AppProfile>(
+        &mut self,
+        message: ProfileMessage<P>,
+    ) -> Task<Message> {
+        match message {
+            ProfileMessage::HeadersLoaded(result) => match result {
+                Ok(prepared) => {
+                    self.detected_format = prepared.csv_format;
+                    if prepared.needs_sheet() {
+                        self.import_state.status = ImportStatus::AwaitingSheet;
+                        self.import_state.set_message(format!(
+                            "{} has {} sheets. Choose the one to import.",
+                            prepared.source.file_name,
+                            prepared.sheets.len()
+                        ));
+                        P::import(self).sheet_pending = Some(prepared);
+                        return Task::none();
+                    }
+
+                    if let Some(run) = &prepared.previous_run {
+                        self.import_state.status = ImportStatus::AwaitingConfirmation;
+                        self.import_state.set_message(format!(
+                            "This file was already imported on {} (run #{}, {}).",
+                            run.imported_at_display(),
+                            run.id,
+                            run.file_name
+                        ));
+                        P::import(self).reimport_pending = Some(prepared);
+                        return Task::none();
+                    }
+
+                    self.continue_import(prepared)
+                }
+                Err(err) => {
+                    self.import_state.set_error(err);
+                    Task::none()
+                }
+            },
+            ProfileMessage::SheetSelected(sheet) => match P::import(self).sheet_pending.take() {
+                Some(prepared) => {
+                    self.import_state.status = ImportStatus::Loading;
+                    self.import_state
+                        .set_message(format!("Reading sheet \"{sheet}\"..."));
+                    Task::perform(
+                        crate::importing::prepare_import::<P>(
+                            self.db_path.clone(),
+                            prepared.path,
+                            Some(sheet),
+                            self.csv_format,
+                        ),
+                        |result| P::message(ProfileMessage::HeadersLoaded(result)),
+                    )
+                }
+                None => Task::none(),
+            },
+            ProfileMessage::CancelSheet => {
+                P::import(self).sheet_pending = None;
+                self.import_state.status = ImportStatus::Idle;
+                self.import_state.set_message("Import cancelled.".to_owned());
+                Task::none()
+            }
+            ProfileMessage::ConfirmReimport => match P::import(self).reimport_pending.take() {
+                Some(prepared) => self.continue_import(prepared),
+                None => Task::none(),
+            },
+            ProfileMessage::CancelReimport => {
+                P::import(self).reimport_pending = None;
+                self.import_state.status = ImportStatus::Idle;
+                self.import_state.set_message("Import cancelled.".to_owned());
+                Task::none()
+            }
+            ProfileMessage::MappingProfileSelected(name) => {
+                if let Some(editor) = P::import(self).mapping_editor.as_mut()
+                    && let Some(profile) = editor
+                        .profiles
+                        .iter()
+                        .find(|mapping| mapping.name == name)
+                        .cloned()
+                {
+                    editor.select_profile(profile);
+                }
+                Task::none()
+            }
+            ProfileMessage::MappingHeaderSelected(field, choice) => {
+                if let Some(editor) = P::import(self).mapping_editor.as_mut() {
+                    editor.mapping.set_header(field, choice.0);
+                    editor.overwrite_confirmed = false;
+                }
+                Task::none()
+            }
+            ProfileMessage::MappingNameChanged(name) => {
+                if let Some(editor) = P::import(self).mapping_editor.as_mut() {
+                    editor.profile_name = name;
+                    editor.overwrite_confirmed = false;
+                }
+                Task::none()
+            }
+            ProfileMessage::ConfirmMapping => {
+                let Some(mut editor) = P::import(self).mapping_editor.take() else {
+                    return Task::none();
+                };
+
+                if let Some(err) = editor.save_error() {
+                    self.import_state.set_message(err);
+                    P::import(self).mapping_editor = Some(editor);
+                    return Task::none();
+                }
+                let dropped = editor.dropped_fields();
+                if !dropped.is_empty() && !editor.overwrite_confirmed {
+                    let fields = dropped
+                        .iter()
+                        .map(|field| field.header())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    self.import_state.set_message(format!(
+                        "Saving replaces profile \"{}\" and drops {fields}. \
+                         Press Import again to replace it, or change the name.",
+                        editor.profile_name.trim()
+                    ));
+                    editor.overwrite_confirmed = true;
+                    P::import(self).mapping_editor = Some(editor);
+                    return Task::none();
+                }
+
+                let mut mapping = editor.mapping;
+                let name = editor.profile_name.trim().to_owned();
+                if name.is_empty() {
+                    return self.read_with_mapping(editor.path, mapping);
+                }
+
+                mapping.name = name;
+                self.pending_import_path = Some(editor.path);
+                self.import_state.status = ImportStatus::Loading;
+                self.import_state
+                    .set_message("Saving column mapping...".to_owned());
+                Task::perform(
+                    crate::importing::mapping::save_profile(self.db_path.clone(), mapping),
+                    |result| P::message(ProfileMessage::MappingSaved(result)),
+                )
             }
-            ImportStep::SkippedUnchanged => {
-                self.import_state.skipped += 1;
-                self.process_next_record()
+            ProfileMessage::CancelMapping => {
+                P::import(self).mapping_editor = None;
+                self.import_state.status = ImportStatus::Idle;
+                self.import_state.message = None;
+                Task::none()
             }
-            ImportStep::SkippedDecision => {
-                self.import_state.skipped += 1;
+            ProfileMessage::MappingSaved(result) => match result {
+                Ok(mapping) => match self.pending_import_path.take() {
+                    Some(path) => self.read_with_mapping(path, mapping),
+                    None => Task::none(),
+                },
+                Err(err) => {
+                    self.pending_import_path = None;
+                    self.import_state.set_error(err);
+                    Task::none()
+                }
+            },
+            ProfileMessage::Prepared(result) => match result {
+                Ok(outcome) => self.handle_read_outcome::<P>(outcome),
+                Err(err) => {
+                    self.import_state.set_error(err);
+                    Task::none()
+                }
+            },
+            ProfileMessage::PreviewLoaded(result) => match result {
+                Ok(preview) => {
+                    self.import_state.status = ImportStatus::AwaitingCommit;
+                    self.import_state.set_message(format!(
+                        "{} to insert, {} unchanged, {} resolved by policy, {} would change.",
+                        preview.inserts.len(),
+                        preview.unchanged,
+                        preview.auto_resolved.len(),
+                        preview.changed.len()
+                    ));
+                    P::import(self).preview = Some(preview);
+                    Task::none()
+                }
+                Err(err) => {
+                    self.import_state.set_error(err);
+                    Task::none()
+                }
+            },
+            ProfileMessage::CommitPreview => match P::import(self).preview.take() {
+                Some(preview) => self.begin_import::<P>(preview.records, preview.decisions),
+                None => Task::none(),
+            },
+            ProfileMessage::DiscardPreview => {
+                P::import(self).preview = None;
+                self.pending_sync_cursor = None;
+                self.import_state.status = ImportStatus::Idle;
+                self.import_state
+                    .set_message("Import discarded. Nothing was written.".to_owned());
+                Task::none()
+            }
+            ProfileMessage::Progressed(event) => self.handle_import_event(event),
+            ProfileMessage::PendingDuplicatesLoaded(result) => match result {
+                Ok(entries) => self.queue_pending_duplicates(entries),
+                Err(err) => {
+                    self.import_state.set_message(err);
+                    Task::none()
+                }
+            },
+            ProfileMessage::DecideDuplicate { number, overwrite } => {
+                self.decide_duplicate::<P>(number, DuplicateDecision::from_overwrite(overwrite))
+            }
+            ProfileMessage::DecideAll { overwrite } => {
+                let import = P::import(self);
+                import.merge_selection = None;
+                while let Some(entry) = import.pending_duplicates.pop_front() {
+                    import.decision_queue.push_back((
+                        entry.run_id,
+                        entry.record,
+                        DuplicateDecision::from_overwrite(overwrite),
+                    ));
+                }
                 self.process_next_decision()
             }
-            ImportStep::Duplicate(record) => {
-                self.pending_duplicates.push_back(record.clone());
+            ProfileMessage::OpenMerge(number) => {
+                let import = P::import(self);
+                import.merge_selection = import
+                    .pending_duplicates
+                    .iter()
+                    .find(|entry| entry.summary.number == number)
+                    .map(|entry| MergeSelection::new(&entry.summary));
+                Task::none()
+            }
+            ProfileMessage::MergeFieldChosen { field, take_new } => {
+                if let Some(selection) = P::import(self).merge_selection.as_mut() {
+                    selection.choose(field, take_new);
+                }
+                Task::none()
+            }
+            ProfileMessage::ApplyMerge => match P::import(self).merge_selection.take() {
+                Some(selection) => {
+                    self.decide_duplicate::<P>(selection.number, selection.decision())
+                }
+                None => Task::none(),
+            },
+            ProfileMessage::CancelMerge => {
+                P::import(self).merge_selection = None;
+                Task::none()
+            }
+            ProfileMessage::DecisionApplied(result) => {
+                self.applying_decisions = false;
+                match result {
+                    Ok(outcome) => self.handle_decision_outcome(outcome),
+                    Err(err) => {
+                        self.import_state.set_error(err);
+                        Task::none()
+                    }
+                }
+            }
+            ProfileMessage::PoliciesLoaded(result) => {
+                let editor = P::policies(&mut self.settings);
+                editor.loading = false;
+                match result {
+                    Ok(policies) => {
+                        editor.policies = policies;
+                        editor.error = None;
+                    }
+                    Err(err) => {
+                        editor.policies.clear();
+                        editor.error = Some(err);
+                    }
+                }
+                Task::none()
+            }
+            ProfileMessage::PolicyDraftKindSelected(kind) => {
+                P::policies(&mut self.settings).set_draft_kind(kind);
+                Task::none()
+            }
+            ProfileMessage::PolicyDraftFieldAdded(field) => {
+                P::policies(&mut self.settings).add_draft_field(field);
+                Task::none()
+            }
+            ProfileMessage::PolicyDraftCleared => {
+                let editor = P::policies(&mut self.settings);
+                editor.draft_kind = None;
+                editor.draft_fields.clear();
+                Task::none()
+            }
+            ProfileMessage::AddPolicy => {
+                let editor = P::policies(&mut self.settings);
+                match editor.take_draft() {
+                    Some(policy) => {
+                        editor.policies.push(policy);
+                        self.save_policies::<P>()
+                    }
+                    None => Task::none(),
+                }
+            }
+            ProfileMessage::TogglePolicy(index, enabled) => {
+                if let Some(policy) = P::policies(&mut self.settings).policies.get_mut(index) {
+                    policy.enabled = enabled;
+                }
+                self.save_policies::<P>()
+            }
+            ProfileMessage::MovePolicyUp(index) => {
+                let policies = &mut P::policies(&mut self.settings).policies;
+                if index > 0 && index < policies.len() {
+                    policies.swap(index - 1, index);
+                }
+                self.save_policies::<P>()
+            }
+            ProfileMessage::RemovePolicy(index) => {
+                let policies = &mut P::policies(&mut self.settings).policies;
+                if index < policies.len() {
+                    policies.remove(index);
+                }
+                self.save_policies::<P>()
+            }
+            ProfileMessage::PoliciesSaved(result) => {
+                match result {
+                    Ok(policies) => {
+                        P::policies(&mut self.settings).policies = policies;
+                        self.settings.message = Some("Duplicate policies saved.".to_owned());
+                    }
+                    Err(err) => self.settings.message = Some(err),
+                }
+                Task::none()
+            }
+        }
+    }
+
+    fn load_policies<P: AppProfile>(&mut self) -> Task<Message> {
+        let editor = P::policies(&mut self.settings);
+        editor.loading = true;
+        editor.error = None;
+        Task::perform(
+            crate::importing::policy::load_policy_list::<P>(self.db_path.clone()),
+            |result| P::message(ProfileMessage::PoliciesLoaded(result)),
+        )
+    }
+
+    fn save_policies<P: AppProfile>(&mut self) -> Task<Message> {
+        self.settings.message = Some("Saving duplicate policies...".to_owned());
+        Task::perform(
+            crate::importing::policy::save_policies::<P>(
+                self.db_path.clone(),
+                P::policies(&mut self.settings).policies.clone(),
+            ),
+            |result| P::message(ProfileMessage::PoliciesSaved(result)),
+        )
+    }
+
+    fn load_pending_duplicates<P: AppProfile>(&self) -> Task<Message> {
+        Task::perform(
+            crate::importing::load_pending_duplicates::<P>(self.db_path.clone()),
+            |result| P::message(ProfileMessage::PendingDuplicatesLoaded(result)),
+        )
+    }
+
+    fn reset_import_setup(&mut self) {
+        self.new_hires.reset_setup();
+        self.incidents.reset_setup();
+        self.pending_sync_cursor = None;
+        self.import_state.rejected_headers.clear();
+        self.import_state.rejected_rows.clear();
+    }
+
+    fn pending_reviews(&self) -> usize {
+        self.new_hires.pending_duplicates.len() + self.incidents.pending_duplicates.len()
+    }
+
+    fn has_queued_decisions(&self) -> bool {
+        !self.new_hires.decision_queue.is_empty() || !self.incidents.decision_queue.is_empty()
+    }
+
+    fn queue_pending_duplicates<P: AppProfile>(
+        &mut self,
+        entries: Vec<DuplicateEntry<P>>,
+    ) -> Task<Message> {
+        let (added, repeats) = crate::importing::queue::merge_pending(
+            &mut P::import(self).pending_duplicates,
+            entries,
+        );
+
+        if added > 0 && self.import_state.status.is_ready() {
+            self.import_state.status = ImportStatus::AwaitingDecision;
+            self.import_state.set_message(format!(
+                "{added} duplicate record(s) from earlier imports need review."
+            ));
+        }
+
+        self.queue_repeat_decisions(repeats)
+    }
+
+    fn queue_repeat_decisions<P: AppProfile>(
+        &mut self,
+        repeats: Vec<RepeatDecision<P>>,
+    ) -> Task<Message> {
+        if repeats.is_empty() {
+            return Task::none();
+        }
+
+        for repeat in repeats {
+            self.import_state.auto_decisions.push(repeat.auto);
+            P::import(self).decision_queue.push_back((
+                repeat.run_id,
+                repeat.record,
+                repeat.decision,
+            ));
+        }
+        self.process_next_decision()
+    }
+
+    fn scan_watch_folder(&mut self) -> Task<Message> {
+        let Some(folder) = self.settings.watch_folder.clone() else {
+            return Task::none();
+        };
+        if self.watch_scanning
+            || self.applying_decisions
+            || self.import_queue.is_busy()
+            || !self.import_state.status.is_ready()
+        {
+            return Task::none();
+        }
+
+        self.watch_scanning = true;
+        Task::perform(
+            crate::importing::watch::import_new_files(self.db_path.clone(), folder),
+            Message::WatchFolderScanned,
+        )
+    }
+
+    /// Pending reviews do not hold up the queue; a watched folder import does.
+    fn start_next_queued_file(&mut self) -> Task<Message> {
+        let status = self.import_state.status;
+        let idle = status.is_ready() || status == ImportStatus::AwaitingDecision;
+        if !idle || self.applying_decisions || self.watch_scanning || self.has_queued_decisions() {
+            return Task::none();
+        }
+
+        match self.import_queue.start_next() {
+            Some((path, kind)) => self.start_import_with_path(path, kind),
+            None => Task::none(),
+        }
+    }
+
+    fn settle_queued_file(&mut self) {
+        if !self.import_queue.is_active() || self.import_streaming {
+            return;
+        }
+
+        let status = match self.import_state.status {
+            ImportStatus::Idle => QueuedStatus::Cancelled,
+            ImportStatus::Error => QueuedStatus::Failed(
+                self.import_state
+                    .message
+                    .clone()
+                    .unwrap_or_else(|| "Import failed.".to_owned()),
+            ),
+            ImportStatus::Done => QueuedStatus::Imported {
+                inserted: 0,
+                updated: 0,
+                skipped: 0,
+                to_review: 0,
+            },
+            _ => return,
+        };
+        self.import_queue.finish(status);
+    }
+
+    fn start_import_with_path(&mut self, path: PathBuf, kind: ImportKind) -> Task<Message> {
+        match kind {
+            ImportKind::NewHires => self.prepare_file::<NewHireProfile>(path),
+            ImportKind::Incidents => self.prepare_file::<IncidentProfile>(path),
+        }
+    }
+
+    fn prepare_file<P: AppProfile>(&mut self, path: PathBuf) -> Task<Message> {
+        self.reset_import_setup();
+        self.import_state.status = ImportStatus::Loading;
+        self.import_state.set_message("Reading file headers...".to_owned());
+        Task::perform(
+            crate::importing::prepare_import::<P>(
+                self.db_path.clone(),
+                path,
+                None,
+                self.csv_format,
+            ),
+            |result| P::message(ProfileMessage::HeadersLoaded(result)),
+        )
+    }
+
+    fn reread_unmapped_csv(&mut self) -> Task<Message> {
+        let new_hires = self.new_hires.mapping_editor.as_ref();
+        let incidents = self.incidents.mapping_editor.as_ref();
+        let unmapped = [
+            (ImportKind::NewHires, new_hires.map(|editor| &editor.path)),
+            (ImportKind::Incidents, incidents.map(|editor| &editor.path)),
+        ]
+        .into_iter()
+        .find_map(|(kind, path)| {
+            path.filter(|path| !crate::importing::spreadsheet::is_spreadsheet(path))
+                .map(|path| (path.clone(), kind))
+        });
+
+        match unmapped {
+            Some((path, kind)) => self.start_import_with_path(path, kind),
+            None => Task::none(),
+        }
+    }
+
+    fn continue_import<P: AppProfile>(&mut self, prepared: PreparedImport<P>) -> Task<Message> {
+        self.import_source = Some(prepared.source.clone());
+        self.import_sheet = prepared.sheet.clone();
+
+        if let Some(mapping) = prepared.matching_profile().cloned() {
+            return self.read_with_mapping(prepared.path, mapping);
+        }
+
+        P::import(self).mapping_editor = Some(MappingEditor::new(
+            prepared.path,
+            prepared.headers,
+            prepared.profiles,
+        ));
+        self.import_state.status = ImportStatus::AwaitingMapping;
+        self.import_state
+            .set_message("No column mapping profile matches this file.".to_owned());
+        Task::none()
+    }
+
+    fn read_options(&self) -> CsvReadOptions {
+        CsvReadOptions {
+            lenient: self.lenient_import,
+            format: self.csv_format,
+            timestamps: self.settings.timestamps.clone(),
+        }
+    }
+
+    /// Only a CSV imported without a preview is streamed.
+    fn read_with_mapping<P: AppProfile>(
+        &mut self,
+        path: PathBuf,
+        mapping: ColumnMapping<P>,
+    ) -> Task<Message> {
+        self.import_state.status = ImportStatus::Loading;
+        self.import_state.set_message(format!(
+            "Reading {} with the \"{}\" column mapping...",
+            if self.import_sheet.is_some() { "sheet" } else { "CSV" },
+            mapping.name
+        ));
+        let options = self.read_options();
+
+        match self.import_sheet.clone() {
+            Some(sheet) => Task::perform(
+                crate::importing::spreadsheet::read_mapped_sheet(path, sheet, mapping, options),
+                |result| P::message(ProfileMessage::Prepared(result)),
+            ),
+            None if !self.preview_enabled => self.stream_csv(path, mapping, options),
+            None => Task::perform(
+                crate::importing::read_mapped_csv(path, mapping, options),
+                |result| P::message(ProfileMessage::Prepared(result)),
+            ),
+        }
+    }
+
+    fn handle_read_outcome<P: AppProfile>(
+        &mut self,
+        outcome: CsvReadOutcome<P::Record>,
+    ) -> Task<Message> {
+        self.import_state.rejected_headers = outcome.headers;
+        self.import_state.rejected_rows = outcome.rejected;
+        let records = outcome.records;
+
+        if !self.preview_enabled || records.is_empty() {
+            return self.begin_import::<P>(records, PresetDecisions::new());
+        }
+
+        self.import_state.status = ImportStatus::Loading;
+        self.import_state
+            .set_message(format!("Previewing {} records...", records.len()));
+        Task::perform(
+            crate::importing::preview_records::<P>(
+                self.db_path.clone(),
+                records,
+                self.import_queue.newer_versions(),
+            ),
+            |result| P::message(ProfileMessage::PreviewLoaded(result)),
+        )
+    }
+
+    fn begin_import<P: AppProfile>(
+        &mut self,
+        records: Vec<P::Record>,
+        decisions: PresetDecisions<P::Field>,
+    ) -> Task<Message> {
+        let Some(source) = self.import_source.clone() else {
+            self.import_state
+                .set_error("No import source selected.".to_owned());
+            return Task::none();
+        };
+
+        let total = records.len();
+        if total == 0 {
+            self.import_state.start(total);
+            self.import_state.status = ImportStatus::Done;
+            self.import_state
+                .set_message("No data rows found.".to_owned());
+            return Task::none();
+        }
+
+        let cancel = self.start_writing::<P>(total);
+        self.import_state.set_message("Processing records...".to_owned());
+        let mut presets = decisions;
+        presets.extend(self.import_queue.newer_versions());
+        Task::run(
+            crate::importing::import_records::<P>(
+                self.db_path.clone(),
+                source,
+                records,
+                presets,
+                cancel,
+            ),
+            |event| P::message(ProfileMessage::Progressed(event)),
+        )
+    }
+
+    fn stream_csv<P: AppProfile>(
+        &mut self,
+        path: PathBuf,
+        mapping: ColumnMapping<P>,
+        options: CsvReadOptions,
+    ) -> Task<Message> {
+        let Some(source) = self.import_source.clone() else {
+            self.import_state
+                .set_error("No import source selected.".to_owned());
+            return Task::none();
+        };
+
+        let cancel = self.start_writing::<P>(0);
+        self.import_state.set_message(format!(
+            "Reading CSV with the \"{}\" column mapping...",
+            mapping.name
+        ));
+        Task::run(
+            crate::importing::import_mapped_csv(
+                self.db_path.clone(),
+                path,
+                mapping,
+                options,
+                source,
+                self.import_queue.newer_versions(),
+                cancel,
+            ),
+            |event| P::message(ProfileMessage::Progressed(event)),
+        )
+    }
+
+    fn start_writing<P: ImportProfile>(&mut self, total: usize) -> ImportCancel {
+        self.missing_tickets.clear();
+        // Rows rejected while reading still name tickets the export holds.
+        let snapshot = P::SOFT_DELETES && self.full_snapshot && self.pending_sync_cursor.is_none();
+        self.snapshot_numbers =
+            snapshot.then(|| rejected_numbers(&self.import_state.rejected_rows));
+        self.streaming_run = None;
+        self.import_state.start(total);
+        self.import_streaming = true;
+        let cancel = ImportCancel::default();
+        self.import_cancel = Some(cancel.clone());
+        cancel
+    }
+
+    fn handle_import_event<P: AppProfile>(&mut self, event: ImportEvent<P>) -> Task<Message> {
+        match event {
+            ImportEvent::Started { headers, total } => {
+                self.import_state.rejected_headers = headers;
+                self.import_state.start(total);
                 self.import_state
+                    .set_message("Processing records...".to_owned());
+            }
+            ImportEvent::Progress(progress) => {
+                self.import_state.apply_progress(&progress);
+                self.import_queue.track_numbers(&progress.numbers);
+                if let Some(numbers) = self.snapshot_numbers.as_mut() {
+                    numbers.extend(&progress.numbers);
+                    numbers.extend(rejected_numbers(&progress.rejected));
+                }
+                if let Some(entry) = progress.duplicates.first() {
+                    self.streaming_run = Some(entry.run_id);
+                }
+                P::import(self)
                     .pending_duplicates
-                    .push(record.summary.clone());
-                self.process_next_record()
+                    .extend(progress.duplicates);
+                self.import_state.set_message(format!(
+                    "Processed {} of {} records...",
+                    self.import_state.processed, self.import_state.total
+                ));
             }
+            ImportEvent::Finished(Ok(run_id)) => return self.finish_import::<P>(run_id, false),
+            ImportEvent::Cancelled(run_id) => return self.finish_import::<P>(run_id, true),
+            ImportEvent::Finished(Err(err)) => {
+                self.import_streaming = false;
+                self.import_cancel = None;
+                self.pending_sync_cursor = None;
+                self.snapshot_numbers = None;
+                if let Some(run_id) = self.streaming_run.take() {
+                    let import = P::import(self);
+                    import.pending_duplicates.retain(|entry| entry.run_id != run_id);
+                    import
+                        .decision_queue
+                        .retain(|(decision_run, _, _)| *decision_run != run_id);
+                }
+                self.import_state.roll_back();
+                self.import_state
+                    .set_error(format!("Import rolled back: {err}"));
+            }
+        }
+
+        Task::none()
+    }
+
+    fn finish_import<P: AppProfile>(&mut self, run_id: i64, cancelled: bool) -> Task<Message> {
+        self.import_streaming = false;
+        self.import_cancel = None;
+        let mut pending = std::mem::take(&mut P::import(self).pending_duplicates);
+        let repeats = self.import_queue.settle_repeats(run_id, &mut pending);
+        P::import(self).pending_duplicates = pending;
+        if self.import_queue.is_active() {
+            let (mut updated, mut skipped) =
+                (self.import_state.updated, self.import_state.skipped);
+            for repeat in repeats.iter().filter(|repeat| repeat.run_id == run_id) {
+                match repeat.decision {
+                    DuplicateDecision::Skip => skipped += 1,
+                    _ => updated += 1,
+                }
+            }
+            let inserted = self.import_state.inserted;
+            let to_review = P::import(self)
+                .pending_duplicates
+                .iter()
+                .filter(|entry| entry.run_id == run_id)
+                .count();
+            self.import_queue.finish(if cancelled {
+                QueuedStatus::PartlyImported {
+                    inserted,
+                    updated,
+                    skipped,
+                    to_review,
+                }
+            } else {
+                QueuedStatus::Imported {
+                    inserted,
+                    updated,
+                    skipped,
+                    to_review,
+                }
+            });
+        }
+
+        // A cancelled import neither moves the sync cursor nor tells which
+        // tickets a full snapshot is missing.
+        if let Some(cursor) = self.pending_sync_cursor.take().filter(|_| !cancelled) {
+            self.held_sync_cursor = Some((run_id, cursor));
+        }
+        let snapshot = self.snapshot_numbers.take().filter(|_| !cancelled);
+        self.missing_run = snapshot.as_ref().map(|_| run_id);
+        let find_missing = match snapshot {
+            Some(numbers) => Task::perform(
+                crate::importing::snapshot::find_missing(self.db_path.clone(), numbers),
+                Message::MissingTicketsLoaded,
+            ),
+            None => Task::none(),
+        };
+
+        let cancel_message = cancelled.then(|| {
+            format!(
+                "Import cancelled after {} of {} records; those were kept.",
+                self.import_state.processed, self.import_state.total
+            )
+        });
+        if self.pending_reviews() == 0 {
+            self.import_state.status = ImportStatus::Done;
+            // A streamed file may turn out to have no data rows.
+            let done = if self.import_state.total == 0 {
+                "No data rows found."
+            } else {
+                "Import complete."
+            };
+            self.import_state
+                .set_message(cancel_message.unwrap_or_else(|| done.to_owned()));
+        } else {
+            self.import_state.status = ImportStatus::AwaitingDecision;
+            let review = format!(
+                "{} duplicate record(s) need review.",
+                self.pending_reviews()
+            );
+            self.import_state.set_message(match cancel_message {
+                Some(cancelled) => format!("{cancelled} {review}"),
+                None => review,
+            });
+        }
+        let decide_repeats = self.queue_repeat_decisions(repeats);
+        Task::batch([decide_repeats, self.release_sync_cursor(), find_missing])
+    }
+
+    /// Until then the stored cursor stays, so an interrupted sync fetches those
+    /// tickets again.
+    fn release_sync_cursor(&mut self) -> Task<Message> {
+        let Some((run_id, _)) = self.held_sync_cursor else {
+            return Task::none();
+        };
+        if self.applying_decisions || self.new_hires.waits_on(run_id) {
+            return Task::none();
+        }
+
+        match self.held_sync_cursor.take() {
+            Some((_, cursor)) => Task::perform(
+                crate::importing::api::save_sync_cursor(self.db_path.clone(), Some(cursor)),
+                Message::SyncCursorSaved,
+            ),
+            None => Task::none(),
         }
     }
 
+    fn handle_decision_outcome(&mut self, outcome: DecisionOutcome) -> Task<Message> {
+        self.import_state.updated += outcome.updated;
+        self.import_state.skipped += outcome.skipped;
+        self.process_next_decision()
+    }
+
+    fn decide_duplicate<P: AppProfile>(
+        &mut self,
+        number: i64,
+        decision: DuplicateDecision<P::Field>,
+    ) -> Task<Message> {
+        let import = P::import(self);
+        if import
+            .merge_selection
+            .as_ref()
+            .is_some_and(|selection| selection.number == number)
+        {
+            import.merge_selection = None;
+        }
+
+        if let Some(index) = import
+            .pending_duplicates
+            .iter()
+            .position(|entry| P::key(&entry.record) == number)
+            && let Some(entry) = import.pending_duplicates.remove(index)
+        {
+            import
+                .decision_queue
+                .push_back((entry.run_id, entry.record, decision));
+            return self.process_next_decision();
+        }
+
+        Task::none()
+    }
+
     fn process_next_decision(&mut self) -> Task<Message> {
-        if let Some((record, overwrite)) = self.decision_queue.pop_front() {
-            self.import_state.status = ImportStatus::Importing;
-            let db_path = self.db_path.clone();
-            return Task::perform(
-                crate::importing::apply_duplicate_decision(db_path, record, overwrite),
-                Message::DecisionApplied,
+        if self.applying_decisions || self.import_streaming {
+            return Task::none();
+        }
+
+        // Another queued file may be waiting on a sheet, mapping or preview;
+        // decisions are still applied but its status is left alone.
+        let status = self.import_state.status;
+        let in_setup = !status.is_ready()
+            && !matches!(
+                status,
+                ImportStatus::Importing | ImportStatus::AwaitingDecision
             );
+
+        let apply = self
+            .apply_queued_decisions::<NewHireProfile>()
+            .or_else(|| self.apply_queued_decisions::<IncidentProfile>());
+        if let Some(apply) = apply {
+            if !in_setup {
+                self.import_state.status = ImportStatus::Importing;
+            }
+            self.applying_decisions = true;
+            return apply;
+        }
+
+        let save_cursor = self.release_sync_cursor();
+        if in_setup {
+            return save_cursor;
         }
 
-        if self.pending_duplicates.is_empty() {
+        if self.pending_reviews() == 0 {
             self.import_state.status = ImportStatus::Done;
             self.import_state
                 .set_message("Import complete.".to_owned());
+        } else {
+            self.import_state.status = ImportStatus::AwaitingDecision;
         }
 
-        Task::none()
+        save_cursor
     }
+
+    fn apply_queued_decisions<P: AppProfile>(&mut self) -> Option<Task<Message>> {
+        let import = P::import(self);
+        if import.decision_queue.is_empty() {
+            return None;
+        }
+
+        let decisions = import.decision_queue.drain(..).collect();
+        Some(Task::perform(
+            crate::importing::apply_duplicate_decisions::<P>(self.db_path.clone(), decisions),
+            |result| P::message(ProfileMessage::DecisionApplied(result)),
+        ))
+    }
+}
+
+trait AppProfile: ScreenProfile {
+    fn import(app: &mut App) -> &mut ProfileImport<Self>;
+
+    fn policies(settings: &mut SettingsState) -> &mut PolicyEditor<Self::Field>;
+}
+
+impl AppProfile for NewHireProfile {
+    fn import(app: &mut App) -> &mut ProfileImport<Self> {
+        &mut app.new_hires
+    }
+
+    fn policies(settings: &mut SettingsState) -> &mut PolicyEditor<Self::Field> {
+        &mut settings.new_hire_policies
+    }
+}
+
+impl AppProfile for IncidentProfile {
+    fn import(app: &mut App) -> &mut ProfileImport<Self> {
+        &mut app.incidents
+    }
+
+    fn policies(settings: &mut SettingsState) -> &mut PolicyEditor<Self::Field> {
+        &mut settings.incident_policies
+    }
+}
+
+fn rejected_numbers(rows: &[RejectedRow]) -> Vec<i64> {
+    rows.iter().filter_map(|row| row.number).collect()
 }
@@ -125,7 +125,7 @@ impl canvas::Program<crate::message::Message> for CircleChart {
                 color: palette.background.base.text,
                 size: 20.0.into(),
                 align_x: iced::alignment::Horizontal::Center.into(),
-                align_y: iced::alignment::Vertical::Center.into(),
+                align_y: iced::alignment::Vertical::Center,
                 ..Text::default()
             });
 
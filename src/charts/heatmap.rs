@@ -183,7 +183,7 @@ impl canvas::Program<crate::message::Message> for HeatmapChart {
                     color: palette.background.base.text,
                     size: 11.0.into(),
                     align_x: iced::alignment::Horizontal::Right.into(),
-                    align_y: iced::alignment::Vertical::Center.into(),
+                    align_y: iced::alignment::Vertical::Center,
                     ..Text::default()
                 });
             }
@@ -1,3 +1,7 @@
+// Chart widgets keep builder options and model types the reports do not use
+// yet.
+#![allow(dead_code)]
+
 pub mod bar;
 pub mod circle;
 pub mod heatmap;
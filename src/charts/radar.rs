@@ -128,7 +128,7 @@ impl canvas::Program<crate::message::Message> for RadarChart {
                     color: palette.background.base.text,
                     size: 11.0.into(),
                     align_x: iced::alignment::Horizontal::Center.into(),
-                    align_y: iced::alignment::Vertical::Center.into(),
+                    align_y: iced::alignment::Vertical::Center,
                     ..Text::default()
                 });
             }
@@ -179,19 +179,19 @@ impl canvas::Program<crate::message::Message> for RadarChart {
             let step = std::f32::consts::TAU / axis_count as f32;
             let axis_index = (normalized / step).round() as usize % axis_count;
 
-            if let Some(series) = self.series.first() {
-                if axis_index < series.values.len() {
-                    overlay.fill_text(Text {
-                        content: format!(
-                            "{}: {:.2}",
-                            self.axes.labels[axis_index], series.values[axis_index]
-                        ),
-                        position: Point::new(cursor_pos.x + 8.0, cursor_pos.y - 8.0),
-                        color: palette.background.base.text,
-                        size: 12.0.into(),
-                        ..Text::default()
-                    });
-                }
+            if let Some(series) = self.series.first()
+                && axis_index < series.values.len()
+            {
+                overlay.fill_text(Text {
+                    content: format!(
+                        "{}: {:.2}",
+                        self.axes.labels[axis_index], series.values[axis_index]
+                    ),
+                    position: Point::new(cursor_pos.x + 8.0, cursor_pos.y - 8.0),
+                    color: palette.background.base.text,
+                    size: 12.0.into(),
+                    ..Text::default()
+                });
             }
 
             geometries.push(overlay.into_geometry());
@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use sqlx::Row;
+
+use crate::importing::incidents::OPEN_CONDITION;
+use crate::importing::open_pool;
+
+pub async fn load_series(db_path: PathBuf) -> Result<Vec<(String, f32)>, String> {
+    let pool = open_pool(&db_path).await?;
+
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT COALESCE(category, 'Uncategorized') AS label, COUNT(*) AS count
+        FROM incidents
+        WHERE {OPEN_CONDITION}
+        GROUP BY label
+        ORDER BY count DESC
+        LIMIT 10
+        "#
+    ))
+    .fetch_all(&pool)
+    .await
+    .map_err(|err| format!("Failed to load backlog: {err}"))?;
+
+    let points = rows
+        .into_iter()
+        .map(|row| {
+            let label: String = row.get("label");
+            let count: i64 = row.get("count");
+            (label, count as f32)
+        })
+        .collect();
+
+    Ok(points)
+}
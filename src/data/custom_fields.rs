@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use sqlx::Row;
+
+use crate::importing::open_pool;
+use crate::importing::snapshot::ACTIVE_CONDITION;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomFilter {
+    pub key: String,
+    pub value: String,
+}
+
+pub async fn load_keys(db_path: PathBuf) -> Result<Vec<String>, String> {
+    let pool = open_pool(&db_path).await?;
+
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT DISTINCT extra.key AS key
+        FROM new_hire_metrics, json_each(new_hire_metrics.extras) AS extra
+        WHERE new_hire_metrics.extras IS NOT NULL AND {ACTIVE_CONDITION}
+        ORDER BY key
+        "#
+    ))
+    .fetch_all(&pool)
+    .await
+    .map_err(|err| format!("Failed to load custom fields: {err}"))?;
+
+    Ok(rows.into_iter().map(|row| row.get("key")).collect())
+}
+
+pub async fn load_series(
+    db_path: PathBuf,
+    group_by: String,
+    filter: Option<CustomFilter>,
+) -> Result<Vec<(String, f32)>, String> {
+    let pool = open_pool(&db_path).await?;
+    let (filter_key, filter_value) = match filter {
+        Some(filter) => (Some(filter.key), Some(filter.value)),
+        None => (None, None),
+    };
+
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT
+            COALESCE(
+                (SELECT value FROM json_each(metrics.extras) WHERE key = ?1),
+                'Unknown'
+            ) AS label,
+            COUNT(*) AS count
+        FROM new_hire_metrics AS metrics
+        WHERE {ACTIVE_CONDITION} AND (?2 IS NULL OR EXISTS (
+            SELECT 1 FROM json_each(metrics.extras) WHERE key = ?2 AND value = ?3
+        ))
+        GROUP BY label
+        ORDER BY count DESC
+        LIMIT 10
+        "#
+    ))
+    .bind(group_by)
+    .bind(filter_key)
+    .bind(filter_value)
+    .fetch_all(&pool)
+    .await
+    .map_err(|err| format!("Failed to load custom field counts: {err}"))?;
+
+    let points = rows
+        .into_iter()
+        .map(|row| {
+            let label: String = row.get("label");
+            let count: i64 = row.get("count");
+            (label, count as f32)
+        })
+        .collect();
+
+    Ok(points)
+}
@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use sqlx::Row;
+
+use crate::importing::open_pool;
+
+const DAY_LIMIT: i64 = 30;
+
+pub async fn load_series(db_path: PathBuf) -> Result<Vec<(String, f32, f32)>, String> {
+    let pool = open_pool(&db_path).await?;
+
+    let rows = sqlx::query(
+        r#"
+        WITH activity AS (
+            SELECT date(created_at) AS day, 1 AS created, 0 AS closed
+            FROM incidents
+            WHERE date(created_at) IS NOT NULL
+            UNION ALL
+            SELECT date(resolved_at) AS day, 0 AS created, 1 AS closed
+            FROM incidents
+            WHERE date(resolved_at) IS NOT NULL
+        )
+        SELECT day, SUM(created) AS created, SUM(closed) AS closed
+        FROM activity
+        GROUP BY day
+        ORDER BY day DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(DAY_LIMIT)
+    .fetch_all(&pool)
+    .await
+    .map_err(|err| format!("Failed to load daily ticket counts: {err}"))?;
+
+    let mut points: Vec<(String, f32, f32)> = rows
+        .into_iter()
+        .map(|row| {
+            let day: String = row.get("day");
+            let created: i64 = row.get("created");
+            let closed: i64 = row.get("closed");
+            (day, created as f32, closed as f32)
+        })
+        .collect();
+    points.reverse();
+
+    Ok(points)
+}
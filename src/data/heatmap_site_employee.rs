@@ -3,24 +3,26 @@ use std::path::PathBuf;
 
 use sqlx::Row;
 
-use crate::importing::{ensure_schema, open_pool};
+use crate::importing::open_pool;
+use crate::importing::snapshot::ACTIVE_CONDITION;
 
-pub async fn load_grid(
-    db_path: PathBuf,
-) -> Result<(Vec<String>, Vec<String>, Vec<Vec<f32>>), String> {
+/// Sites (columns), employee types (rows) and the count of each cell, row by row.
+pub type SiteEmployeeGrid = (Vec<String>, Vec<String>, Vec<Vec<f32>>);
+
+pub async fn load_grid(db_path: PathBuf) -> Result<SiteEmployeeGrid, String> {
     let pool = open_pool(&db_path).await?;
-    ensure_schema(&pool).await?;
 
-    let rows = sqlx::query(
+    let rows = sqlx::query(&format!(
         r#"
         SELECT
             COALESCE(site, 'Unknown') AS site,
             COALESCE(employee_type, 'Unknown') AS employee_type,
             COUNT(*) AS count
         FROM new_hire_metrics
+        WHERE {ACTIVE_CONDITION}
         GROUP BY site, employee_type
-        "#,
-    )
+        "#
+    ))
     .fetch_all(&pool)
     .await
     .map_err(|err| format!("Failed to load heatmap data: {err}"))?;
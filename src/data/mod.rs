@@ -1,6 +1,11 @@
+pub mod backlog_by_category;
+pub mod custom_fields;
+pub mod daily_ticket_flow;
 pub mod employee_type_counts;
 pub mod heatmap_site_employee;
 pub mod lead_time;
+pub mod open_by_assignee;
 pub mod radar_metrics;
 pub mod sla_breach_rate;
 pub mod state_counts;
+pub mod state_durations;
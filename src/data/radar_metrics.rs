@@ -2,22 +2,25 @@ use std::path::PathBuf;
 
 use sqlx::Row;
 
-use crate::importing::{ensure_schema, open_pool};
+use crate::importing::open_pool;
+use crate::importing::snapshot::ACTIVE_CONDITION;
 
 pub async fn load_metrics(db_path: PathBuf) -> Result<Vec<(String, f32)>, String> {
     let pool = open_pool(&db_path).await?;
-    ensure_schema(&pool).await?;
 
-    let row = sqlx::query(
+    let row = sqlx::query(&format!(
         r#"
         SELECT
             AVG(it_lead_time_elapsed) AS avg_elapsed,
             AVG(it_lead_time_business) AS avg_business,
+            AVG(to_first_response_business_minutes) / 60.0 AS avg_first_response,
+            AVG(to_resolution_business_minutes) / 60.0 AS avg_resolution,
             SUM(CASE WHEN sla_breaches IS NOT NULL AND sla_breaches != '' THEN 1 ELSE 0 END) AS breaches,
             COUNT(*) AS total
         FROM new_hire_metrics
-        "#,
-    )
+        WHERE {ACTIVE_CONDITION}
+        "#
+    ))
     .fetch_one(&pool)
     .await
     .map_err(|err| format!("Failed to load radar metrics: {err}"))?;
@@ -28,6 +31,12 @@ pub async fn load_metrics(db_path: PathBuf) -> Result<Vec<(String, f32)>, String
     let avg_business: f32 = row
         .try_get::<f64, _>("avg_business")
         .unwrap_or(0.0) as f32;
+    let avg_first_response: f32 = row
+        .try_get::<f64, _>("avg_first_response")
+        .unwrap_or(0.0) as f32;
+    let avg_resolution: f32 = row
+        .try_get::<f64, _>("avg_resolution")
+        .unwrap_or(0.0) as f32;
     let breaches: f32 = row
         .try_get::<i64, _>("breaches")
         .unwrap_or(0) as f32;
@@ -36,6 +45,8 @@ pub async fn load_metrics(db_path: PathBuf) -> Result<Vec<(String, f32)>, String
     Ok(vec![
         ("Avg Elapsed".to_string(), avg_elapsed),
         ("Avg Business".to_string(), avg_business),
+        ("Avg First Response (h)".to_string(), avg_first_response),
+        ("Avg Resolution (h)".to_string(), avg_resolution),
         ("Breach Count".to_string(), breaches),
         ("Total Records".to_string(), total),
     ])
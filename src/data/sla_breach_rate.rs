@@ -2,20 +2,21 @@ use std::path::PathBuf;
 
 use sqlx::Row;
 
-use crate::importing::{ensure_schema, open_pool};
+use crate::importing::open_pool;
+use crate::importing::snapshot::ACTIVE_CONDITION;
 
 pub async fn load_rate(db_path: PathBuf) -> Result<(f32, f32), String> {
     let pool = open_pool(&db_path).await?;
-    ensure_schema(&pool).await?;
 
-    let row = sqlx::query(
+    let row = sqlx::query(&format!(
         r#"
         SELECT
             SUM(CASE WHEN sla_breaches IS NOT NULL AND sla_breaches != '' THEN 1 ELSE 0 END) AS breaches,
             COUNT(*) AS total
         FROM new_hire_metrics
-        "#,
-    )
+        WHERE {ACTIVE_CONDITION}
+        "#
+    ))
     .fetch_one(&pool)
     .await
     .map_err(|err| format!("Failed to load breach rate: {err}"))?;
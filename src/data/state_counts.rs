@@ -2,21 +2,22 @@ use std::path::PathBuf;
 
 use sqlx::Row;
 
-use crate::importing::{ensure_schema, open_pool};
+use crate::importing::open_pool;
+use crate::importing::snapshot::ACTIVE_CONDITION;
 
 pub async fn load_series(db_path: PathBuf) -> Result<Vec<(String, f32)>, String> {
     let pool = open_pool(&db_path).await?;
-    ensure_schema(&pool).await?;
 
-    let rows = sqlx::query(
+    let rows = sqlx::query(&format!(
         r#"
         SELECT COALESCE(state, 'Unknown') AS label, COUNT(*) AS count
         FROM new_hire_metrics
+        WHERE {ACTIVE_CONDITION}
         GROUP BY label
         ORDER BY count DESC
         LIMIT 10
-        "#,
-    )
+        "#
+    ))
     .fetch_all(&pool)
     .await
     .map_err(|err| format!("Failed to load state counts: {err}"))?;
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use sqlx::Row;
+
+use crate::importing::profile::ImportProfile;
+use crate::importing::snapshot::ACTIVE_CONDITION;
+use crate::importing::{open_pool, NewHireProfile};
+
+const MAX_GROUPS: usize = 10;
+
+/// States (columns), groups (rows) and the average hours of each cell, row by row.
+pub type StateGrid = (Vec<String>, Vec<String>, Vec<Vec<f32>>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateTimeGroup {
+    Site,
+    Assignee,
+}
+
+impl StateTimeGroup {
+    fn column(self) -> &'static str {
+        match self {
+            StateTimeGroup::Site => "site",
+            StateTimeGroup::Assignee => "assignee_name",
+        }
+    }
+}
+
+/// Only stays that ended with a later transition are counted.
+pub async fn load_grid(
+    db_path: PathBuf,
+    group: StateTimeGroup,
+) -> Result<StateGrid, String> {
+    let pool = open_pool(&db_path).await?;
+
+    let rows = sqlx::query(&format!(
+        r#"
+        WITH stays AS (
+            SELECT
+                to_state AS state,
+                COALESCE({column}, 'Unknown') AS grouping,
+                (
+                    julianday(LEAD(transitioned_at) OVER (PARTITION BY number ORDER BY id))
+                    - julianday(transitioned_at)
+                ) * 24.0 AS hours
+            FROM state_transitions
+            WHERE profile = '{profile}'
+                AND number IN (SELECT number FROM new_hire_metrics WHERE {ACTIVE_CONDITION})
+        )
+        SELECT state, grouping, AVG(hours) AS hours, COUNT(*) AS stays
+        FROM stays
+        WHERE hours >= 0
+        GROUP BY state, grouping
+        "#,
+        column = group.column(),
+        profile = NewHireProfile::NAME
+    ))
+    .fetch_all(&pool)
+    .await
+    .map_err(|err| format!("Failed to load time in state: {err}"))?;
+
+    let mut states = Vec::new();
+    let mut stays: HashMap<String, i64> = HashMap::new();
+    let mut map: HashMap<(String, String), f32> = HashMap::new();
+
+    for row in rows {
+        let state: String = row.get("state");
+        let grouping: String = row.get("grouping");
+        let hours: f64 = row.get("hours");
+        let count: i64 = row.get("stays");
+        if !states.contains(&state) {
+            states.push(state.clone());
+        }
+        *stays.entry(grouping.clone()).or_default() += count;
+        map.insert((state, grouping), hours as f32);
+    }
+
+    let mut groups: Vec<String> = stays.keys().cloned().collect();
+    groups.sort_by(|a, b| stays[b].cmp(&stays[a]).then_with(|| a.cmp(b)));
+    groups.truncate(MAX_GROUPS);
+    groups.sort();
+    states.sort();
+
+    let mut values = vec![vec![0.0; states.len()]; groups.len()];
+    for (y, grouping) in groups.iter().enumerate() {
+        for (x, state) in states.iter().enumerate() {
+            if let Some(value) = map.get(&(state.clone(), grouping.clone())) {
+                values[y][x] = *value;
+            }
+        }
+    }
+
+    Ok((states, groups, values))
+}
@@ -0,0 +1,574 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use chrono::{DateTime, FixedOffset};
+use serde_json::Value;
+
+use super::history::ImportSource;
+use super::mapping::{CellError, RecordField};
+use super::profile::build_record;
+use super::settings::{load_setting, store_setting};
+use super::timestamps::TimestampSettings;
+use super::{
+    open_pool, CsvReadOptions, CsvReadOutcome, NewHireProfile, NewHireRecord,
+    RejectedRow,
+};
+
+pub const DEFAULT_BASE_URL: &str = "https://api.samanage.com";
+
+const ENDPOINTS: [&str; 2] = ["incidents", "service_requests"];
+const PAGE_SIZE: usize = 100;
+
+const BASE_URL_KEY: &str = "api.base_url";
+/// Where builds that kept the token in the database stored it.
+const LEGACY_TOKEN_KEY: &str = "api.token";
+const CURSOR_KEY: &str = "api.last_updated_at";
+
+const KEYRING_SERVICE: &str = "sw-reports";
+const KEYRING_USER: &str = "solarwinds-api-token";
+
+/// The token lives in the system keyring, never in the database.
+#[derive(Debug, Clone, Default)]
+pub struct ApiSettings {
+    pub base_url: String,
+    pub token: String,
+    pub last_updated_at: Option<String>,
+}
+
+impl ApiSettings {
+    pub fn is_configured(&self) -> bool {
+        !self.base_url.trim().is_empty() && !self.token.trim().is_empty()
+    }
+}
+
+/// A token an earlier build left in the database is moved to the keyring.
+pub async fn load_api_settings(db_path: PathBuf) -> Result<ApiSettings, String> {
+    let pool = open_pool(&db_path).await?;
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|err| format!("Failed to connect to database: {err}"))?;
+
+    let token = match load_setting(&mut conn, LEGACY_TOKEN_KEY).await? {
+        Some(legacy) => {
+            if store_token(legacy.clone()).await.is_ok() {
+                store_setting(&mut conn, LEGACY_TOKEN_KEY, None).await?;
+            }
+            legacy
+        }
+        None => load_token().await,
+    };
+
+    Ok(ApiSettings {
+        base_url: load_setting(&mut conn, BASE_URL_KEY)
+            .await?
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_owned()),
+        token,
+        last_updated_at: load_setting(&mut conn, CURSOR_KEY).await?,
+    })
+}
+
+pub async fn save_api_settings(
+    db_path: PathBuf,
+    settings: ApiSettings,
+) -> Result<ApiSettings, String> {
+    let pool = open_pool(&db_path).await?;
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|err| format!("Failed to connect to database: {err}"))?;
+
+    store_token(settings.token.trim().to_owned()).await?;
+    store_setting(&mut conn, BASE_URL_KEY, Some(settings.base_url.trim())).await?;
+    store_setting(&mut conn, LEGACY_TOKEN_KEY, None).await?;
+
+    Ok(settings)
+}
+
+async fn load_token() -> String {
+    with_keyring(|entry| entry.get_password().map_err(|err| err.to_string()))
+        .await
+        .unwrap_or_default()
+}
+
+async fn store_token(token: String) -> Result<(), String> {
+    with_keyring(move |entry| {
+        let result = if token.is_empty() {
+            match entry.delete_credential() {
+                Err(keyring::Error::NoEntry) => Ok(()),
+                result => result,
+            }
+        } else {
+            entry.set_password(&token)
+        };
+        result.map_err(|err| format!("Failed to save the API token to the system keyring: {err}"))
+    })
+    .await
+}
+
+/// Keyring calls block, so they run off the async runtime.
+async fn with_keyring<T: Send + 'static>(
+    action: impl FnOnce(&keyring::Entry) -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tokio::task::spawn_blocking(move || {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+            .map_err(|err| format!("Failed to open the system keyring: {err}"))?;
+        action(&entry)
+    })
+    .await
+    .map_err(|err| format!("Failed to reach the system keyring: {err}"))?
+}
+
+/// `None` makes the next sync fetch every ticket.
+pub async fn save_sync_cursor(
+    db_path: PathBuf,
+    cursor: Option<String>,
+) -> Result<Option<String>, String> {
+    let pool = open_pool(&db_path).await?;
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|err| format!("Failed to connect to database: {err}"))?;
+
+    store_setting(&mut conn, CURSOR_KEY, cursor.as_deref()).await?;
+
+    Ok(cursor)
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiFetch {
+    pub source: ImportSource,
+    pub outcome: CsvReadOutcome,
+    pub cursor: Option<String>,
+}
+
+pub async fn sync_tickets(db_path: PathBuf, options: CsvReadOptions) -> Result<ApiFetch, String> {
+    let settings = load_api_settings(db_path).await?;
+    if !settings.is_configured() {
+        return Err("Set the SolarWinds API URL and token in Settings first.".to_owned());
+    }
+
+    fetch_tickets(settings, options).await
+}
+
+pub async fn fetch_tickets(
+    settings: ApiSettings,
+    options: CsvReadOptions,
+) -> Result<ApiFetch, String> {
+    let client = reqwest::Client::new();
+    let base_url = settings.base_url.trim().trim_end_matches('/');
+    let since = settings.last_updated_at.as_deref().and_then(parse_updated_at);
+
+    let mut tickets = Vec::new();
+    let mut content = Vec::new();
+    for endpoint in ENDPOINTS {
+        let mut page = 1;
+        loop {
+            let (items, total_pages, body) =
+                fetch_page(&client, base_url, &settings, endpoint, page).await?;
+            content.extend(body);
+
+            let fetched = items.len();
+            tickets.extend(
+                items
+                    .into_iter()
+                    .filter(|ticket| match (since, ticket_updated_at(ticket)) {
+                        (Some(since), Some(updated_at)) => updated_at >= since,
+                        _ => true,
+                    })
+                    .map(|ticket| (endpoint, ticket)),
+            );
+
+            let last_page = match total_pages {
+                Some(total_pages) => page >= total_pages,
+                None => fetched < PAGE_SIZE,
+            };
+            if fetched == 0 || last_page {
+                break;
+            }
+            page += 1;
+        }
+    }
+
+    let cursor = tickets
+        .iter()
+        .filter_map(|(_, ticket)| {
+            let updated_at = ticket.get("updated_at")?.as_str()?;
+            Some((parse_updated_at(updated_at)?, updated_at))
+        })
+        .max_by_key(|(timestamp, _)| *timestamp)
+        .map(|(_, updated_at)| updated_at.to_owned())
+        .or(settings.last_updated_at.clone());
+
+    let outcome = read_tickets(&tickets, options)?;
+    let file_name = match &settings.last_updated_at {
+        Some(since) => format!("SolarWinds API (updated after {since})"),
+        None => "SolarWinds API (full sync)".to_owned(),
+    };
+
+    Ok(ApiFetch {
+        source: ImportSource::from_content(file_name, &content),
+        outcome,
+        cursor,
+    })
+}
+
+async fn fetch_page(
+    client: &reqwest::Client,
+    base_url: &str,
+    settings: &ApiSettings,
+    endpoint: &str,
+    page: usize,
+) -> Result<(Vec<Value>, Option<usize>, Vec<u8>), String> {
+    let url = format!("{base_url}/{endpoint}.json");
+    let mut query = vec![
+        ("per_page", PAGE_SIZE.to_string()),
+        ("page", page.to_string()),
+    ];
+    if let Some(since) = &settings.last_updated_at {
+        query.push(("updated_custom_gte[]", since.clone()));
+    }
+
+    let response = client
+        .get(&url)
+        .query(&query)
+        .header(
+            "X-Samanage-Authorization",
+            format!("Bearer {}", settings.token.trim()),
+        )
+        .header("Accept", "application/vnd.samanage.v2.1+json")
+        .send()
+        .await
+        .map_err(|err| format!("Failed to reach the SolarWinds API: {err}"))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("SolarWinds API returned {status} for {url}"));
+    }
+
+    let total_pages = response
+        .headers()
+        .get("X-Total-Pages")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok());
+    let body = response
+        .bytes()
+        .await
+        .map_err(|err| format!("Failed to read SolarWinds API response: {err}"))?;
+    let items: Vec<Value> = serde_json::from_slice(&body)
+        .map_err(|err| format!("Unexpected SolarWinds API response from {url}: {err}"))?;
+
+    Ok((items, total_pages, body.to_vec()))
+}
+
+fn ticket_updated_at(ticket: &Value) -> Option<DateTime<FixedOffset>> {
+    ticket
+        .get("updated_at")
+        .and_then(Value::as_str)
+        .and_then(parse_updated_at)
+}
+
+fn parse_updated_at(value: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(value).ok()
+}
+
+/// A ticket seen on several pages keeps its last values; a number used by both
+/// endpoints is rejected.
+fn read_tickets(
+    tickets: &[(&'static str, Value)],
+    options: CsvReadOptions,
+) -> Result<CsvReadOutcome, String> {
+    let mut outcome = CsvReadOutcome {
+        headers: vec!["Ticket".to_owned()],
+        ..CsvReadOutcome::default()
+    };
+    let mut positions: HashMap<i64, (&str, usize)> = HashMap::new();
+
+    for (index, (endpoint, ticket)) in tickets.iter().enumerate() {
+        let line = index as u64 + 1;
+        let rejected = |column: Option<String>, reason: String| RejectedRow {
+            line,
+            number: text_field(ticket, RecordField::Number).and_then(|number| number.parse().ok()),
+            column,
+            reason,
+            values: vec![ticket.to_string()],
+        };
+
+        match record_from_ticket(ticket, &options.timestamps) {
+            Ok(Some(record)) => match positions.get(&record.number) {
+                Some((seen, position)) if seen == endpoint => {
+                    outcome.records[*position] = record
+                }
+                Some((seen, _)) => {
+                    let reason = format!("number {} is also used in {seen}", record.number);
+                    if !options.lenient {
+                        return Err(format!("SolarWinds API ticket {line}: {reason}"));
+                    }
+                    outcome.rejected.push(rejected(
+                        Some(RecordField::Number.default_header().to_owned()),
+                        reason,
+                    ));
+                }
+                None => {
+                    positions.insert(record.number, (endpoint, outcome.records.len()));
+                    outcome.records.push(record);
+                }
+            },
+            Ok(None) if options.lenient => outcome.rejected.push(rejected(
+                Some(RecordField::Number.default_header().to_owned()),
+                "missing ticket number".to_owned(),
+            )),
+            Ok(None) => {}
+            Err(err) if options.lenient => {
+                outcome.rejected.push(rejected(Some(err.column), err.reason))
+            }
+            Err(err) => return Err(format!("SolarWinds API ticket {line}: {err}")),
+        }
+    }
+
+    Ok(outcome)
+}
+
+/// Fields without an API attribute are read from the custom field with the
+/// matching CSV header.
+fn record_from_ticket(
+    ticket: &Value,
+    timestamps: &TimestampSettings,
+) -> Result<Option<NewHireRecord>, CellError> {
+    build_record::<NewHireProfile>(
+        |field| text_field(ticket, field),
+        |field| field.default_header().to_owned(),
+        extra_fields(ticket),
+        timestamps,
+    )
+}
+
+fn api_key(field: RecordField) -> Option<&'static str> {
+    match field {
+        RecordField::Number => Some("number"),
+        RecordField::State => Some("state"),
+        RecordField::Title => Some("name"),
+        RecordField::AssigneeName => Some("assignee"),
+        RecordField::Requester => Some("requester"),
+        RecordField::CreatedAt => Some("created_at"),
+        RecordField::Site => Some("site"),
+        RecordField::Division => Some("department"),
+        _ => None,
+    }
+}
+
+fn text_field(ticket: &Value, field: RecordField) -> Option<String> {
+    if let Some(key) = api_key(field) {
+        return ticket.get(key).and_then(value_text);
+    }
+
+    ticket
+        .get("custom_fields_values")?
+        .as_array()?
+        .iter()
+        .find(|custom| {
+            custom
+                .get("name")
+                .and_then(Value::as_str)
+                .is_some_and(|name| name.eq_ignore_ascii_case(field.default_header()))
+        })
+        .and_then(|custom| custom.get("value"))
+        .and_then(value_text)
+}
+
+fn extra_fields(ticket: &Value) -> BTreeMap<String, String> {
+    let Some(customs) = ticket
+        .get("custom_fields_values")
+        .and_then(Value::as_array)
+    else {
+        return BTreeMap::new();
+    };
+
+    customs
+        .iter()
+        .filter_map(|custom| {
+            let name = custom.get("name").and_then(Value::as_str)?.trim();
+            let known = RecordField::ALL
+                .into_iter()
+                .any(|field| name.eq_ignore_ascii_case(field.default_header()));
+            if name.is_empty() || known {
+                return None;
+            }
+            Some((name.to_owned(), custom.get("value").and_then(value_text)?))
+        })
+        .collect()
+}
+
+fn value_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.trim().to_owned()).filter(|text| !text.is_empty()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        Value::Object(object) => object.get("name").and_then(value_text),
+        Value::Null | Value::Array(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::matchers::{header, method, path, query_param, query_param_is_missing};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    fn ticket(number: i64, updated_at: &str) -> Value {
+        json!({
+            "number": number,
+            "name": format!("Ticket {number}"),
+            "state": "Assigned",
+            "updated_at": updated_at,
+        })
+    }
+
+    fn settings(server: &MockServer, cursor: Option<&str>) -> ApiSettings {
+        ApiSettings {
+            base_url: format!("{}/", server.uri()),
+            token: "secret".to_owned(),
+            last_updated_at: cursor.map(str::to_owned),
+        }
+    }
+
+    async fn mount_page(server: &MockServer, endpoint: &str, page: usize, body: ResponseTemplate) {
+        Mock::given(method("GET"))
+            .and(path(format!("/{endpoint}.json")))
+            .and(query_param("page", page.to_string()))
+            .and(query_param("per_page", PAGE_SIZE.to_string()))
+            .and(query_param_is_missing("updated_custom_gte[]"))
+            .and(header("X-Samanage-Authorization", "Bearer secret"))
+            .respond_with(body)
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn pages_through_every_endpoint() {
+        let server = MockServer::start().await;
+        let pages = |items: Vec<Value>| {
+            ResponseTemplate::new(200)
+                .insert_header("X-Total-Pages", "2")
+                .set_body_json(items)
+        };
+        mount_page(
+            &server,
+            "incidents",
+            1,
+            pages(vec![ticket(1, "2024-03-01T10:00:00Z")]),
+        )
+        .await;
+        mount_page(
+            &server,
+            "incidents",
+            2,
+            pages(vec![
+                ticket(2, "2024-03-03T10:00:00+01:00"),
+                ticket(1, "2024-03-04T10:00:00Z"),
+            ]),
+        )
+        .await;
+        // Without a page count, a short page is the last one.
+        mount_page(
+            &server,
+            "service_requests",
+            1,
+            ResponseTemplate::new(200).set_body_json(vec![ticket(3, "2024-03-02T10:00:00Z")]),
+        )
+        .await;
+
+        let fetch = fetch_tickets(settings(&server, None), CsvReadOptions::default())
+            .await
+            .unwrap();
+
+        let numbers: Vec<i64> = fetch.outcome.records.iter().map(|record| record.number).collect();
+        assert_eq!(numbers, [1, 2, 3]);
+        assert_eq!(fetch.cursor.as_deref(), Some("2024-03-04T10:00:00Z"));
+        assert_eq!(fetch.source.file_name, "SolarWinds API (full sync)");
+    }
+
+    #[tokio::test]
+    async fn incremental_sync_keeps_tickets_updated_at_the_cursor() {
+        let server = MockServer::start().await;
+        let cursor = "2024-03-02T10:00:00Z";
+        for endpoint in ENDPOINTS {
+            Mock::given(method("GET"))
+                .and(path(format!("/{endpoint}.json")))
+                .and(query_param("updated_custom_gte[]", cursor))
+                .respond_with(ResponseTemplate::new(200).set_body_json(match endpoint {
+                    "incidents" => vec![
+                        ticket(1, "2024-03-01T10:00:00Z"),
+                        ticket(2, cursor),
+                        ticket(3, "2024-03-02T10:00:01Z"),
+                    ],
+                    _ => Vec::new(),
+                }))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let fetch = fetch_tickets(settings(&server, Some(cursor)), CsvReadOptions::default())
+            .await
+            .unwrap();
+
+        let numbers: Vec<i64> = fetch.outcome.records.iter().map(|record| record.number).collect();
+        assert_eq!(numbers, [2, 3]);
+        assert_eq!(fetch.cursor.as_deref(), Some("2024-03-02T10:00:01Z"));
+    }
+
+    #[tokio::test]
+    async fn incremental_sync_without_changes_keeps_the_cursor() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(Vec::<Value>::new()))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let cursor = "2024-03-02T10:00:00Z";
+        let fetch = fetch_tickets(settings(&server, Some(cursor)), CsvReadOptions::default())
+            .await
+            .unwrap();
+
+        assert!(fetch.outcome.records.is_empty());
+        assert_eq!(fetch.cursor.as_deref(), Some(cursor));
+    }
+
+    #[tokio::test]
+    async fn reports_server_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+
+        let err = fetch_tickets(settings(&server, None), CsvReadOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.starts_with("SolarWinds API returned 401"), "{err}");
+    }
+
+    #[test]
+    fn rejects_numbers_used_by_both_endpoints() {
+        let tickets = [
+            ("incidents", ticket(1, "2024-03-01T10:00:00Z")),
+            ("service_requests", ticket(1, "2024-03-02T10:00:00Z")),
+        ];
+
+        let err = read_tickets(&tickets, CsvReadOptions::default()).unwrap_err();
+        assert_eq!(err, "SolarWinds API ticket 2: number 1 is also used in incidents");
+
+        let options = CsvReadOptions {
+            lenient: true,
+            ..CsvReadOptions::default()
+        };
+        let outcome = read_tickets(&tickets, options).unwrap();
+        assert_eq!(outcome.records.len(), 1);
+        assert_eq!((outcome.rejected[0].line, outcome.rejected[0].number), (2, Some(1)));
+    }
+}
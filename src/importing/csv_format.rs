@@ -0,0 +1,448 @@
+use std::fmt;
+use std::io::{self, SeekFrom};
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use csv_async::{AsyncReader, AsyncReaderBuilder};
+use encoding_rs::{Decoder, Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use futures::io::AsyncRead;
+use futures::StreamExt;
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::compat::TokioAsyncReadCompatExt;
+
+const SAMPLE_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Windows1252,
+}
+
+impl CsvEncoding {
+    pub const ALL: [CsvEncoding; 4] = [
+        CsvEncoding::Utf8,
+        CsvEncoding::Utf16Le,
+        CsvEncoding::Utf16Be,
+        CsvEncoding::Windows1252,
+    ];
+
+    fn encoding(self) -> &'static Encoding {
+        match self {
+            CsvEncoding::Utf8 => UTF_8,
+            CsvEncoding::Utf16Le => UTF_16LE,
+            CsvEncoding::Utf16Be => UTF_16BE,
+            CsvEncoding::Windows1252 => WINDOWS_1252,
+        }
+    }
+
+    fn from_encoding(encoding: &'static Encoding) -> Self {
+        if encoding == UTF_16LE {
+            CsvEncoding::Utf16Le
+        } else if encoding == UTF_16BE {
+            CsvEncoding::Utf16Be
+        } else {
+            CsvEncoding::Utf8
+        }
+    }
+}
+
+impl fmt::Display for CsvEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CsvEncoding::Utf8 => "UTF-8",
+            CsvEncoding::Utf16Le => "UTF-16 LE",
+            CsvEncoding::Utf16Be => "UTF-16 BE",
+            CsvEncoding::Windows1252 => "Windows-1252",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvDelimiter {
+    Comma,
+    Semicolon,
+    Tab,
+    Pipe,
+}
+
+impl CsvDelimiter {
+    pub const ALL: [CsvDelimiter; 4] = [
+        CsvDelimiter::Comma,
+        CsvDelimiter::Semicolon,
+        CsvDelimiter::Tab,
+        CsvDelimiter::Pipe,
+    ];
+
+    fn byte(self) -> u8 {
+        match self {
+            CsvDelimiter::Comma => b',',
+            CsvDelimiter::Semicolon => b';',
+            CsvDelimiter::Tab => b'\t',
+            CsvDelimiter::Pipe => b'|',
+        }
+    }
+}
+
+impl fmt::Display for CsvDelimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CsvDelimiter::Comma => "Comma",
+            CsvDelimiter::Semicolon => "Semicolon",
+            CsvDelimiter::Tab => "Tab",
+            CsvDelimiter::Pipe => "Pipe",
+        })
+    }
+}
+
+/// `None` is detected from the file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CsvFormat {
+    pub encoding: Option<CsvEncoding>,
+    pub delimiter: Option<CsvDelimiter>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodingChoice(pub Option<CsvEncoding>);
+
+impl EncodingChoice {
+    pub fn all() -> Vec<EncodingChoice> {
+        std::iter::once(EncodingChoice(None))
+            .chain(CsvEncoding::ALL.into_iter().map(Some).map(EncodingChoice))
+            .collect()
+    }
+}
+
+impl fmt::Display for EncodingChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(encoding) => encoding.fmt(f),
+            None => f.write_str("Detect encoding"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DelimiterChoice(pub Option<CsvDelimiter>);
+
+impl DelimiterChoice {
+    pub fn all() -> Vec<DelimiterChoice> {
+        std::iter::once(DelimiterChoice(None))
+            .chain(CsvDelimiter::ALL.into_iter().map(Some).map(DelimiterChoice))
+            .collect()
+    }
+}
+
+impl fmt::Display for DelimiterChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(delimiter) => delimiter.fmt(f),
+            None => f.write_str("Detect delimiter"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetectedFormat {
+    pub encoding: CsvEncoding,
+    pub delimiter: CsvDelimiter,
+    pub bom: bool,
+}
+
+impl DetectedFormat {
+    pub fn describe(&self) -> String {
+        format!(
+            "{}{}, {} delimited",
+            self.encoding,
+            if self.bom { " with BOM" } else { "" },
+            self.delimiter.to_string().to_lowercase()
+        )
+    }
+}
+
+pub async fn detect_format(path: &Path, format: CsvFormat) -> Result<DetectedFormat, String> {
+    let mut file = fs::File::open(path)
+        .await
+        .map_err(|err| format!("Failed to open CSV: {err}"))?;
+    let mut sample = Vec::with_capacity(SAMPLE_SIZE);
+    (&mut file)
+        .take(SAMPLE_SIZE as u64)
+        .read_to_end(&mut sample)
+        .await
+        .map_err(|err| format!("Failed to read CSV: {err}"))?;
+
+    let bom = Encoding::for_bom(&sample);
+    let encoding = format
+        .encoding
+        .or_else(|| bom.map(|(encoding, _)| CsvEncoding::from_encoding(encoding)))
+        .unwrap_or_else(|| guess_encoding(&sample));
+    let bom = bom.is_some_and(|(found, _)| found == encoding.encoding());
+
+    let delimiter = match format.delimiter {
+        Some(delimiter) => delimiter,
+        None => {
+            let skip = if bom { bom_length(encoding) } else { 0 };
+            let (text, _) = encoding
+                .encoding()
+                .decode_without_bom_handling(&sample[skip.min(sample.len())..]);
+            guess_delimiter(&text)
+        }
+    };
+
+    Ok(DetectedFormat {
+        encoding,
+        delimiter,
+        bom,
+    })
+}
+
+pub async fn open_reader(
+    path: &Path,
+    format: DetectedFormat,
+) -> Result<AsyncReader<Box<dyn AsyncRead + Unpin + Send>>, String> {
+    let mut file = fs::File::open(path)
+        .await
+        .map_err(|err| format!("Failed to open CSV: {err}"))?;
+    let skip = if format.bom { bom_length(format.encoding) } else { 0 };
+
+    file.seek(SeekFrom::Start(skip as u64))
+        .await
+        .map_err(|err| format!("Failed to read CSV: {err}"))?;
+    let input: Box<dyn AsyncRead + Unpin + Send> = if format.encoding == CsvEncoding::Utf8 {
+        Box::new(file.compat())
+    } else {
+        Box::new(DecodingReader::new(file.compat(), format.encoding.encoding()))
+    };
+
+    // Rows with the wrong number of fields are read as they are so a lenient
+    // import can reject them with their values.
+    Ok(AsyncReaderBuilder::new()
+        .trim(csv_async::Trim::All)
+        .flexible(true)
+        .delimiter(format.delimiter.byte())
+        .create_reader(input))
+}
+
+pub async fn count_rows(path: &Path, format: DetectedFormat) -> Result<usize, String> {
+    let mut reader = open_reader(path, format).await?;
+    let mut rows = reader.byte_records();
+    let mut count = 0;
+    while rows.next().await.is_some() {
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+const DECODE_CHUNK: usize = 16 * 1024;
+
+/// Malformed bytes become replacement characters, as with [`Encoding::decode`].
+struct DecodingReader<R> {
+    inner: R,
+    decoder: Decoder,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+impl<R> DecodingReader<R> {
+    fn new(inner: R, encoding: &'static Encoding) -> Self {
+        Self {
+            inner,
+            decoder: encoding.new_decoder_without_bom_handling(),
+            input: vec![0; DECODE_CHUNK],
+            output: Vec::new(),
+            position: 0,
+            finished: false,
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for DecodingReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            if this.position < this.output.len() {
+                let count = buf.len().min(this.output.len() - this.position);
+                buf[..count].copy_from_slice(&this.output[this.position..this.position + count]);
+                this.position += count;
+                return Poll::Ready(Ok(count));
+            }
+            if this.finished {
+                return Poll::Ready(Ok(0));
+            }
+
+            let read = ready!(Pin::new(&mut this.inner).poll_read(cx, &mut this.input))?;
+            let last = read == 0;
+            let capacity = this
+                .decoder
+                .max_utf8_buffer_length(read)
+                .unwrap_or(DECODE_CHUNK * 3);
+            this.output.resize(capacity, 0);
+            let (_, _, written, _) =
+                this.decoder
+                    .decode_to_utf8(&this.input[..read], &mut this.output, last);
+            this.output.truncate(written);
+            this.position = 0;
+            this.finished = last;
+        }
+    }
+}
+
+fn bom_length(encoding: CsvEncoding) -> usize {
+    match encoding {
+        CsvEncoding::Utf8 => 3,
+        CsvEncoding::Utf16Le | CsvEncoding::Utf16Be => 2,
+        CsvEncoding::Windows1252 => 0,
+    }
+}
+
+/// UTF-16 is recognised by the zero bytes of its ASCII characters; anything
+/// that is not valid UTF-8 is taken to be Windows-1252.
+fn guess_encoding(sample: &[u8]) -> CsvEncoding {
+    let pairs = sample.len() / 2;
+    if pairs > 0 {
+        let zeros_at = |offset: usize| {
+            sample
+                .iter()
+                .skip(offset)
+                .step_by(2)
+                .filter(|byte| **byte == 0)
+                .count()
+        };
+        let (even, odd) = (zeros_at(0), zeros_at(1));
+        if odd > pairs / 3 && even < pairs / 20 {
+            return CsvEncoding::Utf16Le;
+        }
+        if even > pairs / 3 && odd < pairs / 20 {
+            return CsvEncoding::Utf16Be;
+        }
+    }
+
+    match std::str::from_utf8(sample) {
+        Ok(_) => CsvEncoding::Utf8,
+        // A character cut off at the end of the sample is still UTF-8.
+        Err(err) if err.error_len().is_none() => CsvEncoding::Utf8,
+        Err(_) => CsvEncoding::Windows1252,
+    }
+}
+
+/// Ignores quoted text and falls back to a comma.
+fn guess_delimiter(text: &str) -> CsvDelimiter {
+    let Some(header) = text.lines().find(|line| !line.trim().is_empty()) else {
+        return CsvDelimiter::Comma;
+    };
+
+    let mut counts = [0usize; CsvDelimiter::ALL.len()];
+    let mut quoted = false;
+    for character in header.chars() {
+        if character == '"' {
+            quoted = !quoted;
+            continue;
+        }
+        if quoted {
+            continue;
+        }
+        if let Some(index) = CsvDelimiter::ALL
+            .iter()
+            .position(|delimiter| char::from(delimiter.byte()) == character)
+        {
+            counts[index] += 1;
+        }
+    }
+
+    CsvDelimiter::ALL
+        .into_iter()
+        .zip(counts)
+        .filter(|(_, count)| *count > 0)
+        .max_by_key(|(_, count)| *count)
+        .map(|(delimiter, _)| delimiter)
+        .unwrap_or(CsvDelimiter::Comma)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn detect(bytes: &[u8], format: CsvFormat) -> DetectedFormat {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.csv");
+        std::fs::write(&path, bytes).unwrap();
+        detect_format(&path, format).await.unwrap()
+    }
+
+    fn utf16le_with_bom(text: &str) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+
+    #[tokio::test]
+    async fn detects_encoding_bom_and_delimiter() {
+        let detected = detect(b"Number,Title\n1,Laptop\n", CsvFormat::default()).await;
+        assert_eq!(
+            detected,
+            DetectedFormat {
+                encoding: CsvEncoding::Utf8,
+                delimiter: CsvDelimiter::Comma,
+                bom: false,
+            }
+        );
+
+        let detected = detect(
+            &utf16le_with_bom("Number;Title;State\n1;Laptop;Open\n"),
+            CsvFormat::default(),
+        )
+        .await;
+        assert_eq!(detected.encoding, CsvEncoding::Utf16Le);
+        assert_eq!(detected.delimiter, CsvDelimiter::Semicolon);
+        assert!(detected.bom);
+
+        // 0xE9 is "é" in Windows-1252 and not valid UTF-8.
+        let detected = detect(b"Number\tTitle\n1\tCaf\xE9\n", CsvFormat::default()).await;
+        assert_eq!(detected.encoding, CsvEncoding::Windows1252);
+        assert_eq!(detected.delimiter, CsvDelimiter::Tab);
+    }
+
+    #[tokio::test]
+    async fn ignores_quoted_delimiters_and_keeps_overrides() {
+        let detected = detect(
+            b"\"Title, short\";\"Notes, long\";Number\n",
+            CsvFormat::default(),
+        )
+        .await;
+        assert_eq!(detected.delimiter, CsvDelimiter::Semicolon);
+
+        let format = CsvFormat {
+            encoding: Some(CsvEncoding::Windows1252),
+            delimiter: Some(CsvDelimiter::Pipe),
+        };
+        let detected = detect(b"Number,Title\n1,Laptop\n", format).await;
+        assert_eq!(detected.encoding, CsvEncoding::Windows1252);
+        assert_eq!(detected.delimiter, CsvDelimiter::Pipe);
+        assert!(!detected.bom);
+    }
+
+    #[tokio::test]
+    async fn reads_utf16_files_as_utf8() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.csv");
+        std::fs::write(&path, utf16le_with_bom("Number;Title\n1;Café\n")).unwrap();
+
+        let format = detect_format(&path, CsvFormat::default()).await.unwrap();
+        let mut reader = open_reader(&path, format).await.unwrap();
+        let headers = reader.headers().await.unwrap().clone();
+        assert_eq!(headers, vec!["Number", "Title"]);
+        let rows: Vec<_> = reader.records().collect().await;
+        let row = rows[0].as_ref().unwrap();
+        assert_eq!(row, vec!["1", "Café"]);
+    }
+}
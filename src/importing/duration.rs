@@ -0,0 +1,116 @@
+use super::mapping::RecordField;
+
+pub const DURATION_COLUMNS: [(RecordField, &str); 3] = [
+    (
+        RecordField::ToFirstResponseBusiness,
+        "to_first_response_business_minutes",
+    ),
+    (
+        RecordField::ToResolutionBusiness,
+        "to_resolution_business_minutes",
+    ),
+    (
+        RecordField::ToResolutionElapsed,
+        "to_resolution_elapsed_minutes",
+    ),
+];
+
+pub fn minutes_column(field: RecordField) -> Option<&'static str> {
+    DURATION_COLUMNS
+        .iter()
+        .find(|(duration, _)| *duration == field)
+        .map(|(_, column)| *column)
+}
+
+/// Seconds are dropped.
+pub fn parse_minutes(text: &str) -> Option<i64> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    if text.contains(':') {
+        return parse_clock(text);
+    }
+
+    let mut seconds = 0i64;
+    let mut rest = text;
+    let mut parts = 0;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|character: char| !character.is_ascii_digit() && character != '.')
+            .unwrap_or(rest.len());
+        let amount: f64 = rest[..digits].parse().ok()?;
+        rest = rest[digits..].trim_start();
+
+        let letters = rest
+            .find(|character: char| !character.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let unit = match rest[..letters].to_ascii_lowercase().as_str() {
+            "d" | "day" | "days" => 24 * 60 * 60,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60,
+            "s" | "sec" | "secs" | "second" | "seconds" => 1,
+            _ => return None,
+        };
+        seconds += (amount * unit as f64) as i64;
+        parts += 1;
+
+        rest = rest[letters..].trim_start_matches([',', ' ']);
+    }
+
+    (parts > 0).then_some(seconds / 60)
+}
+
+fn parse_clock(text: &str) -> Option<i64> {
+    let parts = text
+        .split(':')
+        .map(|part| part.trim().parse::<u32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [hours, minutes] => (*hours, *minutes, 0),
+        [hours, minutes, seconds] => (*hours, *minutes, *seconds),
+        _ => return None,
+    };
+    (minutes < 60 && seconds < 60).then_some(i64::from(hours) * 60 + i64::from(minutes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_minutes;
+
+    #[test]
+    fn parses_unit_durations() {
+        assert_eq!(parse_minutes("1.5h"), Some(90));
+        assert_eq!(parse_minutes("1d 3h 20m"), Some(24 * 60 + 3 * 60 + 20));
+        assert_eq!(parse_minutes("45 min"), Some(45));
+        assert_eq!(parse_minutes("2 hours, 30 mins"), Some(150));
+        assert_eq!(parse_minutes("90 sec"), Some(1));
+    }
+
+    #[test]
+    fn parses_clock_durations() {
+        assert_eq!(parse_minutes("02:30"), Some(150));
+        assert_eq!(parse_minutes("02:30:59"), Some(150));
+        assert_eq!(parse_minutes("100:05"), Some(6005));
+    }
+
+    #[test]
+    fn rejects_out_of_range_clock_parts() {
+        assert_eq!(parse_minutes("1:75:00"), None);
+        assert_eq!(parse_minutes("1:30:60"), None);
+        assert_eq!(parse_minutes("1:60"), None);
+        assert_eq!(parse_minutes("-1:30"), None);
+        assert_eq!(parse_minutes("1:2:3:4"), None);
+    }
+
+    #[test]
+    fn rejects_placeholders_and_unknown_units() {
+        assert_eq!(parse_minutes("-"), None);
+        assert_eq!(parse_minutes(""), None);
+        assert_eq!(parse_minutes("   "), None);
+        assert_eq!(parse_minutes("3 weeks"), None);
+        assert_eq!(parse_minutes("soon"), None);
+    }
+}
@@ -0,0 +1,618 @@
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use sqlx::{Row, SqliteConnection};
+use tokio::fs;
+use tokio::io::AsyncReadExt;
+
+use super::incidents::IncidentProfile;
+use super::mapping::RecordField;
+use super::profile::{delete_record, fetch_existing_record, update_record, ImportProfile};
+use super::{open_pool, snapshot, transitions, FieldValue, NewHireProfile, NewHireRecord};
+
+pub const TIMELINE_FIELDS: [RecordField; 3] = [
+    RecordField::State,
+    RecordField::AssigneeName,
+    RecordField::ResolvedAt,
+];
+
+#[derive(Debug, Clone)]
+pub struct ImportSource {
+    pub file_name: String,
+    pub content_hash: String,
+}
+
+impl ImportSource {
+    pub async fn from_file(path: &Path) -> Result<Self, String> {
+        let mut file = fs::File::open(path)
+            .await
+            .map_err(|err| format!("Failed to open file: {err}"))?;
+
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            let read = file
+                .read(&mut buffer)
+                .await
+                .map_err(|err| format!("Failed to read file: {err}"))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+
+        let content_hash = hex(&hasher.finalize());
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+
+        Ok(Self {
+            file_name,
+            content_hash,
+        })
+    }
+
+    pub fn from_content(file_name: String, content: &[u8]) -> Self {
+        Self {
+            file_name,
+            content_hash: hex(&Sha256::digest(content)),
+        }
+    }
+
+    /// Importing another sheet of the same file then is not taken for a re-import.
+    pub fn with_sheet(self, sheet: &str) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(self.content_hash.as_bytes());
+        hasher.update(sheet.as_bytes());
+
+        Self {
+            file_name: format!("{} [{sheet}]", self.file_name),
+            content_hash: hex(&hasher.finalize()),
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportRun {
+    pub id: i64,
+    pub file_name: String,
+    pub content_hash: String,
+    pub imported_at: String,
+    pub inserted: i64,
+    pub updated: i64,
+    pub skipped: i64,
+    pub rolled_back_at: Option<String>,
+    pub cancelled_at: Option<String>,
+    pub profile: String,
+}
+
+impl ImportRun {
+    pub fn is_incident_export(&self) -> bool {
+        self.profile == IncidentProfile::NAME
+    }
+
+    pub fn is_rolled_back(&self) -> bool {
+        self.rolled_back_at.is_some()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled_at.is_some()
+    }
+
+    pub fn short_hash(&self) -> &str {
+        self.content_hash.get(..8).unwrap_or(&self.content_hash)
+    }
+
+    pub fn imported_at_display(&self) -> String {
+        format_timestamp(&self.imported_at)
+    }
+
+    pub fn rolled_back_at_display(&self) -> Option<String> {
+        self.rolled_back_at.as_deref().map(format_timestamp)
+    }
+}
+
+fn format_timestamp(value: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| {
+            timestamp
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|_| value.to_owned())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunAction {
+    Inserted,
+    Updated,
+    Deleted,
+    Restored,
+}
+
+impl RunAction {
+    pub fn key(self) -> &'static str {
+        match self {
+            RunAction::Inserted => "inserted",
+            RunAction::Updated => "updated",
+            RunAction::Deleted => "deleted",
+            RunAction::Restored => "restored",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "inserted" => Some(RunAction::Inserted),
+            "updated" => Some(RunAction::Updated),
+            "deleted" => Some(RunAction::Deleted),
+            "restored" => Some(RunAction::Restored),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RunAction::Inserted => "Inserted",
+            RunAction::Updated => "Updated",
+            RunAction::Deleted => "Deleted",
+            RunAction::Restored => "Restored",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RunRecord {
+    pub number: i64,
+    pub action: RunAction,
+}
+
+pub(super) async fn start_run<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    source: &ImportSource,
+) -> Result<i64, String> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO import_runs (profile, file_name, content_hash, imported_at)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(P::NAME)
+    .bind(&source.file_name)
+    .bind(&source.content_hash)
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(conn)
+    .await
+    .map_err(|err| format!("Failed to record import run: {err}"))?;
+
+    Ok(result.last_insert_rowid())
+}
+
+pub(super) async fn log_record<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    run_id: i64,
+    number: i64,
+    action: RunAction,
+    previous: Option<&P::Record>,
+) -> Result<(), String> {
+    let previous = previous
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|err| format!("Failed to snapshot record: {err}"))?;
+
+    log_action(conn, run_id, number, action, previous).await?;
+    if P::KEEPS_HISTORY {
+        record_version::<P>(conn, Some(run_id), number).await?;
+    }
+    Ok(())
+}
+
+pub(super) async fn log_action(
+    conn: &mut SqliteConnection,
+    run_id: i64,
+    number: i64,
+    action: RunAction,
+    previous: Option<String>,
+) -> Result<(), String> {
+    sqlx::query(
+        "INSERT INTO import_run_records (run_id, number, action, previous) VALUES (?, ?, ?, ?)",
+    )
+    .bind(run_id)
+    .bind(number)
+    .bind(action.key())
+    .bind(previous)
+    .execute(conn)
+    .await
+    .map_err(|err| format!("Failed to log import run record: {err}"))?;
+
+    Ok(())
+}
+
+async fn record_version<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    run_id: Option<i64>,
+    number: i64,
+) -> Result<(), String> {
+    let Some(record) = fetch_existing_record::<P>(conn, number).await? else {
+        return Ok(());
+    };
+
+    let recorded_at = insert_version::<P>(conn, run_id, &record).await?;
+    transitions::record_transition::<P>(conn, run_id, &record, &recorded_at).await
+}
+
+async fn insert_version<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    run_id: Option<i64>,
+    record: &P::Record,
+) -> Result<String, String> {
+    let snapshot = serde_json::to_string(record)
+        .map_err(|err| format!("Failed to snapshot record: {err}"))?;
+    let recorded_at = chrono::Utc::now().to_rfc3339();
+
+    sqlx::query(
+        r#"
+        INSERT INTO record_history (profile, number, run_id, recorded_at, record)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(P::NAME)
+    .bind(P::key(record))
+    .bind(run_id)
+    .bind(&recorded_at)
+    .bind(snapshot)
+    .execute(conn)
+    .await
+    .map_err(|err| format!("Failed to record ticket history: {err}"))?;
+
+    Ok(recorded_at)
+}
+
+pub(super) async fn add_counts(
+    conn: &mut SqliteConnection,
+    run_id: i64,
+    inserted: usize,
+    updated: usize,
+    skipped: usize,
+) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        UPDATE import_runs SET
+            inserted = inserted + ?,
+            updated = updated + ?,
+            skipped = skipped + ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(inserted as i64)
+    .bind(updated as i64)
+    .bind(skipped as i64)
+    .bind(run_id)
+    .execute(conn)
+    .await
+    .map_err(|err| format!("Failed to update import run: {err}"))?;
+
+    Ok(())
+}
+
+pub(super) async fn mark_cancelled(conn: &mut SqliteConnection, run_id: i64) -> Result<(), String> {
+    sqlx::query("UPDATE import_runs SET cancelled_at = ? WHERE id = ?")
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(run_id)
+        .execute(conn)
+        .await
+        .map_err(|err| format!("Failed to update import run: {err}"))?;
+
+    Ok(())
+}
+
+pub async fn find_run_by_hash(
+    conn: &mut SqliteConnection,
+    content_hash: &str,
+) -> Result<Option<ImportRun>, String> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, file_name, content_hash, imported_at, inserted, updated, skipped,
+            rolled_back_at, cancelled_at, profile
+        FROM import_runs
+        WHERE content_hash = ? AND rolled_back_at IS NULL AND cancelled_at IS NULL
+        ORDER BY id DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(content_hash)
+    .fetch_optional(conn)
+    .await
+    .map_err(|err| format!("Failed to look up import runs: {err}"))?;
+
+    Ok(row.map(|row| run_from_row(&row)))
+}
+
+pub async fn load_runs(db_path: PathBuf) -> Result<Vec<ImportRun>, String> {
+    let pool = open_pool(&db_path).await?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, file_name, content_hash, imported_at, inserted, updated, skipped,
+            rolled_back_at, cancelled_at, profile
+        FROM import_runs
+        ORDER BY id DESC
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|err| format!("Failed to load import history: {err}"))?;
+
+    Ok(rows.iter().map(run_from_row).collect())
+}
+
+pub async fn load_run_records(
+    db_path: PathBuf,
+    run_id: i64,
+) -> Result<(i64, Vec<RunRecord>), String> {
+    let pool = open_pool(&db_path).await?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT number, action
+        FROM import_run_records
+        WHERE run_id = ?
+        ORDER BY number
+        "#,
+    )
+    .bind(run_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|err| format!("Failed to load import run records: {err}"))?;
+
+    let records = rows
+        .into_iter()
+        .filter_map(|row| {
+            let number: i64 = row.get("number");
+            let action: String = row.get("action");
+            RunAction::from_key(&action).map(|action| RunRecord { number, action })
+        })
+        .collect();
+
+    Ok((run_id, records))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RollbackOutcome {
+    pub run_id: i64,
+    pub removed: usize,
+    pub restored: usize,
+}
+
+/// Refuses when a later active run touched the same tickets.
+pub async fn roll_back_run<P: ImportProfile>(
+    db_path: PathBuf,
+    run_id: i64,
+) -> Result<RollbackOutcome, String> {
+    let pool = open_pool(&db_path).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|err| format!("Failed to start transaction: {err}"))?;
+
+    let run = sqlx::query("SELECT rolled_back_at, profile FROM import_runs WHERE id = ?")
+        .bind(run_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|err| format!("Failed to load import run: {err}"))?;
+    let Some(run) = run else {
+        return Err(format!("Import run #{run_id} does not exist."));
+    };
+    if run.get::<Option<String>, _>("rolled_back_at").is_some() {
+        return Err(format!("Import run #{run_id} was already rolled back."));
+    }
+    let profile: String = run.get("profile");
+    if profile != P::NAME {
+        return Err(format!(
+            "Import run #{run_id} imported \"{profile}\", not \"{}\".",
+            P::NAME
+        ));
+    }
+
+    let conflicts: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(DISTINCT later.number)
+        FROM import_run_records AS later
+        JOIN import_runs AS run ON run.id = later.run_id
+        WHERE later.run_id > ?
+            AND run.rolled_back_at IS NULL
+            AND run.profile = ?
+            AND later.number IN (SELECT number FROM import_run_records WHERE run_id = ?)
+        "#,
+    )
+    .bind(run_id)
+    .bind(P::NAME)
+    .bind(run_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|err| format!("Failed to check later import runs: {err}"))?;
+
+    if conflicts > 0 {
+        return Err(format!(
+            "{conflicts} ticket(s) from run #{run_id} were changed by later imports. \
+             Roll those runs back first."
+        ));
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT number, action, previous
+        FROM import_run_records
+        WHERE run_id = ?
+        ORDER BY rowid DESC
+        "#,
+    )
+    .bind(run_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|err| format!("Failed to load import run records: {err}"))?;
+
+    let outcome = revert_records::<P>(&mut tx, run_id, rows).await?;
+
+    sqlx::query("DELETE FROM pending_duplicates WHERE run_id = ?")
+        .bind(run_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| format!("Failed to clear queued duplicates: {err}"))?;
+
+    sqlx::query("DELETE FROM record_history WHERE run_id = ?")
+        .bind(run_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| format!("Failed to clear ticket history: {err}"))?;
+
+    sqlx::query("DELETE FROM state_transitions WHERE run_id = ?")
+        .bind(run_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| format!("Failed to clear state transitions: {err}"))?;
+
+    sqlx::query("UPDATE import_runs SET rolled_back_at = ? WHERE id = ?")
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(run_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| format!("Failed to update import run: {err}"))?;
+
+    tx.commit()
+        .await
+        .map_err(|err| format!("Failed to commit rollback: {err}"))?;
+
+    Ok(outcome)
+}
+
+async fn revert_records<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    run_id: i64,
+    rows: Vec<sqlx::sqlite::SqliteRow>,
+) -> Result<RollbackOutcome, String> {
+    let mut outcome = RollbackOutcome {
+        run_id,
+        removed: 0,
+        restored: 0,
+    };
+
+    for row in rows {
+        let number: i64 = row.get("number");
+        let action: String = row.get("action");
+        let previous: Option<String> = row.get("previous");
+
+        match RunAction::from_key(&action) {
+            Some(RunAction::Inserted) => {
+                delete_record::<P>(conn, number).await?;
+                outcome.removed += 1;
+            }
+            Some(RunAction::Updated) => {
+                let Some(previous) = previous else {
+                    return Err(format!(
+                        "Ticket {number} has no saved previous values; run #{run_id} cannot be rolled back."
+                    ));
+                };
+                let record: P::Record = serde_json::from_str(&previous)
+                    .map_err(|err| format!("Failed to read snapshot of ticket {number}: {err}"))?;
+                update_record::<P>(conn, &record).await?;
+                outcome.restored += 1;
+            }
+            Some(RunAction::Deleted) => {
+                snapshot::undo_delete::<P>(conn, number).await?;
+                outcome.restored += 1;
+            }
+            Some(RunAction::Restored) => {
+                let Some(previous) = previous else {
+                    return Err(format!(
+                        "Ticket {number} has no saved deletion; run #{run_id} cannot be rolled back."
+                    ));
+                };
+                snapshot::redo_delete::<P>(conn, number, &previous).await?;
+                outcome.removed += 1;
+            }
+            None => {}
+        }
+    }
+
+    Ok(outcome)
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordVersion {
+    /// `None` for values stored before ticket history was kept.
+    pub run_id: Option<i64>,
+    pub file_name: Option<String>,
+    pub recorded_at: String,
+    pub record: NewHireRecord,
+}
+
+impl RecordVersion {
+    pub fn recorded_at_display(&self) -> String {
+        format_timestamp(&self.recorded_at)
+    }
+
+    pub fn value(&self, field: RecordField) -> FieldValue {
+        self.record.field_value(field)
+    }
+}
+
+pub async fn load_timeline(
+    db_path: PathBuf,
+    number: i64,
+) -> Result<(i64, Vec<RecordVersion>), String> {
+    let pool = open_pool(&db_path).await?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT history.run_id, run.file_name, history.recorded_at, history.record
+        FROM record_history AS history
+        LEFT JOIN import_runs AS run ON run.id = history.run_id
+        WHERE history.profile = ? AND history.number = ?
+        ORDER BY history.id
+        "#,
+    )
+    .bind(NewHireProfile::NAME)
+    .bind(number)
+    .fetch_all(&pool)
+    .await
+    .map_err(|err| format!("Failed to load ticket history: {err}"))?;
+
+    let versions = rows
+        .into_iter()
+        .map(|row| {
+            let record: String = row.get("record");
+            let record = serde_json::from_str(&record)
+                .map_err(|err| format!("Failed to read history of ticket {number}: {err}"))?;
+            Ok(RecordVersion {
+                run_id: row.get("run_id"),
+                file_name: row.get("file_name"),
+                recorded_at: row.get("recorded_at"),
+                record,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok((number, versions))
+}
+
+fn run_from_row(row: &sqlx::sqlite::SqliteRow) -> ImportRun {
+    ImportRun {
+        id: row.get("id"),
+        file_name: row.get("file_name"),
+        content_hash: row.get("content_hash"),
+        imported_at: row.get("imported_at"),
+        inserted: row.get("inserted"),
+        updated: row.get("updated"),
+        skipped: row.get("skipped"),
+        rolled_back_at: row.get("rolled_back_at"),
+        cancelled_at: row.get("cancelled_at"),
+        profile: row.get("profile"),
+    }
+}
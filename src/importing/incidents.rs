@@ -0,0 +1,372 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::profile::{FieldKind, ImportProfile, ProfileField};
+use super::FieldValue;
+
+/// Incidents still counting towards an agent's load.
+pub const OPEN_CONDITION: &str = "resolved_at IS NULL \
+    AND LOWER(COALESCE(state, '')) NOT IN ('resolved', 'closed', 'canceled', 'cancelled')";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentRecord {
+    pub number: i64,
+    pub title: Option<String>,
+    pub state: Option<String>,
+    pub priority: Option<String>,
+    pub category: Option<String>,
+    pub subcategory: Option<String>,
+    pub assignee_name: Option<String>,
+    pub requester: Option<String>,
+    pub site: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub resolved_at: Option<String>,
+    #[serde(default)]
+    pub extras: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IncidentField {
+    Number,
+    Title,
+    State,
+    Priority,
+    Category,
+    Subcategory,
+    AssigneeName,
+    Requester,
+    Site,
+    CreatedAt,
+    UpdatedAt,
+    ResolvedAt,
+    Extras,
+}
+
+impl IncidentField {
+    pub const ALL: [IncidentField; 13] = [
+        IncidentField::Number,
+        IncidentField::Title,
+        IncidentField::State,
+        IncidentField::Priority,
+        IncidentField::Category,
+        IncidentField::Subcategory,
+        IncidentField::AssigneeName,
+        IncidentField::Requester,
+        IncidentField::Site,
+        IncidentField::CreatedAt,
+        IncidentField::UpdatedAt,
+        IncidentField::ResolvedAt,
+        IncidentField::Extras,
+    ];
+}
+
+impl ProfileField for IncidentField {
+    fn column(self) -> &'static str {
+        match self {
+            IncidentField::Number => "number",
+            IncidentField::Title => "title",
+            IncidentField::State => "state",
+            IncidentField::Priority => "priority",
+            IncidentField::Category => "category",
+            IncidentField::Subcategory => "subcategory",
+            IncidentField::AssigneeName => "assignee_name",
+            IncidentField::Requester => "requester",
+            IncidentField::Site => "site",
+            IncidentField::CreatedAt => "created_at",
+            IncidentField::UpdatedAt => "updated_at",
+            IncidentField::ResolvedAt => "resolved_at",
+            IncidentField::Extras => "extras",
+        }
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            IncidentField::Number => "Number",
+            IncidentField::Title => "Title",
+            IncidentField::State => "State",
+            IncidentField::Priority => "Priority",
+            IncidentField::Category => "Category",
+            IncidentField::Subcategory => "Subcategory",
+            IncidentField::AssigneeName => "Assignee Name",
+            IncidentField::Requester => "Requester",
+            IncidentField::Site => "Site",
+            IncidentField::CreatedAt => "Created At (Timestamp)",
+            IncidentField::UpdatedAt => "Updated At (Timestamp)",
+            IncidentField::ResolvedAt => "Resolved At",
+            IncidentField::Extras => "Custom fields",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            IncidentField::Number => "number",
+            IncidentField::Title => "title",
+            IncidentField::State => "state",
+            IncidentField::Priority => "priority",
+            IncidentField::Category => "category",
+            IncidentField::Subcategory => "subcategory",
+            IncidentField::AssigneeName => "assignee",
+            IncidentField::Requester => "requester",
+            IncidentField::Site => "site",
+            IncidentField::CreatedAt => "created_at",
+            IncidentField::UpdatedAt => "updated_at",
+            IncidentField::ResolvedAt => "resolved_at",
+            IncidentField::Extras => "custom_fields",
+        }
+    }
+
+    fn kind(self) -> FieldKind {
+        match self {
+            IncidentField::Number => FieldKind::Integer,
+            IncidentField::CreatedAt | IncidentField::UpdatedAt | IncidentField::ResolvedAt => {
+                FieldKind::Timestamp
+            }
+            IncidentField::Extras => FieldKind::Extras,
+            _ => FieldKind::Text,
+        }
+    }
+
+    /// Older exports use shorter headers for a few fields.
+    fn matches_header(self, header: &str) -> bool {
+        let alias = match self {
+            IncidentField::Title => Some("Name"),
+            IncidentField::AssigneeName => Some("Assignee"),
+            IncidentField::CreatedAt => Some("Created At"),
+            IncidentField::UpdatedAt => Some("Updated At"),
+            _ => None,
+        };
+        header == self.header() || alias == Some(header)
+    }
+}
+
+impl fmt::Display for IncidentField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.header())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IncidentProfile;
+
+impl ImportProfile for IncidentProfile {
+    type Field = IncidentField;
+    type Record = IncidentRecord;
+
+    const NAME: &'static str = "incidents";
+    const TABLE: &'static str = "incidents";
+    const KEY: IncidentField = IncidentField::Number;
+    const FIELDS: &'static [IncidentField] = &IncidentField::ALL;
+    const EXTRAS: IncidentField = IncidentField::Extras;
+    const TITLE: IncidentField = IncidentField::Title;
+    const CREATED_AT: IncidentField = IncidentField::CreatedAt;
+    const KEEPS_HISTORY: bool = true;
+    const STATE: Option<IncidentField> = Some(IncidentField::State);
+    const SITE: Option<IncidentField> = Some(IncidentField::Site);
+    const ASSIGNEE: Option<IncidentField> = Some(IncidentField::AssigneeName);
+    const UPDATED_AT: Option<IncidentField> = Some(IncidentField::UpdatedAt);
+
+    fn key(record: &IncidentRecord) -> i64 {
+        record.number
+    }
+
+    fn value(record: &IncidentRecord, field: IncidentField) -> FieldValue {
+        match field {
+            IncidentField::Number => FieldValue::Integer(Some(record.number)),
+            IncidentField::Title => FieldValue::Text(record.title.clone()),
+            IncidentField::State => FieldValue::Text(record.state.clone()),
+            IncidentField::Priority => FieldValue::Text(record.priority.clone()),
+            IncidentField::Category => FieldValue::Text(record.category.clone()),
+            IncidentField::Subcategory => FieldValue::Text(record.subcategory.clone()),
+            IncidentField::AssigneeName => FieldValue::Text(record.assignee_name.clone()),
+            IncidentField::Requester => FieldValue::Text(record.requester.clone()),
+            IncidentField::Site => FieldValue::Text(record.site.clone()),
+            IncidentField::CreatedAt => FieldValue::Text(record.created_at.clone()),
+            IncidentField::UpdatedAt => FieldValue::Text(record.updated_at.clone()),
+            IncidentField::ResolvedAt => FieldValue::Text(record.resolved_at.clone()),
+            IncidentField::Extras => FieldValue::from_extras(&record.extras),
+        }
+    }
+
+    fn build(mut value: impl FnMut(IncidentField) -> FieldValue) -> IncidentRecord {
+        IncidentRecord {
+            number: value(IncidentField::Number).into_integer().unwrap_or_default(),
+            title: value(IncidentField::Title).into_text(),
+            state: value(IncidentField::State).into_text(),
+            priority: value(IncidentField::Priority).into_text(),
+            category: value(IncidentField::Category).into_text(),
+            subcategory: value(IncidentField::Subcategory).into_text(),
+            assignee_name: value(IncidentField::AssigneeName).into_text(),
+            requester: value(IncidentField::Requester).into_text(),
+            site: value(IncidentField::Site).into_text(),
+            created_at: value(IncidentField::CreatedAt).into_text(),
+            updated_at: value(IncidentField::UpdatedAt).into_text(),
+            resolved_at: value(IncidentField::ResolvedAt).into_text(),
+            extras: value(IncidentField::Extras).into_extras(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::importing::csv_format::CsvFormat;
+    use crate::importing::mapping::ColumnMapping;
+    use crate::importing::migrations::prepare_database;
+    use crate::importing::policy::PresetDecisions;
+    use crate::importing::{
+        apply_duplicate_decisions, history, import_mapped_csv, load_pending_duplicates,
+        prepare_import, CsvReadOptions, DuplicateDecision, ImportCancel, ImportEvent,
+        ImportProgress,
+    };
+
+    async fn import(db_path: &Path, path: PathBuf) -> ImportProgress<IncidentProfile> {
+        let prepared = prepare_import::<IncidentProfile>(
+            db_path.to_path_buf(),
+            path.clone(),
+            None,
+            CsvFormat::default(),
+        )
+        .await
+        .unwrap();
+        let mapping = ColumnMapping::solar_winds_default().fit_to(&prepared.headers);
+        let events: Vec<_> = import_mapped_csv(
+            db_path.to_path_buf(),
+            path,
+            mapping,
+            CsvReadOptions::default(),
+            prepared.source,
+            PresetDecisions::new(),
+            ImportCancel::default(),
+        )
+        .collect()
+        .await;
+
+        let mut total = ImportProgress::default();
+        for event in events {
+            match event {
+                ImportEvent::Started { .. } => {}
+                ImportEvent::Progress(progress) => {
+                    total.inserted += progress.inserted;
+                    total.skipped += progress.skipped;
+                    total.duplicates.extend(progress.duplicates);
+                }
+                ImportEvent::Finished(result) => {
+                    result.unwrap();
+                }
+                other => panic!("unexpected event {other:?}"),
+            }
+        }
+        total
+    }
+
+    #[tokio::test]
+    async fn incident_imports_are_logged_reviewed_and_rolled_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("incidents.sqlite");
+        prepare_database(db_path.clone()).await.unwrap();
+        let first = dir.path().join("first.csv");
+        let second = dir.path().join("second.csv");
+        std::fs::write(
+            &first,
+            "Number,Title,State\n1,Printer jam,Open\n2,VPN down,Open\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &second,
+            "Number,Name,State\n1,Printer jam,Resolved\n2,VPN down,Open\n",
+        )
+        .unwrap();
+
+        let outcome = import(&db_path, first).await;
+        assert_eq!(outcome.inserted, 2);
+
+        let outcome = import(&db_path, second).await;
+        assert_eq!(outcome.skipped, 1);
+        assert_eq!(outcome.duplicates.len(), 1);
+        assert_eq!(outcome.duplicates[0].summary.number, 1);
+
+        let pending = load_pending_duplicates::<IncidentProfile>(db_path.clone())
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].record.state.as_deref(), Some("Resolved"));
+
+        let runs = history::load_runs(db_path.clone()).await.unwrap();
+        assert_eq!(runs.len(), 2);
+        assert!(runs.iter().all(history::ImportRun::is_incident_export));
+
+        let first_run = runs.iter().map(|run| run.id).min().unwrap();
+        let err = history::roll_back_run::<crate::importing::NewHireProfile>(
+            db_path.clone(),
+            first_run,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.contains("not \"new_hire\""), "{err}");
+        let rolled_back = history::roll_back_run::<IncidentProfile>(db_path.clone(), first_run)
+            .await
+            .unwrap();
+        assert_eq!(rolled_back.removed, 2);
+    }
+
+    #[tokio::test]
+    async fn state_changes_are_timed_by_the_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("transitions.sqlite");
+        prepare_database(db_path.clone()).await.unwrap();
+        let first = dir.path().join("first.csv");
+        let second = dir.path().join("second.csv");
+        let header = "Number,State,Created At (Timestamp),Updated At (Timestamp)";
+        std::fs::write(
+            &first,
+            format!("{header}\n5,Open,2024-01-02 08:00,2024-01-02 08:00\n"),
+        )
+        .unwrap();
+        std::fs::write(
+            &second,
+            format!("{header}\n5,Resolved,2024-01-02 08:00,2024-01-04 17:30\n"),
+        )
+        .unwrap();
+
+        import(&db_path, first).await;
+        let outcome = import(&db_path, second).await;
+        let decisions = outcome
+            .duplicates
+            .into_iter()
+            .map(|entry| (entry.run_id, entry.record, DuplicateDecision::Overwrite))
+            .collect();
+        apply_duplicate_decisions::<IncidentProfile>(db_path.clone(), decisions)
+            .await
+            .unwrap();
+
+        let pool = crate::importing::open_pool(&db_path).await.unwrap();
+        let transitions: Vec<(Option<String>, String, String)> = sqlx::query_as(
+            r#"
+            SELECT from_state, to_state, transitioned_at FROM state_transitions
+            WHERE profile = 'incidents'
+            ORDER BY id
+            "#,
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            transitions,
+            [
+                (None, "Open".to_owned(), "2024-01-02T08:00:00Z".to_owned()),
+                (
+                    Some("Open".to_owned()),
+                    "Resolved".to_owned(),
+                    "2024-01-04T17:30:00Z".to_owned()
+                ),
+            ]
+        );
+    }
+}
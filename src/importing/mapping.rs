@@ -0,0 +1,616 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::PathBuf;
+
+use csv_async::StringRecord;
+use sqlx::{Row, SqliteConnection};
+
+use super::profile::{build_record, field_by_column, FieldKind, ImportProfile, ProfileField};
+use super::timestamps::TimestampSettings;
+use super::{open_pool, NewHireProfile};
+
+pub const DEFAULT_PROFILE_NAME: &str = "SolarWinds Default";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecordField {
+    Number,
+    State,
+    Title,
+    AssigneeName,
+    Requester,
+    CreatedAt,
+    Site,
+    Division,
+    EmployeeType,
+    StartDate,
+    SuccessFactorsDateEntered,
+    Day1OrDay3,
+    ToFirstResponseBusiness,
+    ToResolutionBusiness,
+    ToResolutionElapsed,
+    SlaBreaches,
+    ResolvedAt,
+    ItLeadTimeElapsed,
+    ItLeadTimeBusiness,
+    /// Unmapped columns; stored but never mapped to a header.
+    Extras,
+}
+
+impl RecordField {
+    pub const ALL: [RecordField; 19] = [
+        RecordField::Number,
+        RecordField::State,
+        RecordField::Title,
+        RecordField::AssigneeName,
+        RecordField::Requester,
+        RecordField::CreatedAt,
+        RecordField::Site,
+        RecordField::Division,
+        RecordField::EmployeeType,
+        RecordField::StartDate,
+        RecordField::SuccessFactorsDateEntered,
+        RecordField::Day1OrDay3,
+        RecordField::ToFirstResponseBusiness,
+        RecordField::ToResolutionBusiness,
+        RecordField::ToResolutionElapsed,
+        RecordField::SlaBreaches,
+        RecordField::ResolvedAt,
+        RecordField::ItLeadTimeElapsed,
+        RecordField::ItLeadTimeBusiness,
+    ];
+
+    pub const STORED: [RecordField; 20] = [
+        RecordField::Number,
+        RecordField::State,
+        RecordField::Title,
+        RecordField::AssigneeName,
+        RecordField::Requester,
+        RecordField::CreatedAt,
+        RecordField::Site,
+        RecordField::Division,
+        RecordField::EmployeeType,
+        RecordField::StartDate,
+        RecordField::SuccessFactorsDateEntered,
+        RecordField::Day1OrDay3,
+        RecordField::ToFirstResponseBusiness,
+        RecordField::ToResolutionBusiness,
+        RecordField::ToResolutionElapsed,
+        RecordField::SlaBreaches,
+        RecordField::ResolvedAt,
+        RecordField::ItLeadTimeElapsed,
+        RecordField::ItLeadTimeBusiness,
+        RecordField::Extras,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            RecordField::Number => "number",
+            RecordField::State => "state",
+            RecordField::Title => "title",
+            RecordField::AssigneeName => "assignee_name",
+            RecordField::Requester => "requester",
+            RecordField::CreatedAt => "created_at",
+            RecordField::Site => "site",
+            RecordField::Division => "division",
+            RecordField::EmployeeType => "employee_type",
+            RecordField::StartDate => "start_date",
+            RecordField::SuccessFactorsDateEntered => "success_factors_date_entered",
+            RecordField::Day1OrDay3 => "day_1_or_day_3",
+            RecordField::ToFirstResponseBusiness => "to_first_response_business",
+            RecordField::ToResolutionBusiness => "to_resolution_business",
+            RecordField::ToResolutionElapsed => "to_resolution_elapsed",
+            RecordField::SlaBreaches => "sla_breaches",
+            RecordField::ResolvedAt => "resolved_at",
+            RecordField::ItLeadTimeElapsed => "it_lead_time_elapsed",
+            RecordField::ItLeadTimeBusiness => "it_lead_time_business",
+            RecordField::Extras => "extras",
+        }
+    }
+
+    pub fn default_header(self) -> &'static str {
+        match self {
+            RecordField::Number => "Number",
+            RecordField::State => "State",
+            RecordField::Title => "Title",
+            RecordField::AssigneeName => "Assignee Name",
+            RecordField::Requester => "Requester",
+            RecordField::CreatedAt => "Created At (Timestamp)",
+            RecordField::Site => "Site",
+            RecordField::Division => "Division",
+            RecordField::EmployeeType => "Employee Type",
+            RecordField::StartDate => "Start Date",
+            RecordField::SuccessFactorsDateEntered => "Success Factors Date entered",
+            RecordField::Day1OrDay3 => "Day 1 or Day 3",
+            RecordField::ToFirstResponseBusiness => "To First Response (Business)",
+            RecordField::ToResolutionBusiness => "To Resolution (Business)",
+            RecordField::ToResolutionElapsed => "To Resolution (Elapsed)",
+            RecordField::SlaBreaches => "SLA Breaches",
+            RecordField::ResolvedAt => "Resolved At",
+            RecordField::ItLeadTimeElapsed => "IT Lead Time (Elapsed)",
+            RecordField::ItLeadTimeBusiness => "IT Lead Time (Business)",
+            RecordField::Extras => "Custom fields",
+        }
+    }
+
+    pub fn diff_label(self) -> &'static str {
+        match self {
+            RecordField::Number => "number",
+            RecordField::State => "state",
+            RecordField::Title => "title",
+            RecordField::AssigneeName => "assignee",
+            RecordField::Requester => "requester",
+            RecordField::CreatedAt => "created_at",
+            RecordField::Site => "site",
+            RecordField::Division => "division",
+            RecordField::EmployeeType => "employee_type",
+            RecordField::StartDate => "start_date",
+            RecordField::SuccessFactorsDateEntered => "success_factors",
+            RecordField::Day1OrDay3 => "day_1_or_day_3",
+            RecordField::ToFirstResponseBusiness => "first_response_business",
+            RecordField::ToResolutionBusiness => "resolution_business",
+            RecordField::ToResolutionElapsed => "resolution_elapsed",
+            RecordField::SlaBreaches => "sla_breaches",
+            RecordField::ResolvedAt => "resolved_at",
+            RecordField::ItLeadTimeElapsed => "it_lead_time_elapsed",
+            RecordField::ItLeadTimeBusiness => "it_lead_time_business",
+            RecordField::Extras => "custom_fields",
+        }
+    }
+}
+
+impl fmt::Display for RecordField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.default_header())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderChoice(pub Option<String>);
+
+impl fmt::Display for HeaderChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(header) => f.write_str(header),
+            None => f.write_str("(not mapped)"),
+        }
+    }
+}
+
+pub fn mapped_fields<P: ImportProfile>() -> impl Iterator<Item = P::Field> {
+    P::FIELDS
+        .iter()
+        .copied()
+        .filter(|field| *field != P::EXTRAS)
+}
+
+#[derive(Debug, Clone)]
+pub struct ColumnMapping<P: ImportProfile = NewHireProfile> {
+    pub name: String,
+    headers: HashMap<P::Field, String>,
+}
+
+impl<P: ImportProfile> ColumnMapping<P> {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            headers: HashMap::new(),
+        }
+    }
+
+    pub fn solar_winds_default() -> Self {
+        let mut mapping = Self::new(DEFAULT_PROFILE_NAME);
+        for field in mapped_fields::<P>() {
+            mapping.set_header(field, Some(field.header().to_owned()));
+        }
+        mapping
+    }
+
+    pub fn header(&self, field: P::Field) -> Option<&str> {
+        self.headers.get(&field).map(String::as_str)
+    }
+
+    pub fn set_header(&mut self, field: P::Field, header: Option<String>) {
+        match header {
+            Some(header) => {
+                self.headers.insert(field, header);
+            }
+            None => {
+                self.headers.remove(&field);
+            }
+        }
+    }
+
+    pub fn missing_headers(&self, headers: &[String]) -> Vec<String> {
+        mapped_fields::<P>()
+            .filter_map(|field| self.header(field))
+            .filter(|header| !headers.iter().any(|candidate| candidate == header))
+            .map(str::to_owned)
+            .collect()
+    }
+
+    pub fn unmapped_headers(&self, headers: &[String]) -> Vec<String> {
+        headers
+            .iter()
+            .filter(|header| !self.headers.values().any(|mapped| mapped == *header))
+            .cloned()
+            .collect()
+    }
+
+    pub fn matches(&self, headers: &[String]) -> bool {
+        self.header(P::KEY).is_some() && self.missing_headers(headers).is_empty()
+    }
+
+    /// Unmapped fields are filled in from headers with the stock SolarWinds name.
+    pub fn fit_to(&self, headers: &[String]) -> Self {
+        let mut fitted = Self::new(self.name.clone());
+        for field in mapped_fields::<P>() {
+            let header = self
+                .header(field)
+                .filter(|header| headers.iter().any(|candidate| candidate == header))
+                .or_else(|| {
+                    headers
+                        .iter()
+                        .map(String::as_str)
+                        .find(|candidate| field.matches_header(candidate))
+                });
+            fitted.set_header(field, header.map(str::to_owned));
+        }
+        fitted
+    }
+
+    pub fn resolve(&self, headers: &StringRecord) -> Result<ResolvedMapping<P::Field>, String> {
+        let mut indexes = HashMap::new();
+        for field in mapped_fields::<P>() {
+            let Some(header) = self.header(field) else {
+                continue;
+            };
+            let index = headers
+                .iter()
+                .position(|candidate| candidate == header)
+                .ok_or_else(|| format!("CSV is missing the \"{header}\" column"))?;
+            indexes.insert(field, (index, header.to_owned()));
+        }
+
+        if !indexes.contains_key(&P::KEY) {
+            return Err(format!(
+                "The column mapping does not map the {} field",
+                P::KEY.header()
+            ));
+        }
+
+        Ok(ResolvedMapping::new(indexes, headers))
+    }
+}
+
+impl ProfileField for RecordField {
+    fn column(self) -> &'static str {
+        self.key()
+    }
+
+    fn header(self) -> &'static str {
+        self.default_header()
+    }
+
+    fn label(self) -> &'static str {
+        self.diff_label()
+    }
+
+    fn kind(self) -> FieldKind {
+        match self {
+            RecordField::Number
+            | RecordField::ItLeadTimeElapsed
+            | RecordField::ItLeadTimeBusiness => FieldKind::Integer,
+            RecordField::CreatedAt
+            | RecordField::StartDate
+            | RecordField::ResolvedAt
+            | RecordField::SuccessFactorsDateEntered => FieldKind::Timestamp,
+            RecordField::Extras => FieldKind::Extras,
+            _ => FieldKind::Text,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedMapping<F = RecordField> {
+    indexes: HashMap<F, (usize, String)>,
+    extras: Vec<(usize, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CellError {
+    pub column: String,
+    pub reason: String,
+}
+
+impl fmt::Display for CellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column \"{}\": {}", self.column, self.reason)
+    }
+}
+
+impl<F: ProfileField> ResolvedMapping<F> {
+    fn new(indexes: HashMap<F, (usize, String)>, headers: &StringRecord) -> Self {
+        let extras = headers
+            .iter()
+            .enumerate()
+            .filter(|(index, header)| {
+                !header.trim().is_empty()
+                    && !indexes.values().any(|(mapped, _)| mapped == index)
+            })
+            .map(|(index, header)| (index, header.trim().to_owned()))
+            .collect();
+
+        ResolvedMapping { indexes, extras }
+    }
+
+    pub fn record<P: ImportProfile<Field = F>>(
+        &self,
+        row: &StringRecord,
+        timestamps: &TimestampSettings,
+    ) -> Result<Option<P::Record>, CellError> {
+        build_record::<P>(
+            |field| self.text(row, field),
+            |field| self.header(field).unwrap_or(field.header()).to_owned(),
+            self.extra_values(row),
+            timestamps,
+        )
+    }
+
+    fn extra_values(&self, row: &StringRecord) -> BTreeMap<String, String> {
+        self.extras
+            .iter()
+            .filter_map(|(index, header)| {
+                let value = row.get(*index)?.trim();
+                (!value.is_empty()).then(|| (header.clone(), value.to_owned()))
+            })
+            .collect()
+    }
+
+    fn text(&self, row: &StringRecord, field: F) -> Option<String> {
+        let (index, _) = self.indexes.get(&field)?;
+        row.get(*index)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_owned)
+    }
+
+    pub fn key<P: ImportProfile<Field = F>>(&self, row: &StringRecord) -> Option<i64> {
+        self.text(row, P::KEY)?.parse().ok()
+    }
+
+    pub fn header(&self, field: F) -> Option<&str> {
+        self.indexes.get(&field).map(|(_, header)| header.as_str())
+    }
+}
+
+/// The built-in default comes first unless a stored mapping overrides it.
+pub async fn load_profiles<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+) -> Result<Vec<ColumnMapping<P>>, String> {
+    let rows = sqlx::query(
+        r#"
+        SELECT profile, field, header
+        FROM column_mapping_profiles
+        WHERE import_profile = ?
+        ORDER BY profile
+        "#,
+    )
+    .bind(P::NAME)
+    .fetch_all(conn)
+    .await
+    .map_err(|err| format!("Failed to load column mappings: {err}"))?;
+
+    let mut profiles: Vec<ColumnMapping<P>> = Vec::new();
+    for row in rows {
+        let profile: String = row.get("profile");
+        let field: String = row.get("field");
+        let header: String = row.get("header");
+
+        let Some(field) = field_by_column::<P>(&field) else {
+            continue;
+        };
+
+        if profiles.last().is_none_or(|mapping| mapping.name != profile) {
+            profiles.push(ColumnMapping::new(profile));
+        }
+        if let Some(mapping) = profiles.last_mut() {
+            mapping.set_header(field, Some(header));
+        }
+    }
+
+    if !profiles
+        .iter()
+        .any(|mapping| mapping.name == DEFAULT_PROFILE_NAME)
+    {
+        profiles.insert(0, ColumnMapping::solar_winds_default());
+    }
+
+    Ok(profiles)
+}
+
+pub async fn save_profile<P: ImportProfile>(
+    db_path: PathBuf,
+    mapping: ColumnMapping<P>,
+) -> Result<ColumnMapping<P>, String> {
+    let pool = open_pool(&db_path).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|err| format!("Failed to start transaction: {err}"))?;
+
+    sqlx::query("DELETE FROM column_mapping_profiles WHERE import_profile = ? AND profile = ?")
+        .bind(P::NAME)
+        .bind(&mapping.name)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| format!("Failed to save column mapping: {err}"))?;
+
+    for field in mapped_fields::<P>() {
+        let Some(header) = mapping.header(field) else {
+            continue;
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO column_mapping_profiles (import_profile, profile, field, header)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(P::NAME)
+        .bind(&mapping.name)
+        .bind(field.column())
+        .bind(header)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| format!("Failed to save column mapping: {err}"))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|err| format!("Failed to save column mapping: {err}"))?;
+
+    Ok(mapping)
+}
+
+#[derive(Debug, Clone)]
+pub struct MappingEditor<P: ImportProfile = NewHireProfile> {
+    pub path: PathBuf,
+    pub headers: Vec<String>,
+    pub profiles: Vec<ColumnMapping<P>>,
+    pub selected_profile: Option<String>,
+    pub mapping: ColumnMapping<P>,
+    pub profile_name: String,
+    pub overwrite_confirmed: bool,
+}
+
+impl<P: ImportProfile> MappingEditor<P> {
+    pub fn new(path: PathBuf, headers: Vec<String>, profiles: Vec<ColumnMapping<P>>) -> Self {
+        let base = profiles
+            .iter()
+            .find(|mapping| mapping.name == DEFAULT_PROFILE_NAME)
+            .cloned()
+            .unwrap_or_else(ColumnMapping::solar_winds_default);
+
+        let mut editor = Self {
+            path,
+            headers,
+            profiles,
+            selected_profile: None,
+            mapping: ColumnMapping::new(String::new()),
+            profile_name: String::new(),
+            overwrite_confirmed: false,
+        };
+        editor.select_profile(base);
+        editor
+    }
+
+    /// The built-in profile is never offered as the name to save under.
+    pub fn select_profile(&mut self, profile: ColumnMapping<P>) {
+        self.mapping = profile.fit_to(&self.headers);
+        self.profile_name = if profile.name == DEFAULT_PROFILE_NAME {
+            String::new()
+        } else {
+            profile.name.clone()
+        };
+        self.selected_profile = Some(profile.name);
+        self.overwrite_confirmed = false;
+    }
+
+    pub fn profile_names(&self) -> Vec<String> {
+        self.profiles
+            .iter()
+            .map(|mapping| mapping.name.clone())
+            .collect()
+    }
+
+    pub fn header_choices(&self) -> Vec<HeaderChoice> {
+        std::iter::once(HeaderChoice(None))
+            .chain(self.headers.iter().cloned().map(Some).map(HeaderChoice))
+            .collect()
+    }
+
+    pub fn unknown_headers(&self) -> Vec<String> {
+        self.profiles
+            .iter()
+            .find(|mapping| Some(&mapping.name) == self.selected_profile.as_ref())
+            .map(|mapping| mapping.unmapped_headers(&self.headers))
+            .unwrap_or_default()
+    }
+
+    pub fn can_import(&self) -> bool {
+        self.mapping.header(P::KEY).is_some()
+    }
+
+    pub fn save_error(&self) -> Option<String> {
+        (self.profile_name.trim() == DEFAULT_PROFILE_NAME).then(|| {
+            format!("\"{DEFAULT_PROFILE_NAME}\" is built in. Save the mapping under another name.")
+        })
+    }
+
+    /// Fields of the stored profile named `profile_name` that saving would drop.
+    pub fn dropped_fields(&self) -> Vec<P::Field> {
+        let name = self.profile_name.trim();
+        let Some(stored) = self.profiles.iter().find(|mapping| mapping.name == name) else {
+            return Vec::new();
+        };
+
+        mapped_fields::<P>()
+            .filter(|field| stored.header(*field).is_some() && self.mapping.header(*field).is_none())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(headers: &[&str], profiles: Vec<ColumnMapping>) -> MappingEditor {
+        let headers = headers.iter().map(|&header| header.to_owned()).collect();
+        MappingEditor::new(PathBuf::from("tickets.csv"), headers, profiles)
+    }
+
+    #[test]
+    fn built_in_profile_is_not_offered_as_save_name() {
+        let mut editor = editor(&["Number", "State"], vec![ColumnMapping::solar_winds_default()]);
+        assert_eq!(editor.selected_profile.as_deref(), Some(DEFAULT_PROFILE_NAME));
+        assert_eq!(editor.profile_name, "");
+        assert_eq!(editor.save_error(), None);
+
+        editor.profile_name = DEFAULT_PROFILE_NAME.to_owned();
+        assert!(editor.save_error().is_some());
+    }
+
+    #[test]
+    fn reports_fields_dropped_from_stored_profile() {
+        let mut stored = ColumnMapping::new("Weekly");
+        stored.set_header(RecordField::Number, Some("Ticket".to_owned()));
+        stored.set_header(RecordField::State, Some("Status".to_owned()));
+        stored.set_header(RecordField::Site, Some("Location".to_owned()));
+
+        let mut editor = editor(
+            &["Ticket", "Status"],
+            vec![ColumnMapping::solar_winds_default(), stored.clone()],
+        );
+        editor.select_profile(stored);
+        assert_eq!(editor.profile_name, "Weekly");
+        assert_eq!(editor.dropped_fields(), [RecordField::Site]);
+
+        editor.profile_name = "Weekly (short)".to_owned();
+        assert!(editor.dropped_fields().is_empty());
+    }
+
+    #[test]
+    fn fit_to_keeps_present_headers_and_stock_names() {
+        let mut mapping = ColumnMapping::<NewHireProfile>::new("Custom");
+        mapping.set_header(RecordField::Number, Some("Ticket".to_owned()));
+        mapping.set_header(RecordField::Site, Some("Location".to_owned()));
+
+        let headers = ["Ticket".to_owned(), "State".to_owned()];
+        let fitted = mapping.fit_to(&headers);
+        assert_eq!(fitted.header(RecordField::Number), Some("Ticket"));
+        assert_eq!(fitted.header(RecordField::Site), None);
+        assert_eq!(fitted.header(RecordField::State), Some("State"));
+        assert!(fitted.matches(&headers));
+    }
+}
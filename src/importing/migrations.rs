@@ -0,0 +1,701 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+
+use super::duration::{parse_minutes, DURATION_COLUMNS};
+use super::open_pool;
+use super::profile::ProfileField;
+use super::timestamps::stored_settings;
+
+/// A migration's version is its position in [`MIGRATIONS`] plus one, so steps
+/// are only ever appended, never edited.
+enum Migration {
+    Sql(&'static [&'static str]),
+    /// Databases from before versioned migrations may already have some columns.
+    AddColumns {
+        table: &'static str,
+        columns: &'static [(&'static str, &'static str)],
+    },
+    BackfillMinutes,
+    NormalizeTimestamps,
+    SeedRecordHistory,
+    DeriveStateTransitions,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    // Tables of the first release.
+    Migration::Sql(&[
+        r#"
+        CREATE TABLE IF NOT EXISTS new_hire_metrics (
+            number INTEGER PRIMARY KEY,
+            state TEXT,
+            title TEXT,
+            assignee_name TEXT,
+            requester TEXT,
+            created_at TEXT,
+            site TEXT,
+            division TEXT,
+            employee_type TEXT,
+            start_date TEXT,
+            success_factors_date_entered TEXT,
+            day_1_or_day_3 TEXT,
+            to_first_response_business TEXT,
+            to_resolution_business TEXT,
+            to_resolution_elapsed TEXT,
+            sla_breaches TEXT,
+            resolved_at TEXT,
+            it_lead_time_elapsed INTEGER,
+            it_lead_time_business INTEGER
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS column_mapping_profiles (
+            profile TEXT NOT NULL,
+            field TEXT NOT NULL,
+            header TEXT NOT NULL,
+            PRIMARY KEY (profile, field)
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS import_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            file_name TEXT NOT NULL,
+            content_hash TEXT NOT NULL,
+            imported_at TEXT NOT NULL,
+            inserted INTEGER NOT NULL DEFAULT 0,
+            updated INTEGER NOT NULL DEFAULT 0,
+            skipped INTEGER NOT NULL DEFAULT 0
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS import_run_records (
+            run_id INTEGER NOT NULL REFERENCES import_runs (id),
+            number INTEGER NOT NULL,
+            action TEXT NOT NULL
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS duplicate_policies (
+            position INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            fields TEXT NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS pending_duplicates (
+            run_id INTEGER NOT NULL,
+            number INTEGER NOT NULL,
+            record TEXT NOT NULL,
+            PRIMARY KEY (run_id, number)
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS app_settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )
+        "#,
+    ]),
+    // Undoing import runs.
+    Migration::AddColumns {
+        table: "import_runs",
+        columns: &[("rolled_back_at", "TEXT")],
+    },
+    Migration::AddColumns {
+        table: "import_run_records",
+        columns: &[("previous", "TEXT")],
+    },
+    // Durations in minutes.
+    Migration::AddColumns {
+        table: "new_hire_metrics",
+        columns: &[
+            ("to_first_response_business_minutes", "INTEGER"),
+            ("to_resolution_business_minutes", "INTEGER"),
+            ("to_resolution_elapsed_minutes", "INTEGER"),
+        ],
+    },
+    Migration::BackfillMinutes,
+    // Timestamps stored as UTC.
+    Migration::NormalizeTimestamps,
+    // General incident export.
+    Migration::Sql(&[r#"
+        CREATE TABLE IF NOT EXISTS incidents (
+            number INTEGER PRIMARY KEY,
+            title TEXT,
+            state TEXT,
+            priority TEXT,
+            category TEXT,
+            subcategory TEXT,
+            assignee_name TEXT,
+            requester TEXT,
+            site TEXT,
+            created_at TEXT,
+            updated_at TEXT,
+            resolved_at TEXT
+        )
+        "#]),
+    // Unmapped columns kept as JSON.
+    Migration::AddColumns {
+        table: "new_hire_metrics",
+        columns: &[("extras", "TEXT")],
+    },
+    Migration::AddColumns {
+        table: "incidents",
+        columns: &[("extras", "TEXT")],
+    },
+    // Every version of a ticket across imports.
+    Migration::Sql(&[
+        r#"
+        CREATE TABLE IF NOT EXISTS record_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            number INTEGER NOT NULL,
+            run_id INTEGER REFERENCES import_runs (id),
+            recorded_at TEXT NOT NULL,
+            record TEXT NOT NULL
+        )
+        "#,
+        "CREATE INDEX IF NOT EXISTS record_history_number ON record_history (number)",
+    ]),
+    Migration::SeedRecordHistory,
+    // State transitions inferred from successive imports.
+    Migration::Sql(&[
+        r#"
+        CREATE TABLE IF NOT EXISTS state_transitions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            number INTEGER NOT NULL,
+            run_id INTEGER REFERENCES import_runs (id),
+            from_state TEXT,
+            to_state TEXT NOT NULL,
+            transitioned_at TEXT NOT NULL,
+            site TEXT,
+            assignee_name TEXT
+        )
+        "#,
+        "CREATE INDEX IF NOT EXISTS state_transitions_number ON state_transitions (number)",
+    ]),
+    Migration::DeriveStateTransitions,
+    // Soft delete of tickets missing from a full export.
+    Migration::AddColumns {
+        table: "new_hire_metrics",
+        columns: &[("deleted_at", "TEXT"), ("deleted_reason", "TEXT")],
+    },
+    // Imports stopped by the user keep the records processed until then.
+    Migration::AddColumns {
+        table: "import_runs",
+        columns: &[("cancelled_at", "TEXT")],
+    },
+    // Runs and duplicate policies of every import profile.
+    Migration::AddColumns {
+        table: "import_runs",
+        columns: &[("profile", "TEXT NOT NULL DEFAULT 'new_hire'")],
+    },
+    Migration::AddColumns {
+        table: "duplicate_policies",
+        columns: &[("profile", "TEXT NOT NULL DEFAULT 'new_hire'")],
+    },
+    // Ticket history of every import profile.
+    Migration::AddColumns {
+        table: "record_history",
+        columns: &[("profile", "TEXT NOT NULL DEFAULT 'new_hire'")],
+    },
+    Migration::AddColumns {
+        table: "state_transitions",
+        columns: &[("profile", "TEXT NOT NULL DEFAULT 'new_hire'")],
+    },
+    Migration::Sql(SKIP_STATELESS_VERSIONS),
+    // Column mappings of every import profile.
+    Migration::Sql(&[
+        "ALTER TABLE column_mapping_profiles RENAME TO column_mapping_profiles_old",
+        r#"
+        CREATE TABLE column_mapping_profiles (
+            import_profile TEXT NOT NULL DEFAULT 'new_hire',
+            profile TEXT NOT NULL,
+            field TEXT NOT NULL,
+            header TEXT NOT NULL,
+            PRIMARY KEY (import_profile, profile, field)
+        )
+        "#,
+        r#"
+        INSERT INTO column_mapping_profiles (profile, field, header)
+        SELECT profile, field, header FROM column_mapping_profiles_old
+        "#,
+        "DROP TABLE column_mapping_profiles_old",
+    ]),
+];
+
+/// Moves after a version without a state continue from the last known state.
+const SKIP_STATELESS_VERSIONS: &[&str] = &[
+    r#"
+    UPDATE state_transitions SET from_state = (
+        SELECT earlier.to_state FROM state_transitions AS earlier
+        WHERE earlier.profile = state_transitions.profile
+            AND earlier.number = state_transitions.number
+            AND earlier.id < state_transitions.id
+        ORDER BY earlier.id DESC
+        LIMIT 1
+    )
+    WHERE from_state IS NULL
+        AND id NOT IN (SELECT MIN(id) FROM state_transitions GROUP BY profile, number)
+    "#,
+    "DELETE FROM state_transitions WHERE from_state IS to_state",
+];
+
+pub async fn prepare_database(db_path: PathBuf) -> Result<(), String> {
+    let pool = open_pool(&db_path).await?;
+    migrate(&pool).await
+}
+
+async fn migrate(pool: &SqlitePool) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            applied_at TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await
+    .map_err(|err| format!("Failed to create schema: {err}"))?;
+
+    let current: i64 = sqlx::query("SELECT COALESCE(MAX(version), 0) AS version FROM schema_version")
+        .fetch_one(pool)
+        .await
+        .map_err(|err| format!("Failed to read schema version: {err}"))?
+        .get("version");
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+        let version = index as i64 + 1;
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|err| format!("Failed to start transaction: {err}"))?;
+
+        apply(&mut tx, migration)
+            .await
+            .map_err(|err| format!("Migration {version} failed: {err}"))?;
+
+        sqlx::query("INSERT INTO schema_version (version, applied_at) VALUES (?, ?)")
+            .bind(version)
+            .bind(chrono::Utc::now().to_rfc3339())
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| format!("Failed to record schema version: {err}"))?;
+
+        tx.commit()
+            .await
+            .map_err(|err| format!("Failed to commit transaction: {err}"))?;
+    }
+
+    Ok(())
+}
+
+async fn apply(conn: &mut SqliteConnection, migration: &Migration) -> Result<(), String> {
+    match migration {
+        Migration::Sql(statements) => {
+            for statement in *statements {
+                sqlx::query(statement)
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(|err| format!("Failed to update schema: {err}"))?;
+            }
+            Ok(())
+        }
+        Migration::AddColumns { table, columns } => {
+            for (column, definition) in *columns {
+                add_column(conn, table, column, definition).await?;
+            }
+            Ok(())
+        }
+        Migration::BackfillMinutes => backfill_minutes(conn).await,
+        Migration::NormalizeTimestamps => normalize_timestamps(conn).await,
+        Migration::SeedRecordHistory => seed_record_history(conn).await,
+        Migration::DeriveStateTransitions => derive_state_transitions(conn).await,
+    }
+}
+
+async fn add_column(
+    conn: &mut SqliteConnection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), String> {
+    let columns = sqlx::query(&format!("PRAGMA table_info({table})"))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|err| format!("Failed to inspect schema: {err}"))?;
+
+    if columns
+        .iter()
+        .any(|row| row.get::<String, _>("name") == column)
+    {
+        return Ok(());
+    }
+
+    sqlx::query(&format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"))
+        .execute(&mut *conn)
+        .await
+        .map_err(|err| format!("Failed to update schema: {err}"))?;
+
+    Ok(())
+}
+
+async fn backfill_minutes(conn: &mut SqliteConnection) -> Result<(), String> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+            number,
+            to_first_response_business,
+            to_resolution_business,
+            to_resolution_elapsed
+        FROM new_hire_metrics
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|err| format!("Failed to load durations: {err}"))?;
+
+    for row in rows {
+        let minutes = DURATION_COLUMNS.map(|(field, _)| {
+            row.try_get::<Option<String>, _>(field.column())
+                .unwrap_or(None)
+                .as_deref()
+                .and_then(parse_minutes)
+        });
+
+        sqlx::query(
+            r#"
+            UPDATE new_hire_metrics SET
+                to_first_response_business_minutes = ?,
+                to_resolution_business_minutes = ?,
+                to_resolution_elapsed_minutes = ?
+            WHERE number = ?
+            "#,
+        )
+        .bind(minutes[0])
+        .bind(minutes[1])
+        .bind(minutes[2])
+        .bind(row.get::<i64, _>("number"))
+        .execute(&mut *conn)
+        .await
+        .map_err(|err| format!("Failed to backfill durations: {err}"))?;
+    }
+
+    Ok(())
+}
+
+const TIMESTAMP_COLUMNS: [&str; 4] = [
+    "created_at",
+    "start_date",
+    "resolved_at",
+    "success_factors_date_entered",
+];
+
+/// Values that cannot be read are left as they are.
+async fn normalize_timestamps(conn: &mut SqliteConnection) -> Result<(), String> {
+    let settings = stored_settings(&mut *conn).await?;
+
+    let rows = sqlx::query(&format!(
+        "SELECT number, {} FROM new_hire_metrics",
+        TIMESTAMP_COLUMNS.join(", ")
+    ))
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|err| format!("Failed to load timestamps: {err}"))?;
+
+    for row in rows {
+        let number: i64 = row.get("number");
+        for column in TIMESTAMP_COLUMNS {
+            let Some(value) = row.try_get::<Option<String>, _>(column).unwrap_or(None) else {
+                continue;
+            };
+            let Some(normalized) = settings.normalize(&value) else {
+                continue;
+            };
+            if normalized == value {
+                continue;
+            }
+
+            sqlx::query(&format!(
+                "UPDATE new_hire_metrics SET {column} = ? WHERE number = ?"
+            ))
+            .bind(normalized)
+            .bind(number)
+            .execute(&mut *conn)
+            .await
+            .map_err(|err| format!("Failed to normalize timestamps: {err}"))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn seed_record_history(conn: &mut SqliteConnection) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        INSERT INTO record_history (number, run_id, recorded_at, record)
+        SELECT number, NULL, ?, json_object(
+            'number', number,
+            'state', state,
+            'title', title,
+            'assignee_name', assignee_name,
+            'requester', requester,
+            'created_at', created_at,
+            'site', site,
+            'division', division,
+            'employee_type', employee_type,
+            'start_date', start_date,
+            'success_factors_date_entered', success_factors_date_entered,
+            'day_1_or_day_3', day_1_or_day_3,
+            'to_first_response_business', to_first_response_business,
+            'to_resolution_business', to_resolution_business,
+            'to_resolution_elapsed', to_resolution_elapsed,
+            'sla_breaches', sla_breaches,
+            'resolved_at', resolved_at,
+            'it_lead_time_elapsed', it_lead_time_elapsed,
+            'it_lead_time_business', it_lead_time_business,
+            'extras', CASE WHEN json_valid(extras) THEN json(extras) ELSE json('{}') END
+        )
+        FROM new_hire_metrics
+        ORDER BY number
+        "#,
+    )
+    .bind(Utc::now().to_rfc3339())
+    .execute(&mut *conn)
+    .await
+    .map_err(|err| format!("Failed to seed ticket history: {err}"))?;
+
+    Ok(())
+}
+
+async fn derive_state_transitions(conn: &mut SqliteConnection) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        INSERT INTO state_transitions
+            (number, run_id, from_state, to_state, transitioned_at, site, assignee_name)
+        SELECT
+            number,
+            run_id,
+            from_state,
+            to_state,
+            CASE
+                WHEN previous_id IS NULL THEN COALESCE(created_at, recorded_at)
+                ELSE recorded_at
+            END,
+            site,
+            assignee_name
+        FROM (
+            SELECT
+                id,
+                number,
+                run_id,
+                recorded_at,
+                json_extract(record, '$.state') AS to_state,
+                json_extract(record, '$.created_at') AS created_at,
+                json_extract(record, '$.site') AS site,
+                json_extract(record, '$.assignee_name') AS assignee_name,
+                LAG(json_extract(record, '$.state')) OVER versions AS from_state,
+                LAG(id) OVER versions AS previous_id
+            FROM record_history
+            WINDOW versions AS (PARTITION BY number ORDER BY id)
+        )
+        WHERE to_state IS NOT NULL
+            AND (previous_id IS NULL OR from_state IS NOT to_state)
+        ORDER BY number, id
+        "#,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|err| format!("Failed to derive state transitions: {err}"))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use sqlx::sqlite::SqliteConnectOptions;
+    use sqlx::{ConnectOptions, Connection};
+
+    use super::*;
+    use crate::importing::profile::fetch_existing_record;
+    use crate::importing::{NewHireProfile, NewHireRecord};
+
+    async fn baseline_database(db_path: &Path) {
+        let mut conn = SqliteConnectOptions::new()
+            .filename(db_path)
+            .create_if_missing(true)
+            .connect()
+            .await
+            .unwrap();
+
+        sqlx::query(
+            r#"
+            CREATE TABLE new_hire_metrics (
+                number INTEGER PRIMARY KEY,
+                state TEXT,
+                title TEXT,
+                assignee_name TEXT,
+                requester TEXT,
+                created_at TEXT,
+                site TEXT,
+                division TEXT,
+                employee_type TEXT,
+                start_date TEXT,
+                success_factors_date_entered TEXT,
+                day_1_or_day_3 TEXT,
+                to_first_response_business TEXT,
+                to_resolution_business TEXT,
+                to_resolution_elapsed TEXT,
+                sla_breaches TEXT,
+                resolved_at TEXT,
+                it_lead_time_elapsed INTEGER,
+                it_lead_time_business INTEGER
+            )
+            "#,
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+        sqlx::query(
+            r#"
+            INSERT INTO new_hire_metrics
+                (number, state, title, site, created_at, start_date,
+                 to_first_response_business, to_resolution_elapsed, it_lead_time_elapsed)
+            VALUES
+                (1, 'Closed', 'Laptop', 'Berlin', '03/05/2024 10:15', '03/11/2024',
+                 '1d 3h 20m', '02:30:59', 4),
+                (2, NULL, 'Badge', NULL, 'not a date', NULL, '-', '1:75:00', NULL)
+            "#,
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+        conn.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn migrates_baseline_database_to_latest_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("baseline.sqlite");
+        baseline_database(&db_path).await;
+
+        prepare_database(db_path.clone()).await.unwrap();
+        let pool = open_pool(&db_path).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+
+        let version: i64 = sqlx::query_scalar("SELECT MAX(version) FROM schema_version")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len() as i64);
+
+        let minutes: Vec<(Option<i64>, Option<i64>)> = sqlx::query_as(
+            r#"
+            SELECT to_first_response_business_minutes, to_resolution_elapsed_minutes
+            FROM new_hire_metrics
+            ORDER BY number
+            "#,
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(minutes, [(Some(1640), Some(150)), (None, None)]);
+
+        let laptop = fetch_existing_record::<NewHireProfile>(&mut conn, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(laptop.created_at.as_deref(), Some("2024-03-05T10:15:00Z"));
+        assert_eq!(laptop.start_date.as_deref(), Some("2024-03-11"));
+        let badge = fetch_existing_record::<NewHireProfile>(&mut conn, 2)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(badge.created_at.as_deref(), Some("not a date"));
+
+        let history: Vec<(i64, Option<i64>, String)> =
+            sqlx::query_as("SELECT number, run_id, record FROM record_history ORDER BY number")
+                .fetch_all(&mut *conn)
+                .await
+                .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].1, None);
+        let seeded: NewHireRecord = serde_json::from_str(&history[0].2).unwrap();
+        assert_eq!(seeded.number, 1);
+        assert_eq!(seeded.title.as_deref(), Some("Laptop"));
+        assert_eq!(seeded.created_at, laptop.created_at);
+        assert_eq!(seeded.it_lead_time_elapsed, Some(4));
+        assert!(seeded.extras.is_empty());
+
+        let transitions: Vec<(i64, Option<String>, String, String)> = sqlx::query_as(
+            r#"
+            SELECT number, from_state, to_state, transitioned_at
+            FROM state_transitions
+            ORDER BY number
+            "#,
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .unwrap();
+        assert_eq!(
+            transitions,
+            [(1, None, "Closed".to_owned(), "2024-03-05T10:15:00Z".to_owned())]
+        );
+    }
+
+    #[tokio::test]
+    async fn derives_transitions_between_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("history.sqlite");
+        prepare_database(db_path.clone()).await.unwrap();
+        let pool = open_pool(&db_path).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+
+        for (state, recorded_at) in [
+            (Some("New"), "2024-01-01T00:00:00Z"),
+            (None, "2024-01-02T00:00:00Z"),
+            (Some("New"), "2024-01-03T00:00:00Z"),
+            (Some("New"), "2024-01-04T00:00:00Z"),
+            (Some("Closed"), "2024-01-05T00:00:00Z"),
+        ] {
+            let record = serde_json::json!({ "number": 9, "state": state });
+            sqlx::query(
+                "INSERT INTO record_history (number, run_id, recorded_at, record) VALUES (9, NULL, ?, ?)",
+            )
+            .bind(recorded_at)
+            .bind(record.to_string())
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        }
+
+        derive_state_transitions(&mut conn).await.unwrap();
+        apply(&mut conn, &Migration::Sql(SKIP_STATELESS_VERSIONS))
+            .await
+            .unwrap();
+
+        let transitions: Vec<(Option<String>, String, String)> = sqlx::query_as(
+            "SELECT from_state, to_state, transitioned_at FROM state_transitions ORDER BY id",
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .unwrap();
+        let transitions: Vec<(Option<&str>, &str, &str)> = transitions
+            .iter()
+            .map(|(from, to, at)| (from.as_deref(), to.as_str(), at.as_str()))
+            .collect();
+        assert_eq!(
+            transitions,
+            [
+                (None, "New", "2024-01-01T00:00:00Z"),
+                (Some("New"), "Closed", "2024-01-05T00:00:00Z"),
+            ]
+        );
+    }
+}
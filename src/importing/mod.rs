@@ -0,0 +1,1881 @@
+pub mod api;
+pub mod csv_format;
+pub mod duration;
+pub mod history;
+pub mod incidents;
+pub mod mapping;
+pub mod migrations;
+pub mod policy;
+pub mod profile;
+pub mod queue;
+pub mod settings;
+pub mod snapshot;
+pub mod spreadsheet;
+pub mod timestamps;
+pub mod transitions;
+pub mod watch;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use csv_async::{ByteRecord, StringRecord};
+use futures::channel::mpsc;
+use futures::{SinkExt, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    sqlite::SqliteConnectOptions, sqlite::SqlitePoolOptions, Row, SqliteConnection, SqlitePool,
+};
+use tokio::fs;
+use tokio_util::compat::TokioAsyncWriteCompatExt;
+
+use csv_format::{CsvFormat, DetectedFormat};
+use history::{ImportRun, ImportSource, RunAction};
+use mapping::{ColumnMapping, RecordField, ResolvedMapping};
+use policy::{PolicySet, PresetDecisions};
+use profile::{
+    diff_records, fetch_existing_record, insert_record, update_fields, update_record, FieldKind,
+    ImportProfile, ProfileField,
+};
+use timestamps::TimestampSettings;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewHireRecord {
+    pub number: i64,
+    pub state: Option<String>,
+    pub title: Option<String>,
+    pub assignee_name: Option<String>,
+    pub requester: Option<String>,
+    pub created_at: Option<String>,
+    pub site: Option<String>,
+    pub division: Option<String>,
+    pub employee_type: Option<String>,
+    pub start_date: Option<String>,
+    pub success_factors_date_entered: Option<String>,
+    pub day_1_or_day_3: Option<String>,
+    pub to_first_response_business: Option<String>,
+    pub to_resolution_business: Option<String>,
+    pub to_resolution_elapsed: Option<String>,
+    pub sla_breaches: Option<String>,
+    pub resolved_at: Option<String>,
+    pub it_lead_time_elapsed: Option<i64>,
+    pub it_lead_time_business: Option<i64>,
+    #[serde(default)]
+    pub extras: BTreeMap<String, String>,
+}
+
+impl NewHireRecord {
+    pub fn field_value(&self, field: RecordField) -> FieldValue {
+        match field {
+            RecordField::Number => FieldValue::Integer(Some(self.number)),
+            RecordField::State => FieldValue::Text(self.state.clone()),
+            RecordField::Title => FieldValue::Text(self.title.clone()),
+            RecordField::AssigneeName => FieldValue::Text(self.assignee_name.clone()),
+            RecordField::Requester => FieldValue::Text(self.requester.clone()),
+            RecordField::CreatedAt => FieldValue::Text(self.created_at.clone()),
+            RecordField::Site => FieldValue::Text(self.site.clone()),
+            RecordField::Division => FieldValue::Text(self.division.clone()),
+            RecordField::EmployeeType => FieldValue::Text(self.employee_type.clone()),
+            RecordField::StartDate => FieldValue::Text(self.start_date.clone()),
+            RecordField::SuccessFactorsDateEntered => {
+                FieldValue::Text(self.success_factors_date_entered.clone())
+            }
+            RecordField::Day1OrDay3 => FieldValue::Text(self.day_1_or_day_3.clone()),
+            RecordField::ToFirstResponseBusiness => {
+                FieldValue::Text(self.to_first_response_business.clone())
+            }
+            RecordField::ToResolutionBusiness => {
+                FieldValue::Text(self.to_resolution_business.clone())
+            }
+            RecordField::ToResolutionElapsed => FieldValue::Text(self.to_resolution_elapsed.clone()),
+            RecordField::SlaBreaches => FieldValue::Text(self.sla_breaches.clone()),
+            RecordField::ResolvedAt => FieldValue::Text(self.resolved_at.clone()),
+            RecordField::ItLeadTimeElapsed => FieldValue::Integer(self.it_lead_time_elapsed),
+            RecordField::ItLeadTimeBusiness => FieldValue::Integer(self.it_lead_time_business),
+            RecordField::Extras => FieldValue::from_extras(&self.extras),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewHireProfile;
+
+impl ImportProfile for NewHireProfile {
+    type Field = RecordField;
+    type Record = NewHireRecord;
+
+    const NAME: &'static str = "new_hire";
+    const TABLE: &'static str = "new_hire_metrics";
+    const KEY: RecordField = RecordField::Number;
+    const FIELDS: &'static [RecordField] = &RecordField::STORED;
+    const EXTRAS: RecordField = RecordField::Extras;
+    const TITLE: RecordField = RecordField::Title;
+    const CREATED_AT: RecordField = RecordField::CreatedAt;
+    const KEEPS_HISTORY: bool = true;
+    const STATE: Option<RecordField> = Some(RecordField::State);
+    const SITE: Option<RecordField> = Some(RecordField::Site);
+    const ASSIGNEE: Option<RecordField> = Some(RecordField::AssigneeName);
+    const SOFT_DELETES: bool = true;
+
+    fn key(record: &NewHireRecord) -> i64 {
+        record.number
+    }
+
+    fn value(record: &NewHireRecord, field: RecordField) -> FieldValue {
+        record.field_value(field)
+    }
+
+    fn build(mut value: impl FnMut(RecordField) -> FieldValue) -> NewHireRecord {
+        NewHireRecord {
+            number: value(RecordField::Number).into_integer().unwrap_or_default(),
+            state: value(RecordField::State).into_text(),
+            title: value(RecordField::Title).into_text(),
+            assignee_name: value(RecordField::AssigneeName).into_text(),
+            requester: value(RecordField::Requester).into_text(),
+            created_at: value(RecordField::CreatedAt).into_text(),
+            site: value(RecordField::Site).into_text(),
+            division: value(RecordField::Division).into_text(),
+            employee_type: value(RecordField::EmployeeType).into_text(),
+            start_date: value(RecordField::StartDate).into_text(),
+            success_factors_date_entered: value(RecordField::SuccessFactorsDateEntered).into_text(),
+            day_1_or_day_3: value(RecordField::Day1OrDay3).into_text(),
+            to_first_response_business: value(RecordField::ToFirstResponseBusiness).into_text(),
+            to_resolution_business: value(RecordField::ToResolutionBusiness).into_text(),
+            to_resolution_elapsed: value(RecordField::ToResolutionElapsed).into_text(),
+            sla_breaches: value(RecordField::SlaBreaches).into_text(),
+            resolved_at: value(RecordField::ResolvedAt).into_text(),
+            it_lead_time_elapsed: value(RecordField::ItLeadTimeElapsed).into_integer(),
+            it_lead_time_business: value(RecordField::ItLeadTimeBusiness).into_integer(),
+            extras: value(RecordField::Extras).into_extras(),
+        }
+    }
+
+    /// Duration fields keep their length in minutes next to the text.
+    fn derived(field: RecordField, value: &FieldValue) -> Option<(&'static str, FieldValue)> {
+        let column = duration::minutes_column(field)?;
+        let minutes = match value {
+            FieldValue::Text(text) => text.as_deref().and_then(duration::parse_minutes),
+            FieldValue::Integer(_) => None,
+        };
+        Some((column, FieldValue::Integer(minutes)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldValue {
+    Text(Option<String>),
+    Integer(Option<i64>),
+}
+
+impl FieldValue {
+    pub fn empty(kind: FieldKind) -> Self {
+        match kind {
+            FieldKind::Integer => FieldValue::Integer(None),
+            FieldKind::Text | FieldKind::Timestamp | FieldKind::Extras => FieldValue::Text(None),
+        }
+    }
+
+    pub fn from_extras(extras: &BTreeMap<String, String>) -> Self {
+        if extras.is_empty() {
+            return FieldValue::Text(None);
+        }
+        FieldValue::Text(serde_json::to_string(extras).ok())
+    }
+
+    pub fn into_extras(self) -> BTreeMap<String, String> {
+        self.into_text()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn into_text(self) -> Option<String> {
+        match self {
+            FieldValue::Text(value) => value,
+            FieldValue::Integer(value) => value.map(|value| value.to_string()),
+        }
+    }
+
+    pub fn into_integer(self) -> Option<i64> {
+        match self {
+            FieldValue::Integer(value) => value,
+            FieldValue::Text(value) => value.and_then(|value| value.parse().ok()),
+        }
+    }
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Text(Some(value)) => f.write_str(value),
+            FieldValue::Integer(Some(value)) => write!(f, "{value}"),
+            FieldValue::Text(None) | FieldValue::Integer(None) => f.write_str("none"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldChange<F = RecordField> {
+    pub field: F,
+    pub old: FieldValue,
+    pub new: FieldValue,
+}
+
+impl<F: ProfileField> fmt::Display for FieldChange<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.field.label(), self.old, self.new)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PendingDuplicate<F = RecordField> {
+    pub number: i64,
+    pub title: Option<String>,
+    pub created_at: Option<String>,
+    pub changes: Vec<FieldChange<F>>,
+}
+
+impl<F: ProfileField> PendingDuplicate<F> {
+    pub fn from_record<P: ImportProfile<Field = F>>(
+        record: &P::Record,
+        changes: Vec<FieldChange<F>>,
+    ) -> Self {
+        Self {
+            number: P::key(record),
+            title: P::value(record, P::TITLE).into_text(),
+            created_at: P::value(record, P::CREATED_AT).into_text(),
+            changes,
+        }
+    }
+
+    pub fn changes_summary(&self) -> String {
+        self.changes
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportStatus {
+    Idle,
+    Loading,
+    AwaitingConfirmation,
+    AwaitingSheet,
+    AwaitingMapping,
+    AwaitingCommit,
+    Importing,
+    AwaitingDecision,
+    Done,
+    Error,
+}
+
+impl ImportStatus {
+    pub fn is_ready(self) -> bool {
+        matches!(
+            self,
+            ImportStatus::Idle | ImportStatus::Done | ImportStatus::Error
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportState {
+    pub status: ImportStatus,
+    pub processed: usize,
+    pub inserted: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub total: usize,
+    pub auto_decisions: Vec<AutoDecision>,
+    pub rejected_headers: Vec<String>,
+    pub rejected_rows: Vec<RejectedRow>,
+    pub message: Option<String>,
+    pub started_at: Option<Instant>,
+}
+
+impl ImportState {
+    pub fn new() -> Self {
+        Self {
+            status: ImportStatus::Idle,
+            processed: 0,
+            inserted: 0,
+            updated: 0,
+            skipped: 0,
+            total: 0,
+            auto_decisions: Vec::new(),
+            rejected_headers: Vec::new(),
+            rejected_rows: Vec::new(),
+            message: None,
+            started_at: None,
+        }
+    }
+
+    pub fn start(&mut self, total: usize) {
+        self.status = ImportStatus::Importing;
+        self.processed = 0;
+        self.inserted = 0;
+        self.updated = 0;
+        self.skipped = 0;
+        self.total = total;
+        self.auto_decisions.clear();
+        self.message = None;
+        self.started_at = Some(Instant::now());
+    }
+
+    pub fn throughput(&self) -> Option<f64> {
+        let elapsed = self.started_at?.elapsed().as_secs_f64();
+        (self.processed > 0 && elapsed > 0.0).then(|| self.processed as f64 / elapsed)
+    }
+
+    pub fn eta(&self) -> Option<Duration> {
+        let remaining = self.total.saturating_sub(self.processed);
+        Some(Duration::from_secs_f64(remaining as f64 / self.throughput()?))
+    }
+
+    pub fn set_error(&mut self, message: String) {
+        self.status = ImportStatus::Error;
+        self.message = Some(message);
+    }
+
+    pub fn set_message(&mut self, message: String) {
+        self.message = Some(message);
+    }
+
+    pub fn apply_progress<P: ImportProfile>(&mut self, progress: &ImportProgress<P>) {
+        self.processed += progress.processed;
+        self.inserted += progress.inserted;
+        self.updated += progress.updated;
+        self.skipped += progress.skipped;
+        self.auto_decisions
+            .extend(progress.auto_decisions.iter().cloned());
+        self.rejected_rows.extend(progress.rejected.iter().cloned());
+    }
+
+    pub fn roll_back(&mut self) {
+        self.processed = 0;
+        self.inserted = 0;
+        self.updated = 0;
+        self.skipped = 0;
+        self.auto_decisions.clear();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ImportStep<P: ImportProfile = NewHireProfile> {
+    Inserted,
+    Duplicate(DuplicateEntry<P>),
+    AutoResolved {
+        entry: DuplicateEntry<P>,
+        decision: DuplicateDecision<P::Field>,
+        rule: String,
+    },
+    SkippedUnchanged,
+}
+
+#[derive(Debug, Clone)]
+pub struct AutoDecision {
+    pub number: i64,
+    pub title: Option<String>,
+    pub rule: String,
+    pub outcome: String,
+}
+
+impl AutoDecision {
+    fn new<F: ProfileField>(
+        summary: &PendingDuplicate<F>,
+        decision: &DuplicateDecision<F>,
+        rule: String,
+    ) -> Self {
+        Self {
+            number: summary.number,
+            title: summary.title.clone(),
+            rule,
+            outcome: decision.describe(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportProgress<P: ImportProfile = NewHireProfile> {
+    pub processed: usize,
+    pub inserted: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub duplicates: Vec<DuplicateEntry<P>>,
+    pub auto_decisions: Vec<AutoDecision>,
+    pub numbers: Vec<i64>,
+    pub rejected: Vec<RejectedRow>,
+}
+
+impl<P: ImportProfile> Default for ImportProgress<P> {
+    fn default() -> Self {
+        Self {
+            processed: 0,
+            inserted: 0,
+            updated: 0,
+            skipped: 0,
+            duplicates: Vec::new(),
+            auto_decisions: Vec::new(),
+            numbers: Vec::new(),
+            rejected: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ImportEvent<P: ImportProfile = NewHireProfile> {
+    Started { headers: Vec<String>, total: usize },
+    Progress(ImportProgress<P>),
+    Finished(Result<i64, String>),
+    /// Keeps the run committed with the records processed before it stopped.
+    Cancelled(i64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DuplicateDecision<F = RecordField> {
+    Overwrite,
+    Skip,
+    Merge(Vec<F>),
+}
+
+impl<F: ProfileField> DuplicateDecision<F> {
+    pub fn from_overwrite(overwrite: bool) -> Self {
+        if overwrite {
+            DuplicateDecision::Overwrite
+        } else {
+            DuplicateDecision::Skip
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            DuplicateDecision::Overwrite => "Overwritten".to_owned(),
+            DuplicateDecision::Skip => "Skipped".to_owned(),
+            DuplicateDecision::Merge(fields) => format!(
+                "Merged {}",
+                fields
+                    .iter()
+                    .map(|field| field.label())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeSelection<F = RecordField> {
+    pub number: i64,
+    pub changes: Vec<FieldChange<F>>,
+    take_new: HashSet<F>,
+}
+
+impl<F: ProfileField> MergeSelection<F> {
+    pub fn new(pending: &PendingDuplicate<F>) -> Self {
+        Self {
+            number: pending.number,
+            changes: pending.changes.clone(),
+            take_new: pending.changes.iter().map(|change| change.field).collect(),
+        }
+    }
+
+    pub fn takes_new(&self, field: F) -> bool {
+        self.take_new.contains(&field)
+    }
+
+    pub fn choose(&mut self, field: F, take_new: bool) {
+        if take_new {
+            self.take_new.insert(field);
+        } else {
+            self.take_new.remove(&field);
+        }
+    }
+
+    pub fn decision(&self) -> DuplicateDecision<F> {
+        DuplicateDecision::Merge(
+            self.changes
+                .iter()
+                .map(|change| change.field)
+                .filter(|field| self.takes_new(*field))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DecisionOutcome {
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct DuplicateEntry<P: ImportProfile = NewHireProfile> {
+    pub run_id: i64,
+    pub record: P::Record,
+    pub summary: PendingDuplicate<P::Field>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImportKind {
+    NewHires,
+    Incidents,
+}
+
+impl ImportKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ImportKind::NewHires => "New hire tickets",
+            ImportKind::Incidents => "Incidents",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PreparedImport<P: ImportProfile = NewHireProfile> {
+    pub path: PathBuf,
+    pub source: ImportSource,
+    pub previous_run: Option<ImportRun>,
+    pub sheets: Vec<String>,
+    pub sheet: Option<String>,
+    pub csv_format: Option<DetectedFormat>,
+    pub headers: Vec<String>,
+    pub profiles: Vec<ColumnMapping<P>>,
+}
+
+impl<P: ImportProfile> PreparedImport<P> {
+    pub fn matching_profile(&self) -> Option<&ColumnMapping<P>> {
+        self.profiles
+            .iter()
+            .find(|mapping| mapping.matches(&self.headers))
+    }
+
+    pub fn needs_sheet(&self) -> bool {
+        self.sheet.is_none() && !self.sheets.is_empty()
+    }
+}
+
+pub fn is_supported_file(path: &Path) -> bool {
+    spreadsheet::is_spreadsheet(path)
+        || path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"))
+}
+
+/// A workbook with several sheets and no sheet chosen yet has no headers.
+pub async fn prepare_import<P: ImportProfile>(
+    db_path: PathBuf,
+    path: PathBuf,
+    sheet: Option<String>,
+    format: CsvFormat,
+) -> Result<PreparedImport<P>, String> {
+    let (sheets, sheet, csv_format, headers) = if spreadsheet::is_spreadsheet(&path) {
+        let sheets = spreadsheet::sheet_names(path.clone()).await?;
+        if sheets.is_empty() {
+            return Err("The spreadsheet has no sheets.".to_owned());
+        }
+
+        let sheet = sheet.or_else(|| (sheets.len() == 1).then(|| sheets[0].clone()));
+        let headers = match &sheet {
+            Some(sheet) => spreadsheet::read_headers(path.clone(), sheet.clone()).await?,
+            None => Vec::new(),
+        };
+        (sheets, sheet, None, headers)
+    } else {
+        let (csv_format, headers) = read_csv_headers(&path, format).await?;
+        (Vec::new(), None, Some(csv_format), headers)
+    };
+
+    let pool = open_pool(&db_path).await?;
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|err| format!("Failed to connect to database: {err}"))?;
+    let profiles = mapping::load_profiles::<P>(&mut conn).await?;
+
+    let mut source = ImportSource::from_file(&path).await?;
+    if let Some(sheet) = &sheet {
+        source = source.with_sheet(sheet);
+    }
+    let previous_run = history::find_run_by_hash(&mut conn, &source.content_hash).await?;
+
+    Ok(PreparedImport {
+        path,
+        source,
+        previous_run,
+        sheets,
+        sheet,
+        csv_format,
+        headers,
+        profiles,
+    })
+}
+
+async fn read_csv_headers(
+    path: &Path,
+    format: CsvFormat,
+) -> Result<(DetectedFormat, Vec<String>), String> {
+    let detected = csv_format::detect_format(path, format).await?;
+    let mut reader = csv_format::open_reader(path, detected).await?;
+
+    let headers = reader
+        .headers()
+        .await
+        .map_err(|err| format!("CSV parse error: {err}"))?
+        .iter()
+        .map(str::to_owned)
+        .collect();
+
+    Ok((detected, headers))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CsvReadOptions {
+    pub lenient: bool,
+    pub format: CsvFormat,
+    pub timestamps: TimestampSettings,
+}
+
+#[derive(Debug, Clone)]
+pub struct RejectedRow {
+    pub line: u64,
+    pub number: Option<i64>,
+    pub column: Option<String>,
+    pub reason: String,
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvReadOutcome<R = NewHireRecord> {
+    pub records: Vec<R>,
+    pub headers: Vec<String>,
+    pub rejected: Vec<RejectedRow>,
+}
+
+impl<R> Default for CsvReadOutcome<R> {
+    fn default() -> Self {
+        Self {
+            records: Vec::new(),
+            headers: Vec::new(),
+            rejected: Vec::new(),
+        }
+    }
+}
+
+pub async fn read_mapped_csv<P: ImportProfile>(
+    path: PathBuf,
+    mapping: ColumnMapping<P>,
+    options: CsvReadOptions,
+) -> Result<CsvReadOutcome<P::Record>, String> {
+    read_profile_csv::<P>(path, options, |headers| mapping.resolve(headers)).await
+}
+
+pub async fn read_profile_csv<P: ImportProfile>(
+    path: PathBuf,
+    options: CsvReadOptions,
+    resolve: impl FnOnce(&StringRecord) -> Result<ResolvedMapping<P::Field>, String>,
+) -> Result<CsvReadOutcome<P::Record>, String> {
+    let detected = csv_format::detect_format(&path, options.format).await?;
+    let mut reader = csv_format::open_reader(&path, detected).await?;
+
+    let headers = reader
+        .headers()
+        .await
+        .map_err(|err| format!("CSV parse error: {err}"))?
+        .clone();
+    let resolved = resolve(&headers)?;
+
+    let mut outcome = CsvReadOutcome {
+        headers: headers.iter().map(str::to_owned).collect(),
+        ..CsvReadOutcome::default()
+    };
+    let mut rows = reader.byte_records();
+
+    while let Some(result) = rows.next().await {
+        read_row::<P>(&resolved, &mut outcome, &options, headers.len(), result)?;
+    }
+
+    Ok(outcome)
+}
+
+fn read_row<P: ImportProfile>(
+    resolved: &ResolvedMapping<P::Field>,
+    outcome: &mut CsvReadOutcome<P::Record>,
+    options: &CsvReadOptions,
+    width: usize,
+    result: Result<ByteRecord, csv_async::Error>,
+) -> Result<(), String> {
+    let row = result.map_err(|err| format!("CSV parse error: {err}"))?;
+    let line = row.position().map(|position| position.line()).unwrap_or(0);
+
+    let mut problem = (row.len() != width)
+        .then(|| format!("found {} fields, expected {width}", row.len()));
+    let row = StringRecord::from_byte_record(row).unwrap_or_else(|err| {
+        problem.get_or_insert_with(|| {
+            format!("invalid UTF-8 in field {}", err.utf8_error().field() + 1)
+        });
+        StringRecord::from_byte_record_lossy(err.into_byte_record())
+    });
+
+    match problem {
+        Some(reason) if options.lenient => {
+            outcome.rejected.push(RejectedRow {
+                line,
+                number: resolved.key::<P>(&row),
+                column: None,
+                reason,
+                values: row.iter().map(str::to_owned).collect(),
+            });
+            Ok(())
+        }
+        Some(reason) => Err(format!("CSV parse error: line {line}, {reason}")),
+        None => push_row::<P>(resolved, outcome, options, line, &row)
+            .map_err(|err| format!("CSV parse error: {err}")),
+    }
+}
+
+fn push_row<P: ImportProfile>(
+    resolved: &ResolvedMapping<P::Field>,
+    outcome: &mut CsvReadOutcome<P::Record>,
+    options: &CsvReadOptions,
+    line: u64,
+    row: &StringRecord,
+) -> Result<(), String> {
+    match resolved.record::<P>(row, &options.timestamps) {
+        Ok(Some(record)) => outcome.records.push(record),
+        Ok(None) if options.lenient => outcome.rejected.push(RejectedRow {
+            line,
+            number: None,
+            column: Some(
+                resolved
+                    .header(P::KEY)
+                    .unwrap_or(P::KEY.header())
+                    .to_owned(),
+            ),
+            reason: "missing ticket number".to_owned(),
+            values: row.iter().map(str::to_owned).collect(),
+        }),
+        Ok(None) => {}
+        Err(err) if options.lenient => outcome.rejected.push(RejectedRow {
+            line,
+            number: resolved.key::<P>(row),
+            column: Some(err.column),
+            reason: err.reason,
+            values: row.iter().map(str::to_owned).collect(),
+        }),
+        Err(err) => return Err(format!("line {line}, {err}")),
+    }
+
+    Ok(())
+}
+
+/// Rows keep the cells they were read with, which may differ from the header.
+pub async fn export_rejected_rows(
+    path: PathBuf,
+    headers: Vec<String>,
+    rows: Vec<RejectedRow>,
+) -> Result<PathBuf, String> {
+    let file = fs::File::create(&path)
+        .await
+        .map_err(|err| format!("Failed to create CSV: {err}"))?;
+    let mut writer = csv_async::AsyncWriterBuilder::new()
+        .flexible(true)
+        .create_writer(file.compat_write());
+
+    let header_row = ["Line", "Column", "Reason"]
+        .into_iter()
+        .map(str::to_owned)
+        .chain(headers);
+    writer
+        .write_record(header_row)
+        .await
+        .map_err(|err| format!("Failed to write CSV: {err}"))?;
+
+    for row in rows {
+        let record = [
+            row.line.to_string(),
+            row.column.unwrap_or_default(),
+            row.reason,
+        ]
+        .into_iter()
+        .chain(row.values);
+        writer
+            .write_record(record)
+            .await
+            .map_err(|err| format!("Failed to write CSV: {err}"))?;
+    }
+
+    writer
+        .flush()
+        .await
+        .map_err(|err| format!("Failed to write CSV: {err}"))?;
+
+    Ok(path)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportCancel(Arc<AtomicBool>);
+
+impl ImportCancel {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+const CHUNK_SIZE: usize = 250;
+
+/// Chunks a streamed CSV may read ahead of the rows being written.
+const CHUNK_BUFFER: usize = 2;
+
+/// On error nothing from the batch is kept; a cancelled import keeps the
+/// records processed before it stopped.
+pub fn import_records<P: ImportProfile>(
+    db_path: PathBuf,
+    source: ImportSource,
+    records: Vec<P::Record>,
+    presets: PresetDecisions<P::Field>,
+    cancel: ImportCancel,
+) -> impl Stream<Item = ImportEvent<P>> {
+    iced::stream::channel(8, async move |mut output: mpsc::Sender<ImportEvent<P>>| {
+        let chunks = futures::stream::iter(records)
+            .chunks(CHUNK_SIZE)
+            .map(|records| {
+                Ok(CsvReadOutcome {
+                    records,
+                    ..CsvReadOutcome::default()
+                })
+            });
+        let event = run_import(&db_path, &source, chunks, presets, &cancel, &mut output)
+            .await
+            .unwrap_or_else(|err| ImportEvent::Finished(Err(err)));
+        let _ = output.send(event).await;
+    })
+}
+
+/// Reads and writes in chunks, so the whole file is never held in memory.
+pub fn import_mapped_csv<P: ImportProfile>(
+    db_path: PathBuf,
+    path: PathBuf,
+    mapping: ColumnMapping<P>,
+    options: CsvReadOptions,
+    source: ImportSource,
+    presets: PresetDecisions<P::Field>,
+    cancel: ImportCancel,
+) -> impl Stream<Item = ImportEvent<P>> {
+    import_profile_csv::<P>(
+        db_path,
+        path,
+        options,
+        source,
+        presets,
+        cancel,
+        move |headers| mapping.resolve(headers),
+    )
+}
+
+pub fn import_profile_csv<P: ImportProfile>(
+    db_path: PathBuf,
+    path: PathBuf,
+    options: CsvReadOptions,
+    source: ImportSource,
+    presets: PresetDecisions<P::Field>,
+    cancel: ImportCancel,
+    resolve: impl FnOnce(&StringRecord) -> Result<ResolvedMapping<P::Field>, String> + Send + 'static,
+) -> impl Stream<Item = ImportEvent<P>> {
+    iced::stream::channel(8, async move |mut output: mpsc::Sender<ImportEvent<P>>| {
+        let import = stream_csv::<P>(
+            &db_path,
+            &path,
+            resolve,
+            options,
+            &source,
+            presets,
+            &cancel,
+            &mut output,
+        );
+        let event = import
+            .await
+            .unwrap_or_else(|err| ImportEvent::Finished(Err(err)));
+        let _ = output.send(event).await;
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn stream_csv<P: ImportProfile>(
+    db_path: &Path,
+    path: &Path,
+    resolve: impl FnOnce(&StringRecord) -> Result<ResolvedMapping<P::Field>, String>,
+    options: CsvReadOptions,
+    source: &ImportSource,
+    presets: PresetDecisions<P::Field>,
+    cancel: &ImportCancel,
+    output: &mut mpsc::Sender<ImportEvent<P>>,
+) -> Result<ImportEvent<P>, String> {
+    let detected = csv_format::detect_format(path, options.format).await?;
+    let total = csv_format::count_rows(path, detected).await?;
+    let mut reader = csv_format::open_reader(path, detected).await?;
+    let headers = reader
+        .headers()
+        .await
+        .map_err(|err| format!("CSV parse error: {err}"))?
+        .clone();
+    let resolved = resolve(&headers)?;
+    let _ = output
+        .send(ImportEvent::Started {
+            headers: headers.iter().map(str::to_owned).collect(),
+            total,
+        })
+        .await;
+
+    let (mut sender, chunks) = mpsc::channel(CHUNK_BUFFER);
+    let read = async move {
+        let mut rows = reader.into_byte_records();
+        let mut chunk = CsvReadOutcome::default();
+        while let Some(result) = rows.next().await {
+            let read = read_row::<P>(
+                &resolved,
+                &mut chunk,
+                &options,
+                headers.len(),
+                result,
+            );
+            if let Err(err) = read {
+                let _ = sender.send(Err(err)).await;
+                return;
+            }
+            if chunk.records.len() + chunk.rejected.len() == CHUNK_SIZE {
+                // Fails once the writer stopped, which ends the read.
+                if sender.send(Ok(std::mem::take(&mut chunk))).await.is_err() {
+                    return;
+                }
+            }
+        }
+        if !chunk.records.is_empty() || !chunk.rejected.is_empty() {
+            let _ = sender.send(Ok(chunk)).await;
+        }
+    };
+
+    let import = run_import(db_path, source, chunks, presets, cancel, output);
+    let ((), event) = futures::join!(read, import);
+    event
+}
+
+async fn run_import<P: ImportProfile>(
+    db_path: &Path,
+    source: &ImportSource,
+    mut chunks: impl Stream<Item = Result<CsvReadOutcome<P::Record>, String>> + Unpin,
+    presets: PresetDecisions<P::Field>,
+    cancel: &ImportCancel,
+    output: &mut mpsc::Sender<ImportEvent<P>>,
+) -> Result<ImportEvent<P>, String> {
+    let pool = open_pool(db_path).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|err| format!("Failed to start transaction: {err}"))?;
+    let run_id = history::start_run::<P>(&mut tx, source).await?;
+    let policies = PolicySet::new(policy::load_policies::<P>(&mut tx).await?).with_presets(presets);
+    let mut inserted = 0;
+    let mut updated = 0;
+    let mut skipped = 0;
+    let mut cancelled = false;
+
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk?;
+        let mut progress = ImportProgress {
+            processed: chunk.rejected.len(),
+            rejected: chunk.rejected,
+            ..ImportProgress::default()
+        };
+
+        for record in chunk.records {
+            if cancel.is_cancelled() {
+                cancelled = true;
+                break;
+            }
+            progress.numbers.push(P::key(&record));
+            write_record::<P>(&mut tx, run_id, record, &policies, &mut progress).await?;
+        }
+
+        inserted += progress.inserted;
+        updated += progress.updated;
+        skipped += progress.skipped;
+        let _ = output.send(ImportEvent::Progress(progress)).await;
+        if cancelled {
+            break;
+        }
+    }
+
+    history::add_counts(&mut tx, run_id, inserted, updated, skipped).await?;
+    if cancelled {
+        history::mark_cancelled(&mut tx, run_id).await?;
+    }
+    tx.commit()
+        .await
+        .map_err(|err| format!("Failed to commit import: {err}"))?;
+
+    Ok(if cancelled {
+        ImportEvent::Cancelled(run_id)
+    } else {
+        ImportEvent::Finished(Ok(run_id))
+    })
+}
+
+async fn write_record<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    run_id: i64,
+    record: P::Record,
+    policies: &PolicySet<P::Field>,
+    progress: &mut ImportProgress<P>,
+) -> Result<(), String> {
+    let number = P::key(&record);
+    match process_record::<P>(conn, run_id, record, policies).await? {
+        ImportStep::Inserted => {
+            history::log_record::<P>(conn, run_id, number, RunAction::Inserted, None).await?;
+            progress.inserted += 1;
+        }
+        ImportStep::SkippedUnchanged => progress.skipped += 1,
+        ImportStep::Duplicate(entry) => {
+            save_pending_duplicate::<P>(conn, run_id, &entry.record).await?;
+            progress.duplicates.push(entry);
+        }
+        ImportStep::AutoResolved {
+            entry,
+            decision,
+            rule,
+        } => {
+            if apply_decision::<P>(conn, run_id, &entry.record, &decision).await? {
+                progress.updated += 1;
+            } else {
+                progress.skipped += 1;
+            }
+            progress
+                .auto_decisions
+                .push(AutoDecision::new(&entry.summary, &decision, rule));
+        }
+    }
+    progress.processed += 1;
+
+    Ok(())
+}
+
+async fn process_record<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    run_id: i64,
+    record: P::Record,
+    policies: &PolicySet<P::Field>,
+) -> Result<ImportStep<P>, String> {
+    let number = P::key(&record);
+    if let Some(existing) = fetch_existing_record::<P>(conn, number).await? {
+        if P::SOFT_DELETES {
+            snapshot::restore::<P>(conn, run_id, number).await?;
+        }
+        let changes = diff_records::<P>(&existing, &record);
+
+        if changes.is_empty() {
+            return Ok(ImportStep::SkippedUnchanged);
+        }
+
+        let resolution = policies.resolve::<P>(&existing, &record, &changes);
+        let entry = DuplicateEntry {
+            run_id,
+            summary: PendingDuplicate::from_record::<P>(&record, changes),
+            record,
+        };
+
+        return Ok(match resolution {
+            Some((decision, rule)) => ImportStep::AutoResolved {
+                entry,
+                decision,
+                rule,
+            },
+            None => ImportStep::Duplicate(entry),
+        });
+    }
+
+    insert_record::<P>(conn, &record).await?;
+    Ok(ImportStep::Inserted)
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportPreview<P: ImportProfile = NewHireProfile> {
+    pub records: Vec<P::Record>,
+    pub inserts: Vec<PendingDuplicate<P::Field>>,
+    pub unchanged: usize,
+    pub auto_resolved: Vec<AutoDecision>,
+    /// Committing the preview overwrites these.
+    pub changed: Vec<PendingDuplicate<P::Field>>,
+    pub decisions: PresetDecisions<P::Field>,
+}
+
+impl<P: ImportProfile> Default for ImportPreview<P> {
+    fn default() -> Self {
+        Self {
+            records: Vec::new(),
+            inserts: Vec::new(),
+            unchanged: 0,
+            auto_resolved: Vec::new(),
+            changed: Vec::new(),
+            decisions: PresetDecisions::new(),
+        }
+    }
+}
+
+const PREVIEW_RULE: &str = "Accepted in preview";
+
+pub async fn preview_records<P: ImportProfile>(
+    db_path: PathBuf,
+    records: Vec<P::Record>,
+    presets: PresetDecisions<P::Field>,
+) -> Result<ImportPreview<P>, String> {
+    let pool = open_pool(&db_path).await?;
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|err| format!("Failed to connect to database: {err}"))?;
+
+    let policies =
+        PolicySet::new(policy::load_policies::<P>(&mut conn).await?).with_presets(presets);
+    let mut preview = ImportPreview::default();
+    let mut batch: HashMap<i64, P::Record> = HashMap::new();
+
+    for record in &records {
+        let number = P::key(record);
+        let existing = match batch.get(&number) {
+            Some(earlier) => Some(earlier.clone()),
+            None => fetch_existing_record::<P>(&mut conn, number).await?,
+        };
+
+        match existing {
+            Some(existing) => {
+                let changes = diff_records::<P>(&existing, record);
+                if changes.is_empty() {
+                    preview.unchanged += 1;
+                } else if let Some((decision, rule)) =
+                    policies.resolve::<P>(&existing, record, &changes)
+                {
+                    let summary = PendingDuplicate::from_record::<P>(record, changes);
+                    preview
+                        .auto_resolved
+                        .push(AutoDecision::new(&summary, &decision, rule.clone()));
+                    preview.decisions.insert(number, (decision, rule));
+                } else {
+                    preview
+                        .changed
+                        .push(PendingDuplicate::from_record::<P>(record, changes));
+                    preview.decisions.insert(
+                        number,
+                        (DuplicateDecision::Overwrite, PREVIEW_RULE.to_owned()),
+                    );
+                }
+            }
+            None => {
+                preview
+                    .inserts
+                    .push(PendingDuplicate::from_record::<P>(record, Vec::new()));
+                batch.insert(number, record.clone());
+            }
+        }
+    }
+
+    preview.records = records;
+    Ok(preview)
+}
+
+pub async fn apply_duplicate_decisions<P: ImportProfile>(
+    db_path: PathBuf,
+    decisions: Vec<(i64, P::Record, DuplicateDecision<P::Field>)>,
+) -> Result<DecisionOutcome, String> {
+    let pool = open_pool(&db_path).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|err| format!("Failed to start transaction: {err}"))?;
+    let mut outcome = DecisionOutcome::default();
+    let mut runs: BTreeMap<i64, DecisionOutcome> = BTreeMap::new();
+
+    for (run_id, record, decision) in decisions {
+        let run = runs.entry(run_id).or_default();
+        if apply_decision::<P>(&mut tx, run_id, &record, &decision).await? {
+            run.updated += 1;
+            outcome.updated += 1;
+        } else {
+            run.skipped += 1;
+            outcome.skipped += 1;
+        }
+        remove_pending_duplicate(&mut tx, run_id, P::key(&record)).await?;
+    }
+
+    for (run_id, run) in runs {
+        history::add_counts(&mut tx, run_id, 0, run.updated, run.skipped).await?;
+    }
+    tx.commit()
+        .await
+        .map_err(|err| format!("Failed to commit decisions: {err}"))?;
+
+    Ok(outcome)
+}
+
+async fn save_pending_duplicate<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    run_id: i64,
+    record: &P::Record,
+) -> Result<(), String> {
+    let number = P::key(record);
+    let json = serde_json::to_string(record)
+        .map_err(|err| format!("Failed to queue duplicate {number}: {err}"))?;
+
+    sqlx::query(
+        "INSERT OR REPLACE INTO pending_duplicates (run_id, number, record) VALUES (?, ?, ?)",
+    )
+    .bind(run_id)
+    .bind(number)
+    .bind(json)
+    .execute(conn)
+    .await
+    .map_err(|err| format!("Failed to queue duplicate {number}: {err}"))?;
+
+    Ok(())
+}
+
+async fn remove_pending_duplicate(
+    conn: &mut SqliteConnection,
+    run_id: i64,
+    number: i64,
+) -> Result<(), String> {
+    sqlx::query("DELETE FROM pending_duplicates WHERE run_id = ? AND number = ?")
+        .bind(run_id)
+        .bind(number)
+        .execute(conn)
+        .await
+        .map_err(|err| format!("Failed to update duplicate queue: {err}"))?;
+
+    Ok(())
+}
+
+/// Entries whose ticket has since been deleted or already matches are dropped.
+pub async fn load_pending_duplicates<P: ImportProfile>(
+    db_path: PathBuf,
+) -> Result<Vec<DuplicateEntry<P>>, String> {
+    let pool = open_pool(&db_path).await?;
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|err| format!("Failed to connect to database: {err}"))?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT pending.run_id, pending.number, pending.record
+        FROM pending_duplicates AS pending
+        JOIN import_runs AS run ON run.id = pending.run_id
+        WHERE run.profile = ?
+        ORDER BY pending.run_id, pending.rowid
+        "#,
+    )
+    .bind(P::NAME)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|err| format!("Failed to load queued duplicates: {err}"))?;
+
+    let mut entries = Vec::new();
+    for row in rows {
+        let run_id: i64 = row.get("run_id");
+        let number: i64 = row.get("number");
+        let json: String = row.get("record");
+
+        let record = serde_json::from_str::<P::Record>(&json).ok();
+        let existing = fetch_existing_record::<P>(&mut conn, number).await?;
+        let changes = match (&record, &existing) {
+            (Some(record), Some(existing)) => diff_records::<P>(existing, record),
+            _ => Vec::new(),
+        };
+
+        match record {
+            Some(record) if !changes.is_empty() => entries.push(DuplicateEntry {
+                run_id,
+                summary: PendingDuplicate::from_record::<P>(&record, changes),
+                record,
+            }),
+            _ => remove_pending_duplicate(&mut conn, run_id, number).await?,
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Returns whether the stored record was changed.
+async fn apply_decision<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    run_id: i64,
+    record: &P::Record,
+    decision: &DuplicateDecision<P::Field>,
+) -> Result<bool, String> {
+    let fields = match decision {
+        DuplicateDecision::Overwrite => None,
+        DuplicateDecision::Merge(fields) if !fields.is_empty() => Some(fields),
+        DuplicateDecision::Merge(_) | DuplicateDecision::Skip => return Ok(false),
+    };
+
+    let number = P::key(record);
+    let previous = fetch_existing_record::<P>(conn, number).await?;
+    match fields {
+        Some(fields) => update_fields::<P>(conn, record, fields).await?,
+        None => update_record::<P>(conn, record).await?,
+    }
+    history::log_record::<P>(conn, run_id, number, RunAction::Updated, previous.as_ref()).await?;
+
+    Ok(true)
+}
+
+pub async fn open_pool(db_path: &Path) -> Result<SqlitePool, String> {
+    if let Some(parent) = db_path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .map_err(|err| format!("Failed to create data directory: {err}"))?;
+    }
+
+    let options = SqliteConnectOptions::new()
+        .filename(db_path)
+        .create_if_missing(true);
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .map_err(|err| format!("Failed to connect to database: {err}"))?;
+
+    Ok(pool)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use migrations::prepare_database;
+
+    fn mapping(headers: &[&str]) -> ColumnMapping {
+        let headers: Vec<String> = headers.iter().map(|&header| header.to_owned()).collect();
+        ColumnMapping::solar_winds_default().fit_to(&headers)
+    }
+
+    #[tokio::test]
+    async fn exports_malformed_rows_with_their_values() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tickets.csv");
+        std::fs::write(
+            &path,
+            "Number,State,Title\n\
+              1,Open,Laptop\n\
+              2,Open\n\
+              3,Closed,Badge,extra\n\
+              x,Open,Desk\n",
+        )
+        .unwrap();
+
+        let options = CsvReadOptions {
+            lenient: true,
+            ..CsvReadOptions::default()
+        };
+        let outcome = read_mapped_csv(
+            path.clone(),
+            mapping(&["Number", "State", "Title"]),
+            options,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(outcome.records.len(), 1);
+        let rejected: Vec<(u64, &str)> = outcome
+            .rejected
+            .iter()
+            .map(|row| (row.line, row.reason.as_str()))
+            .collect();
+        assert_eq!(rejected[0], (3, "found 2 fields, expected 3"));
+        assert_eq!(rejected[1], (4, "found 4 fields, expected 3"));
+        assert_eq!(rejected[2].0, 5);
+        let numbers: Vec<Option<i64>> = outcome.rejected.iter().map(|row| row.number).collect();
+        assert_eq!(numbers, [Some(2), Some(3), None]);
+        assert_eq!(outcome.rejected[1].values, ["3", "Closed", "Badge", "extra"]);
+
+        let export = dir.path().join("tickets.rejected.csv");
+        export_rejected_rows(export.clone(), outcome.headers, outcome.rejected)
+            .await
+            .unwrap();
+
+        let written = std::fs::read_to_string(&export).unwrap();
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines[0], "Line,Column,Reason,Number,State,Title");
+        assert_eq!(lines[1], "3,,\"found 2 fields, expected 3\",2,Open");
+        assert_eq!(lines[2], "4,,\"found 4 fields, expected 3\",3,Closed,Badge,extra");
+        assert_eq!(lines[3], r#"5,Number,"invalid integer ""x""",x,Open,Desk"#);
+    }
+
+    #[test]
+    fn accepts_csv_and_spreadsheet_files_only() {
+        assert!(is_supported_file(Path::new("tickets.CSV")));
+        assert!(is_supported_file(Path::new("tickets.xls")));
+        assert!(!is_supported_file(Path::new("tickets.txt")));
+        assert!(!is_supported_file(Path::new("tickets")));
+    }
+
+    fn record(number: i64, title: &str) -> NewHireRecord {
+        serde_json::from_value(serde_json::json!({ "number": number, "title": title })).unwrap()
+    }
+
+    async fn import(
+        db_path: &Path,
+        name: &str,
+        records: Vec<NewHireRecord>,
+        presets: PresetDecisions,
+    ) -> Vec<DuplicateEntry> {
+        let source = ImportSource::from_content(name.to_owned(), name.as_bytes());
+        let events: Vec<ImportEvent> = import_records(
+            db_path.to_path_buf(),
+            source,
+            records,
+            presets,
+            ImportCancel::default(),
+        )
+        .collect()
+        .await;
+
+        let mut duplicates = Vec::new();
+        for event in events {
+            match event {
+                ImportEvent::Progress(progress) => duplicates.extend(progress.duplicates),
+                ImportEvent::Finished(result) => {
+                    result.unwrap();
+                }
+                other => panic!("unexpected event {other:?}"),
+            }
+        }
+        duplicates
+    }
+
+    #[tokio::test]
+    async fn committing_a_preview_writes_what_it_showed() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("preview.sqlite");
+        prepare_database(db_path.clone()).await.unwrap();
+        import(&db_path, "first", vec![record(1, "Laptop")], PresetDecisions::new()).await;
+
+        let records = vec![record(1, "Laptop and dock"), record(2, "Badge")];
+        let preview: ImportPreview = preview_records(db_path.clone(), records, PresetDecisions::new())
+            .await
+            .unwrap();
+        assert_eq!(preview.changed.len(), 1);
+        assert_eq!(preview.inserts.len(), 1);
+
+        let duplicates = import(&db_path, "second", preview.records, preview.decisions).await;
+        assert!(duplicates.is_empty());
+
+        let pool = open_pool(&db_path).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        let stored = fetch_existing_record::<NewHireProfile>(&mut conn, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.title.as_deref(), Some("Laptop and dock"));
+    }
+
+    #[tokio::test]
+    async fn changed_rows_without_a_preview_wait_for_review() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("review.sqlite");
+        prepare_database(db_path.clone()).await.unwrap();
+        import(&db_path, "first", vec![record(1, "Laptop")], PresetDecisions::new()).await;
+
+        let duplicates = import(
+            &db_path,
+            "second",
+            vec![record(1, "Laptop and dock")],
+            PresetDecisions::new(),
+        )
+        .await;
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].record.number, 1);
+    }
+
+    #[tokio::test]
+    async fn merging_writes_only_the_selected_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("merge.sqlite");
+        prepare_database(db_path.clone()).await.unwrap();
+        let ticket = |state: &str, title: &str| -> NewHireRecord {
+            serde_json::from_value(serde_json::json!({
+                "number": 1, "state": state, "title": title
+            }))
+            .unwrap()
+        };
+        import(&db_path, "first", vec![ticket("New", "Laptop")], PresetDecisions::new()).await;
+        let duplicates = import(
+            &db_path,
+            "second",
+            vec![ticket("Closed", "Laptop and dock")],
+            PresetDecisions::new(),
+        )
+        .await;
+
+        let entry = &duplicates[0];
+        let decision = DuplicateDecision::Merge(vec![RecordField::Title]);
+        let outcome = apply_duplicate_decisions::<NewHireProfile>(
+            db_path.clone(),
+            vec![(entry.run_id, entry.record.clone(), decision)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(outcome.updated, 1);
+
+        let pool = open_pool(&db_path).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        let stored = fetch_existing_record::<NewHireProfile>(&mut conn, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.title.as_deref(), Some("Laptop and dock"));
+        assert_eq!(stored.state.as_deref(), Some("New"));
+    }
+
+    #[tokio::test]
+    async fn versions_without_a_state_keep_the_last_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("states.sqlite");
+        prepare_database(db_path.clone()).await.unwrap();
+        let overwrite = || {
+            PresetDecisions::from([(1, (DuplicateDecision::Overwrite, "test".to_owned()))])
+        };
+        for (name, state) in [
+            ("first", Some("New")),
+            ("second", None),
+            ("third", Some("New")),
+            ("fourth", Some("Closed")),
+        ] {
+            let record: NewHireRecord = serde_json::from_value(serde_json::json!({
+                "number": 1, "state": state, "created_at": "2024-01-01T09:00:00Z"
+            }))
+            .unwrap();
+            import(&db_path, name, vec![record], overwrite()).await;
+        }
+
+        let pool = open_pool(&db_path).await.unwrap();
+        let transitions: Vec<(Option<String>, String)> =
+            sqlx::query_as("SELECT from_state, to_state FROM state_transitions ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            transitions,
+            [
+                (None, "New".to_owned()),
+                (Some("New".to_owned()), "Closed".to_owned())
+            ]
+        );
+        let first: String = sqlx::query_scalar(
+            "SELECT transitioned_at FROM state_transitions ORDER BY id LIMIT 1",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(first, "2024-01-01T09:00:00Z");
+    }
+
+    #[tokio::test]
+    async fn timeline_lists_versions_oldest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("timeline.sqlite");
+        prepare_database(db_path.clone()).await.unwrap();
+        let overwrite =
+            PresetDecisions::from([(1, (DuplicateDecision::Overwrite, "test".to_owned()))]);
+        import(&db_path, "first", vec![record(1, "Laptop")], PresetDecisions::new()).await;
+        import(&db_path, "second", vec![record(2, "Badge")], PresetDecisions::new()).await;
+        import(&db_path, "third", vec![record(1, "Laptop and dock")], overwrite).await;
+
+        let (number, versions) = history::load_timeline(db_path, 1).await.unwrap();
+        assert_eq!(number, 1);
+        let versions: Vec<(Option<&str>, Option<&str>)> = versions
+            .iter()
+            .map(|version| (version.file_name.as_deref(), version.record.title.as_deref()))
+            .collect();
+        assert_eq!(
+            versions,
+            [
+                (Some("first"), Some("Laptop")),
+                (Some("third"), Some("Laptop and dock"))
+            ]
+        );
+    }
+
+    async fn deletion(db_path: &Path, number: i64) -> Option<Option<String>> {
+        let pool = open_pool(db_path).await.unwrap();
+        let row = sqlx::query("SELECT deleted_at, deleted_reason FROM new_hire_metrics WHERE number = ?")
+            .bind(number)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let deleted_at: Option<String> = row.get("deleted_at");
+        deleted_at.map(|_| row.get("deleted_reason"))
+    }
+
+    #[tokio::test]
+    async fn rollback_reverses_soft_deletes_and_restores() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("snapshot.sqlite");
+        prepare_database(db_path.clone()).await.unwrap();
+        let both = vec![record(1, "Laptop"), record(2, "Badge")];
+        import(&db_path, "first", both.clone(), PresetDecisions::new()).await;
+        import(&db_path, "second", vec![record(1, "Laptop")], PresetDecisions::new()).await;
+        let runs = history::load_runs(db_path.clone()).await.unwrap();
+        let snapshot_run = runs[0].id;
+
+        let deleted = snapshot::soft_delete(db_path.clone(), snapshot_run, vec![2], "Left".to_owned())
+            .await
+            .unwrap();
+        assert_eq!(deleted, 1);
+        assert_eq!(deletion(&db_path, 2).await, Some(Some("Left".to_owned())));
+
+        import(&db_path, "third", both, PresetDecisions::new()).await;
+        assert_eq!(deletion(&db_path, 2).await, None);
+
+        let runs = history::load_runs(db_path.clone()).await.unwrap();
+        history::roll_back_run::<NewHireProfile>(db_path.clone(), runs[0].id).await.unwrap();
+        assert_eq!(deletion(&db_path, 2).await, Some(Some("Left".to_owned())));
+
+        let outcome = history::roll_back_run::<NewHireProfile>(db_path.clone(), snapshot_run).await.unwrap();
+        assert_eq!(outcome.restored, 1);
+        assert_eq!(deletion(&db_path, 2).await, None);
+    }
+
+    #[tokio::test]
+    async fn rollback_waits_for_later_runs_of_its_tickets() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("rollback.sqlite");
+        prepare_database(db_path.clone()).await.unwrap();
+        let overwrite = |number| {
+            PresetDecisions::from([(number, (DuplicateDecision::Overwrite, "test".to_owned()))])
+        };
+        import(&db_path, "first", vec![record(1, "Laptop")], PresetDecisions::new()).await;
+        let second = vec![record(1, "Laptop and dock"), record(2, "Badge")];
+        import(&db_path, "second", second, overwrite(1)).await;
+        import(&db_path, "third", vec![record(2, "Badge and lanyard")], overwrite(2)).await;
+        let runs = history::load_runs(db_path.clone()).await.unwrap();
+        let run = |name: &str| runs.iter().find(|run| run.file_name == name).unwrap().id;
+
+        let err = history::roll_back_run::<NewHireProfile>(db_path.clone(), run("second"))
+            .await
+            .unwrap_err();
+        assert!(err.starts_with("1 ticket(s) from run #"), "{err}");
+
+        let third = history::roll_back_run::<NewHireProfile>(db_path.clone(), run("third"))
+            .await
+            .unwrap();
+        assert_eq!((third.removed, third.restored), (0, 1));
+        let second = history::roll_back_run::<NewHireProfile>(db_path.clone(), run("second"))
+            .await
+            .unwrap();
+        assert_eq!((second.removed, second.restored), (1, 1));
+
+        let pool = open_pool(&db_path).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        let laptop = fetch_existing_record::<NewHireProfile>(&mut conn, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(laptop.title.as_deref(), Some("Laptop"));
+        let badge = fetch_existing_record::<NewHireProfile>(&mut conn, 2).await.unwrap();
+        assert!(badge.is_none());
+
+        let err = history::roll_back_run::<NewHireProfile>(db_path.clone(), run("second"))
+            .await
+            .unwrap_err();
+        assert!(err.ends_with("was already rolled back."), "{err}");
+    }
+
+    #[tokio::test]
+    async fn unmapped_columns_are_kept_as_extras() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tickets.csv");
+        std::fs::write(
+            &path,
+            "Number,Title,Cost Center,Badge Color\n1,Laptop,42,\n2,Badge,,Blue\n3,Desk,,\n",
+        )
+        .unwrap();
+
+        let outcome = read_mapped_csv(
+            path,
+            mapping(&["Number", "Title", "Cost Center", "Badge Color"]),
+            CsvReadOptions::default(),
+        )
+        .await
+        .unwrap();
+        let extras: Vec<&BTreeMap<String, String>> =
+            outcome.records.iter().map(|record| &record.extras).collect();
+        assert_eq!(
+            extras[0],
+            &BTreeMap::from([("Cost Center".to_owned(), "42".to_owned())])
+        );
+        assert_eq!(
+            extras[1],
+            &BTreeMap::from([("Badge Color".to_owned(), "Blue".to_owned())])
+        );
+        assert!(extras[2].is_empty());
+
+        assert_eq!(FieldValue::from_extras(extras[2]), FieldValue::Text(None));
+        let stored = FieldValue::from_extras(extras[0]);
+        assert_eq!(
+            stored,
+            FieldValue::Text(Some(r#"{"Cost Center":"42"}"#.to_owned()))
+        );
+        assert_eq!(&stored.into_extras(), extras[0]);
+
+        let db_path = dir.path().join("extras.sqlite");
+
+        prepare_database(db_path.clone()).await.unwrap();
+        import(
+            &db_path,
+            "tickets",
+            outcome.records.clone(),
+            PresetDecisions::new(),
+        )
+        .await;
+        let pool = open_pool(&db_path).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        let stored = fetch_existing_record::<NewHireProfile>(&mut conn, 2)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.extras, outcome.records[1].extras);
+    }
+
+    #[tokio::test]
+    async fn strict_reads_fail_on_malformed_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tickets.csv");
+        std::fs::write(&path, "Number,State,Title\n1,Open,Laptop\n2,Open\n").unwrap();
+
+        let err = read_mapped_csv(
+            path,
+            mapping(&["Number", "State", "Title"]),
+            CsvReadOptions::default(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err, "CSV parse error: line 3, found 2 fields, expected 3");
+    }
+
+    #[tokio::test]
+    async fn streams_files_longer_than_a_chunk() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("stream.sqlite");
+        prepare_database(db_path.clone()).await.unwrap();
+        let rows = 2 * CHUNK_SIZE + 10;
+        let mut content = "Number,State,Title\n".to_owned();
+        for number in 1..=rows {
+            if number == 300 {
+                content.push_str("300,Open\n");
+            } else {
+                content.push_str(&format!("{number},Open,Laptop\n"));
+            }
+        }
+        let path = dir.path().join("tickets.csv");
+        std::fs::write(&path, &content).unwrap();
+
+        let options = CsvReadOptions {
+            lenient: true,
+            ..CsvReadOptions::default()
+        };
+        let events: Vec<ImportEvent> = import_mapped_csv(
+            db_path.clone(),
+            path,
+            mapping(&["Number", "State", "Title"]),
+            options,
+            ImportSource::from_content("tickets.csv".to_owned(), content.as_bytes()),
+            PresetDecisions::new(),
+            ImportCancel::default(),
+        )
+        .collect()
+        .await;
+
+        let mut chunks = 0;
+        let (mut processed, mut inserted, mut rejected) = (0, 0, 0);
+        let mut run_id = None;
+        for event in events {
+            match event {
+                ImportEvent::Started { total, .. } => assert_eq!(total, rows),
+                ImportEvent::Progress(progress) => {
+                    chunks += 1;
+                    processed += progress.processed;
+                    inserted += progress.inserted;
+                    rejected += progress.rejected.len();
+                }
+                ImportEvent::Finished(result) => run_id = Some(result.unwrap()),
+                other => panic!("unexpected event {other:?}"),
+            }
+        }
+        assert_eq!(chunks, 3);
+        assert_eq!((processed, inserted, rejected), (rows, rows - 1, 1));
+
+        let pool = open_pool(&db_path).await.unwrap();
+        let count = format!("SELECT COUNT(*) FROM {}", NewHireProfile::TABLE);
+        let stored: i64 = sqlx::query_scalar(&count).fetch_one(&pool).await.unwrap();
+        assert_eq!(stored as usize, rows - 1);
+        let counted: i64 = sqlx::query_scalar("SELECT inserted FROM import_runs WHERE id = ?")
+            .bind(run_id.unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(counted as usize, rows - 1);
+    }
+
+    #[tokio::test]
+    async fn cancelling_keeps_the_table_and_run_in_step() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("cancel.sqlite");
+        prepare_database(db_path.clone()).await.unwrap();
+        let total = 4 * CHUNK_SIZE;
+        let records = (1..=total as i64).map(|number| record(number, "Laptop")).collect();
+
+        let cancel = ImportCancel::default();
+        let mut events = std::pin::pin!(import_records::<NewHireProfile>(
+            db_path.clone(),
+            ImportSource::from_content("tickets".to_owned(), b"tickets"),
+            records,
+            PresetDecisions::new(),
+            cancel.clone(),
+        ));
+        let mut inserted = 0;
+        let mut run_id = None;
+        while let Some(event) = events.next().await {
+            match event {
+                ImportEvent::Progress(progress) => {
+                    inserted += progress.inserted;
+                    cancel.cancel();
+                }
+                ImportEvent::Cancelled(id) => run_id = Some(id),
+                other => panic!("unexpected event {other:?}"),
+            }
+        }
+        assert!(inserted > 0 && inserted < total);
+
+        let pool = open_pool(&db_path).await.unwrap();
+        let count = format!("SELECT COUNT(*) FROM {}", NewHireProfile::TABLE);
+        let stored: i64 = sqlx::query_scalar(&count).fetch_one(&pool).await.unwrap();
+        assert_eq!(stored as usize, inserted);
+        let (counted, cancelled_at): (i64, Option<String>) =
+            sqlx::query_as("SELECT inserted, cancelled_at FROM import_runs WHERE id = ?")
+                .bind(run_id.unwrap())
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(counted as usize, inserted);
+        assert!(cancelled_at.is_some());
+    }
+}
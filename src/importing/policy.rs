@@ -0,0 +1,410 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+
+use sqlx::{Row, SqliteConnection};
+
+use super::mapping::RecordField;
+use super::profile::{field_by_column, ImportProfile, ProfileField};
+use super::timestamps::parse_stored;
+use super::{open_pool, DuplicateDecision, FieldChange, FieldValue};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyKind {
+    TakeIncomingWhenNewer,
+    NeverOverwrite,
+    AcceptWhenOnlyChanged,
+}
+
+impl PolicyKind {
+    pub const ALL: [PolicyKind; 3] = [
+        PolicyKind::TakeIncomingWhenNewer,
+        PolicyKind::NeverOverwrite,
+        PolicyKind::AcceptWhenOnlyChanged,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            PolicyKind::TakeIncomingWhenNewer => "take_incoming_when_newer",
+            PolicyKind::NeverOverwrite => "never_overwrite",
+            PolicyKind::AcceptWhenOnlyChanged => "accept_when_only_changed",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.key() == key)
+    }
+
+    pub fn is_single_field(self) -> bool {
+        self == PolicyKind::TakeIncomingWhenNewer
+    }
+}
+
+impl fmt::Display for PolicyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PolicyKind::TakeIncomingWhenNewer => "Take incoming when newer",
+            PolicyKind::NeverOverwrite => "Never overwrite",
+            PolicyKind::AcceptWhenOnlyChanged => "Auto-accept if only changed",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicatePolicy<F = RecordField> {
+    pub kind: PolicyKind,
+    pub fields: Vec<F>,
+    pub enabled: bool,
+}
+
+impl<F: ProfileField> DuplicatePolicy<F> {
+    pub fn describe(&self) -> String {
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| field.label())
+            .collect::<Vec<_>>();
+
+        match self.kind {
+            PolicyKind::TakeIncomingWhenNewer => {
+                format!("Take incoming when {} is newer", fields.join(", "))
+            }
+            PolicyKind::NeverOverwrite => format!("Never overwrite {}", fields.join(", ")),
+            PolicyKind::AcceptWhenOnlyChanged => {
+                format!("Auto-accept if only {} changed", fields.join(" or "))
+            }
+        }
+    }
+}
+
+/// Decisions settled before an import starts, with the rule that made each.
+/// They take precedence over the policies.
+pub type PresetDecisions<F = RecordField> = HashMap<i64, (DuplicateDecision<F>, String)>;
+
+#[derive(Debug, Clone)]
+pub struct PolicySet<F = RecordField> {
+    policies: Vec<DuplicatePolicy<F>>,
+    presets: PresetDecisions<F>,
+}
+
+impl<F: ProfileField> PolicySet<F> {
+    pub fn new(policies: Vec<DuplicatePolicy<F>>) -> Self {
+        Self {
+            policies: policies
+                .into_iter()
+                .filter(|policy| policy.enabled && !policy.fields.is_empty())
+                .collect(),
+            presets: PresetDecisions::new(),
+        }
+    }
+
+    pub fn with_presets(mut self, presets: PresetDecisions<F>) -> Self {
+        self.presets = presets;
+        self
+    }
+
+    /// "Never overwrite" rules only restrict what the other rules write; on their
+    /// own they decide a row only when every change is protected.
+    pub fn resolve<P: ImportProfile<Field = F>>(
+        &self,
+        existing: &P::Record,
+        incoming: &P::Record,
+        changes: &[FieldChange<F>],
+    ) -> Option<(DuplicateDecision<F>, String)> {
+        if let Some(preset) = self.presets.get(&P::key(incoming)) {
+            return Some(preset.clone());
+        }
+
+        let protecting: Vec<&DuplicatePolicy<F>> = self
+            .policies
+            .iter()
+            .filter(|policy| policy.kind == PolicyKind::NeverOverwrite)
+            .filter(|policy| {
+                changes
+                    .iter()
+                    .any(|change| policy.fields.contains(&change.field))
+            })
+            .collect();
+        let protected: HashSet<F> = protecting
+            .iter()
+            .flat_map(|policy| policy.fields.iter().copied())
+            .collect();
+
+        let writable: Vec<F> = changes
+            .iter()
+            .map(|change| change.field)
+            .filter(|field| !protected.contains(field))
+            .collect();
+
+        let protection = protecting
+            .iter()
+            .map(|policy| policy.describe())
+            .collect::<Vec<_>>();
+
+        if writable.is_empty() {
+            return (!protection.is_empty())
+                .then(|| (DuplicateDecision::Skip, protection.join("; ")));
+        }
+
+        let decision = if protection.is_empty() {
+            DuplicateDecision::Overwrite
+        } else {
+            DuplicateDecision::Merge(writable)
+        };
+
+        self.policies
+            .iter()
+            .find(|policy| match policy.kind {
+                PolicyKind::TakeIncomingWhenNewer => is_newer(
+                    &P::value(existing, policy.fields[0]),
+                    &P::value(incoming, policy.fields[0]),
+                ),
+                PolicyKind::AcceptWhenOnlyChanged => changes
+                    .iter()
+                    .all(|change| policy.fields.contains(&change.field)),
+                PolicyKind::NeverOverwrite => false,
+            })
+            .map(|policy| {
+                let rule = std::iter::once(policy.describe())
+                    .chain(protection.iter().cloned())
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                (decision, rule)
+            })
+    }
+}
+
+/// A value replacing an empty one counts as newer.
+fn is_newer(old: &FieldValue, new: &FieldValue) -> bool {
+    match (old, new) {
+        (FieldValue::Integer(old), FieldValue::Integer(Some(new))) => {
+            old.is_none_or(|old| *new > old)
+        }
+        (FieldValue::Text(old), FieldValue::Text(Some(new))) => {
+            let Some(new) = parse_stored(new) else {
+                return false;
+            };
+            match old {
+                Some(old) => parse_stored(old).is_some_and(|old| new > old),
+                None => true,
+            }
+        }
+        _ => false,
+    }
+}
+
+pub async fn load_policies<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+) -> Result<Vec<DuplicatePolicy<P::Field>>, String> {
+    let rows = sqlx::query(
+        r#"
+        SELECT kind, fields, enabled
+        FROM duplicate_policies
+        WHERE profile = ?
+        ORDER BY position
+        "#,
+    )
+    .bind(P::NAME)
+    .fetch_all(conn)
+    .await
+    .map_err(|err| format!("Failed to load duplicate policies: {err}"))?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let kind: String = row.get("kind");
+            let fields: String = row.get("fields");
+            let enabled: bool = row.get("enabled");
+
+            Some(DuplicatePolicy {
+                kind: PolicyKind::from_key(&kind)?,
+                fields: fields.split(',').filter_map(field_by_column::<P>).collect(),
+                enabled,
+            })
+        })
+        .collect())
+}
+
+pub async fn load_policy_list<P: ImportProfile>(
+    db_path: PathBuf,
+) -> Result<Vec<DuplicatePolicy<P::Field>>, String> {
+    let pool = open_pool(&db_path).await?;
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|err| format!("Failed to connect to database: {err}"))?;
+
+    load_policies::<P>(&mut conn).await
+}
+
+pub async fn save_policies<P: ImportProfile>(
+    db_path: PathBuf,
+    policies: Vec<DuplicatePolicy<P::Field>>,
+) -> Result<Vec<DuplicatePolicy<P::Field>>, String> {
+    let pool = open_pool(&db_path).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|err| format!("Failed to start transaction: {err}"))?;
+
+    sqlx::query("DELETE FROM duplicate_policies WHERE profile = ?")
+        .bind(P::NAME)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| format!("Failed to save duplicate policies: {err}"))?;
+
+    for (position, policy) in policies.iter().enumerate() {
+        let fields = policy
+            .fields
+            .iter()
+            .map(|field| field.column())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        sqlx::query(
+            r#"
+            INSERT INTO duplicate_policies (profile, position, kind, fields, enabled)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(P::NAME)
+        .bind(position as i64)
+        .bind(policy.kind.key())
+        .bind(fields)
+        .bind(policy.enabled)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| format!("Failed to save duplicate policies: {err}"))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|err| format!("Failed to save duplicate policies: {err}"))?;
+
+    Ok(policies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importing::incidents::IncidentProfile;
+    use crate::importing::migrations::prepare_database;
+    use crate::importing::profile::diff_records;
+    use crate::importing::{NewHireProfile, NewHireRecord};
+
+    fn record(value: serde_json::Value) -> NewHireRecord {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn policy(kind: PolicyKind, fields: &[RecordField]) -> DuplicatePolicy {
+        DuplicatePolicy {
+            kind,
+            fields: fields.to_vec(),
+            enabled: true,
+        }
+    }
+
+    fn resolve(
+        policies: Vec<DuplicatePolicy>,
+        existing: &NewHireRecord,
+        incoming: &NewHireRecord,
+    ) -> Option<(DuplicateDecision, String)> {
+        let changes = diff_records::<NewHireProfile>(existing, incoming);
+        PolicySet::new(policies).resolve::<NewHireProfile>(existing, incoming, &changes)
+    }
+
+    #[test]
+    fn takes_incoming_only_when_newer() {
+        let policies = vec![policy(
+            PolicyKind::TakeIncomingWhenNewer,
+            &[RecordField::ResolvedAt],
+        )];
+        let existing = record(serde_json::json!({
+            "number": 1, "state": "Open", "resolved_at": "2024-03-01T09:00:00Z"
+        }));
+        let newer = record(serde_json::json!({
+            "number": 1, "state": "Resolved", "resolved_at": "2024-03-02T08:00:00Z"
+        }));
+        let older = record(serde_json::json!({
+            "number": 1, "state": "Resolved", "resolved_at": "2024-02-28"
+        }));
+
+        let (decision, rule) = resolve(policies.clone(), &existing, &newer).unwrap();
+        assert_eq!(decision, DuplicateDecision::Overwrite);
+        assert_eq!(rule, "Take incoming when resolved_at is newer");
+        assert!(resolve(policies, &existing, &older).is_none());
+    }
+
+    #[test]
+    fn never_overwrite_narrows_other_rules_to_a_merge() {
+        let existing = record(serde_json::json!({ "number": 1, "state": "Open", "site": "HQ" }));
+        let incoming =
+            record(serde_json::json!({ "number": 1, "state": "Closed", "site": "Remote" }));
+        let protect_site = policy(PolicyKind::NeverOverwrite, &[RecordField::Site]);
+
+        // On its own the rule leaves the unprotected change for review.
+        assert!(resolve(vec![protect_site.clone()], &existing, &incoming).is_none());
+
+        let accept_state = policy(
+            PolicyKind::AcceptWhenOnlyChanged,
+            &[RecordField::State, RecordField::Site],
+        );
+        let (decision, rule) = resolve(
+            vec![protect_site.clone(), accept_state],
+            &existing,
+            &incoming,
+        )
+        .unwrap();
+        assert_eq!(decision, DuplicateDecision::Merge(vec![RecordField::State]));
+        assert_eq!(
+            rule,
+            "Auto-accept if only state or site changed; Never overwrite site"
+        );
+
+        let site_only =
+            record(serde_json::json!({ "number": 1, "state": "Open", "site": "Remote" }));
+        let (decision, _) = resolve(vec![protect_site], &existing, &site_only).unwrap();
+        assert_eq!(decision, DuplicateDecision::Skip);
+    }
+
+    #[test]
+    fn presets_and_disabled_policies() {
+        let existing = record(serde_json::json!({ "number": 7, "state": "Open" }));
+        let incoming = record(serde_json::json!({ "number": 7, "state": "Closed" }));
+        let mut accept = policy(PolicyKind::AcceptWhenOnlyChanged, &[RecordField::State]);
+        accept.enabled = false;
+        assert!(resolve(vec![accept.clone()], &existing, &incoming).is_none());
+
+        let presets =
+            PresetDecisions::from([(7, (DuplicateDecision::Skip, "Earlier file".to_owned()))]);
+        let changes = diff_records::<NewHireProfile>(&existing, &incoming);
+        let resolved = PolicySet::new(vec![accept])
+            .with_presets(presets)
+            .resolve::<NewHireProfile>(&existing, &incoming, &changes);
+        assert_eq!(
+            resolved,
+            Some((DuplicateDecision::Skip, "Earlier file".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn policies_belong_to_their_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("policies.sqlite");
+        prepare_database(db_path.clone()).await.unwrap();
+        let policy = DuplicatePolicy {
+            kind: PolicyKind::AcceptWhenOnlyChanged,
+            fields: vec![RecordField::State],
+            enabled: true,
+        };
+        save_policies::<NewHireProfile>(db_path.clone(), vec![policy.clone()])
+            .await
+            .unwrap();
+
+        let new_hire = load_policy_list::<NewHireProfile>(db_path.clone()).await.unwrap();
+        assert_eq!(new_hire, [policy]);
+        let incidents = load_policy_list::<IncidentProfile>(db_path).await.unwrap();
+        assert!(incidents.is_empty());
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::query::Query;
+use sqlx::sqlite::{Sqlite, SqliteArguments};
+use sqlx::{Row, SqliteConnection};
+
+use super::mapping::CellError;
+use super::timestamps::TimestampSettings;
+use super::{FieldChange, FieldValue};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Text,
+    Integer,
+    Timestamp,
+    /// Unmapped columns, stored as a JSON object of header to text.
+    Extras,
+}
+
+pub trait ProfileField:
+    Copy + Eq + Hash + fmt::Debug + fmt::Display + Send + Sync + 'static
+{
+    fn column(self) -> &'static str;
+    fn header(self) -> &'static str;
+    fn label(self) -> &'static str;
+    fn kind(self) -> FieldKind;
+
+    fn matches_header(self, header: &str) -> bool {
+        header == self.header()
+    }
+}
+
+pub trait ImportProfile: Clone + fmt::Debug + Send + Sync + 'static {
+    type Field: ProfileField;
+    type Record: Clone + fmt::Debug + Send + Sync + Serialize + DeserializeOwned;
+
+    const NAME: &'static str;
+    const TABLE: &'static str;
+    const KEY: Self::Field;
+    const FIELDS: &'static [Self::Field];
+    const EXTRAS: Self::Field;
+    const TITLE: Self::Field;
+    const CREATED_AT: Self::Field;
+    const KEEPS_HISTORY: bool = false;
+    const STATE: Option<Self::Field> = None;
+    const SITE: Option<Self::Field> = None;
+    const ASSIGNEE: Option<Self::Field> = None;
+    const UPDATED_AT: Option<Self::Field> = None;
+    /// Missing records of a full export are soft-deleted, then restored if they return.
+    const SOFT_DELETES: bool = false;
+
+    fn key(record: &Self::Record) -> i64;
+    fn value(record: &Self::Record, field: Self::Field) -> FieldValue;
+    fn build(value: impl FnMut(Self::Field) -> FieldValue) -> Self::Record;
+
+    fn derived(_field: Self::Field, _value: &FieldValue) -> Option<(&'static str, FieldValue)> {
+        None
+    }
+}
+
+pub fn parse_value(
+    kind: FieldKind,
+    text: &str,
+    timestamps: &TimestampSettings,
+) -> Result<FieldValue, String> {
+    match kind {
+        FieldKind::Text | FieldKind::Extras => Ok(FieldValue::Text(Some(text.to_owned()))),
+        FieldKind::Integer => text
+            .parse::<i64>()
+            .map(|value| FieldValue::Integer(Some(value)))
+            .map_err(|_| format!("invalid integer \"{text}\"")),
+        FieldKind::Timestamp => timestamps
+            .normalize(text)
+            .map(|value| FieldValue::Text(Some(value)))
+            .ok_or_else(|| format!("unrecognised timestamp \"{text}\"")),
+    }
+}
+
+/// Returns `None` when the key is empty; `column` names a field's source in errors.
+pub fn build_record<P: ImportProfile>(
+    text: impl Fn(P::Field) -> Option<String>,
+    column: impl Fn(P::Field) -> String,
+    extras: BTreeMap<String, String>,
+    timestamps: &TimestampSettings,
+) -> Result<Option<P::Record>, CellError> {
+    let parse = |field: P::Field| match text(field) {
+        Some(value) => parse_value(field.kind(), &value, timestamps).map_err(|reason| CellError {
+            column: column(field),
+            reason,
+        }),
+        None => Ok(FieldValue::empty(field.kind())),
+    };
+
+    if parse(P::KEY)? == FieldValue::Integer(None) {
+        return Ok(None);
+    }
+
+    let mut values = HashMap::new();
+    for field in P::FIELDS.iter().filter(|field| **field != P::EXTRAS) {
+        values.insert(*field, parse(*field)?);
+    }
+    values.insert(P::EXTRAS, FieldValue::from_extras(&extras));
+
+    Ok(Some(P::build(|field| {
+        values
+            .remove(&field)
+            .unwrap_or_else(|| FieldValue::empty(field.kind()))
+    })))
+}
+
+pub fn field_by_column<P: ImportProfile>(column: &str) -> Option<P::Field> {
+    P::FIELDS.iter().copied().find(|field| field.column() == column)
+}
+
+pub fn diff_records<P: ImportProfile>(
+    existing: &P::Record,
+    incoming: &P::Record,
+) -> Vec<FieldChange<P::Field>> {
+    P::FIELDS
+        .iter()
+        .filter(|field| **field != P::KEY)
+        .filter_map(|field| {
+            let old = P::value(existing, *field);
+            let new = P::value(incoming, *field);
+            (old != new).then_some(FieldChange {
+                field: *field,
+                old,
+                new,
+            })
+        })
+        .collect()
+}
+
+pub async fn fetch_existing_record<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    key: i64,
+) -> Result<Option<P::Record>, String> {
+    let columns = P::FIELDS
+        .iter()
+        .map(|field| field.column())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        "SELECT {columns} FROM {} WHERE {} = ? LIMIT 1",
+        P::TABLE,
+        P::KEY.column()
+    );
+
+    let row = sqlx::query(&sql)
+        .bind(key)
+        .fetch_optional(conn)
+        .await
+        .map_err(|err| format!("Failed to fetch existing record: {err}"))?;
+
+    Ok(row.map(|row| {
+        P::build(|field| match field.kind() {
+            FieldKind::Integer => FieldValue::Integer(row.try_get(field.column()).unwrap_or(None)),
+            FieldKind::Text | FieldKind::Timestamp | FieldKind::Extras => {
+                FieldValue::Text(row.try_get(field.column()).unwrap_or(None))
+            }
+        })
+    }))
+}
+
+pub async fn insert_record<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    record: &P::Record,
+) -> Result<(), String> {
+    let values = with_derived::<P>(record, P::FIELDS);
+    let columns = values
+        .iter()
+        .map(|(column, _)| *column)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let placeholders = vec!["?"; values.len()].join(", ");
+    let sql = format!(
+        "INSERT INTO {} ({columns}) VALUES ({placeholders})",
+        P::TABLE
+    );
+
+    let mut query = sqlx::query(&sql);
+    for (_, value) in values {
+        query = bind_value(query, value);
+    }
+
+    query
+        .execute(conn)
+        .await
+        .map_err(|err| format!("Failed to insert record: {err}"))?;
+
+    Ok(())
+}
+
+pub async fn update_record<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    record: &P::Record,
+) -> Result<(), String> {
+    let fields: Vec<P::Field> = P::FIELDS
+        .iter()
+        .copied()
+        .filter(|field| *field != P::KEY)
+        .collect();
+    update_fields::<P>(conn, record, &fields).await
+}
+
+/// Also rewrites the columns derived from `fields`.
+pub async fn update_fields<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    record: &P::Record,
+    fields: &[P::Field],
+) -> Result<(), String> {
+    let values = with_derived::<P>(record, fields);
+    let assignments = values
+        .iter()
+        .map(|(column, _)| format!("{column} = ?"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        "UPDATE {} SET {assignments} WHERE {} = ?",
+        P::TABLE,
+        P::KEY.column()
+    );
+
+    let mut query = sqlx::query(&sql);
+    for (_, value) in values {
+        query = bind_value(query, value);
+    }
+
+    query
+        .bind(P::key(record))
+        .execute(conn)
+        .await
+        .map_err(|err| format!("Failed to update record: {err}"))?;
+
+    Ok(())
+}
+
+pub async fn delete_record<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    key: i64,
+) -> Result<(), String> {
+    let sql = format!("DELETE FROM {} WHERE {} = ?", P::TABLE, P::KEY.column());
+    sqlx::query(&sql)
+        .bind(key)
+        .execute(conn)
+        .await
+        .map_err(|err| format!("Failed to delete record: {err}"))?;
+
+    Ok(())
+}
+
+fn with_derived<P: ImportProfile>(
+    record: &P::Record,
+    fields: &[P::Field],
+) -> Vec<(&'static str, FieldValue)> {
+    let values: Vec<(P::Field, FieldValue)> = fields
+        .iter()
+        .map(|field| (*field, P::value(record, *field)))
+        .collect();
+    let derived: Vec<(&'static str, FieldValue)> = values
+        .iter()
+        .filter_map(|(field, value)| P::derived(*field, value))
+        .collect();
+
+    values
+        .into_iter()
+        .map(|(field, value)| (field.column(), value))
+        .chain(derived)
+        .collect()
+}
+
+fn bind_value<'q>(
+    query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    value: FieldValue,
+) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+    match value {
+        FieldValue::Text(value) => query.bind(value),
+        FieldValue::Integer(value) => query.bind(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importing::mapping::RecordField;
+    use crate::importing::{NewHireProfile, NewHireRecord};
+
+    fn record(value: serde_json::Value) -> NewHireRecord {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn diff_lists_changed_fields_with_both_values() {
+        let existing = record(serde_json::json!({
+            "number": 4, "state": "Open", "title": "Laptop", "it_lead_time_elapsed": 3,
+            "extras": { "Cost Center": "42" }
+        }));
+        let incoming = record(serde_json::json!({
+            "number": 4, "state": "Closed", "title": "Laptop", "it_lead_time_elapsed": 5,
+            "extras": { "Cost Center": "43" }
+        }));
+
+        let changes = diff_records::<NewHireProfile>(&existing, &incoming);
+        let fields: Vec<RecordField> = changes.iter().map(|change| change.field).collect();
+        assert_eq!(
+            fields,
+            [
+                RecordField::State,
+                RecordField::ItLeadTimeElapsed,
+                RecordField::Extras
+            ]
+        );
+        assert_eq!(changes[0].to_string(), "state: Open -> Closed");
+        assert_eq!(changes[1].old, FieldValue::Integer(Some(3)));
+        assert_eq!(changes[1].new, FieldValue::Integer(Some(5)));
+
+        assert!(diff_records::<NewHireProfile>(&existing, &existing).is_empty());
+    }
+}
@@ -0,0 +1,506 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::policy::PresetDecisions;
+use super::profile::{ImportProfile, ProfileField};
+use super::{AutoDecision, DuplicateDecision, DuplicateEntry, ImportKind, NewHireProfile};
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportQueue {
+    pub files: Vec<QueuedFile>,
+    active: Option<usize>,
+    active_numbers: Vec<i64>,
+    tickets: HashMap<(ImportKind, i64), TicketSource>,
+    next_order: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct QueuedFile {
+    pub path: PathBuf,
+    pub file_name: String,
+    pub kind: ImportKind,
+    pub status: QueuedStatus,
+    /// Modification time when queued; decides which of two files is newer.
+    modified: Option<SystemTime>,
+    order: usize,
+}
+
+impl QueuedFile {
+    /// Oldest first: by modification time, then by when queued.
+    fn age_key(&self) -> (Option<SystemTime>, usize) {
+        (self.modified, self.order)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueuedStatus {
+    Waiting,
+    Importing,
+    Imported {
+        inserted: usize,
+        updated: usize,
+        skipped: usize,
+        to_review: usize,
+    },
+    PartlyImported {
+        inserted: usize,
+        updated: usize,
+        skipped: usize,
+        to_review: usize,
+    },
+    Failed(String),
+    Cancelled,
+}
+
+impl QueuedStatus {
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, QueuedStatus::Waiting | QueuedStatus::Importing)
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            QueuedStatus::Waiting => "Waiting".to_owned(),
+            QueuedStatus::Importing => "Importing".to_owned(),
+            QueuedStatus::Imported {
+                inserted,
+                updated,
+                skipped,
+                to_review,
+            } => format!(
+                "{inserted} inserted, {updated} updated, {skipped} skipped, {to_review} to review"
+            ),
+            QueuedStatus::PartlyImported {
+                inserted,
+                updated,
+                skipped,
+                to_review,
+            } => format!(
+                "Cancelled: {inserted} inserted, {updated} updated, {skipped} skipped, {to_review} to review"
+            ),
+            QueuedStatus::Failed(err) => format!("Failed: {err}"),
+            QueuedStatus::Cancelled => "Cancelled".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TicketSource {
+    file_name: String,
+    age: (Option<SystemTime>, usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct RepeatDecision<P: ImportProfile = NewHireProfile> {
+    pub run_id: i64,
+    pub record: P::Record,
+    pub decision: DuplicateDecision<P::Field>,
+    pub auto: AutoDecision,
+}
+
+impl ImportQueue {
+    /// Queuing into an idle queue starts a new session, so earlier tickets no
+    /// longer count as repeats.
+    pub fn push(&mut self, path: PathBuf, kind: ImportKind) {
+        if self
+            .files
+            .iter()
+            .any(|file| !file.status.is_finished() && file.path == path)
+        {
+            return;
+        }
+
+        if !self.is_busy() {
+            self.tickets.clear();
+        }
+
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        self.files.push(QueuedFile {
+            file_name: file_name(&path),
+            path,
+            kind,
+            status: QueuedStatus::Waiting,
+            modified,
+            order: self.next_order,
+        });
+        self.next_order += 1;
+    }
+
+    pub fn start_next(&mut self) -> Option<(PathBuf, ImportKind)> {
+        if self.active.is_some() {
+            return None;
+        }
+
+        let index = self
+            .files
+            .iter()
+            .position(|file| file.status == QueuedStatus::Waiting)?;
+        self.files[index].status = QueuedStatus::Importing;
+        self.active = Some(index);
+        self.active_numbers.clear();
+        let file = &self.files[index];
+        Some((file.path.clone(), file.kind))
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.files.iter().any(|file| !file.status.is_finished())
+    }
+
+    pub fn waiting(&self) -> usize {
+        self.files
+            .iter()
+            .filter(|file| file.status == QueuedStatus::Waiting)
+            .count()
+    }
+
+    pub fn track_numbers(&mut self, numbers: &[i64]) {
+        if self.active.is_some() {
+            self.active_numbers.extend_from_slice(numbers);
+        }
+    }
+
+    pub fn finish(&mut self, status: QueuedStatus) {
+        if let Some(index) = self.active.take() {
+            self.files[index].status = status;
+        }
+        self.active_numbers.clear();
+    }
+
+    pub fn cancel_waiting(&mut self) {
+        for file in &mut self.files {
+            if file.status == QueuedStatus::Waiting {
+                file.status = QueuedStatus::Cancelled;
+            }
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.files.len() && self.files[index].status == QueuedStatus::Waiting {
+            self.files.remove(index);
+            if let Some(active) = self.active.as_mut()
+                && *active > index
+            {
+                *active -= 1;
+            }
+        }
+    }
+
+    pub fn clear_finished(&mut self) {
+        let active = self.active.map(|index| self.files[index].order);
+        self.files.retain(|file| !file.status.is_finished());
+        self.active = active.and_then(|order| self.files.iter().position(|file| file.order == order));
+    }
+
+    /// The active file must not replace these, whatever the policies decide.
+    pub fn newer_versions<F: ProfileField>(&self) -> PresetDecisions<F> {
+        let Some(index) = self.active else {
+            return PresetDecisions::new();
+        };
+        let file = &self.files[index];
+        let age = file.age_key();
+
+        self.tickets
+            .iter()
+            .filter(|((kind, _), source)| *kind == file.kind && source.age > age)
+            .map(|((_, number), source)| {
+                let rule = format!("Newer file wins ({} was modified later)", source.file_name);
+                (*number, (DuplicateDecision::Skip, rule))
+            })
+            .collect()
+    }
+
+    /// The newer file's version wins. The losing queued duplicate is removed from
+    /// `pending` and returned with the decision to apply.
+    pub fn settle_repeats<P: ImportProfile>(
+        &mut self,
+        run_id: i64,
+        pending: &mut VecDeque<DuplicateEntry<P>>,
+    ) -> Vec<RepeatDecision<P>> {
+        let Some(index) = self.active else {
+            return Vec::new();
+        };
+        let file = &self.files[index];
+        let kind = file.kind;
+        let current = TicketSource {
+            file_name: file.file_name.clone(),
+            age: file.age_key(),
+        };
+
+        let mut decisions = Vec::new();
+        let mut seen = HashSet::new();
+        for number in std::mem::take(&mut self.active_numbers) {
+            if !seen.insert(number) {
+                continue;
+            }
+
+            let own = pending
+                .iter()
+                .position(|entry| entry.run_id == run_id && P::key(&entry.record) == number);
+            let other = pending
+                .iter()
+                .position(|entry| entry.run_id != run_id && P::key(&entry.record) == number);
+
+            let previous = self.tickets.get(&(kind, number)).cloned();
+            let newer = previous
+                .as_ref()
+                .is_none_or(|previous| current.age > previous.age);
+
+            let settled = match (&previous, newer, own, other) {
+                // An older queued change loses to this file's version.
+                (_, true, Some(_), Some(other)) | (Some(_), true, None, Some(other)) => Some((
+                    other,
+                    DuplicateDecision::Skip,
+                    format!("Newer file wins ({} replaces it)", current.file_name),
+                )),
+                // An earlier file of the queue wrote the ticket.
+                (Some(previous), true, Some(own), None) => Some((
+                    own,
+                    DuplicateDecision::Overwrite,
+                    format!("Newer file wins (replaces {})", previous.file_name),
+                )),
+                (Some(previous), false, Some(own), _) => Some((
+                    own,
+                    DuplicateDecision::Skip,
+                    format!("Newer file wins ({} was modified later)", previous.file_name),
+                )),
+                _ => None,
+            };
+
+            if newer {
+                self.tickets.insert((kind, number), current.clone());
+            }
+
+            if let Some((position, decision, rule)) = settled
+                && let Some(entry) = pending.remove(position)
+            {
+                decisions.push(RepeatDecision {
+                    auto: AutoDecision::new(&entry.summary, &decision, rule),
+                    run_id: entry.run_id,
+                    record: entry.record,
+                    decision,
+                });
+            }
+        }
+
+        decisions
+    }
+}
+
+/// Only the version of the latest import run stays queued, whatever the files'
+/// modification times; older ones are returned to be skipped.
+pub fn merge_pending<P: ImportProfile>(
+    pending: &mut VecDeque<DuplicateEntry<P>>,
+    entries: Vec<DuplicateEntry<P>>,
+) -> (usize, Vec<RepeatDecision<P>>) {
+    let mut added = 0;
+    let mut decisions = Vec::new();
+    for entry in entries {
+        let existing = pending
+            .iter()
+            .position(|pending| P::key(&pending.record) == P::key(&entry.record));
+
+        let (stale, winner) = match existing {
+            Some(position) if pending[position].run_id == entry.run_id => continue,
+            Some(position) if pending[position].run_id > entry.run_id => {
+                (entry, pending[position].run_id)
+            }
+            Some(position) => {
+                let Some(stale) = pending.remove(position) else {
+                    continue;
+                };
+                let winner = entry.run_id;
+                pending.push_back(entry);
+                (stale, winner)
+            }
+            None => {
+                pending.push_back(entry);
+                added += 1;
+                continue;
+            }
+        };
+
+        let rule = format!("Later import wins (run #{winner})");
+        decisions.push(RepeatDecision {
+            auto: AutoDecision::new(&stale.summary, &DuplicateDecision::Skip, rule),
+            run_id: stale.run_id,
+            record: stale.record,
+            decision: DuplicateDecision::Skip,
+        });
+    }
+
+    (added, decisions)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::importing::incidents::IncidentField;
+    use crate::importing::mapping::RecordField;
+    use crate::importing::{NewHireRecord, PendingDuplicate};
+
+    fn entry(run_id: i64, number: i64) -> DuplicateEntry {
+        let record: NewHireRecord =
+            serde_json::from_value(serde_json::json!({ "number": number })).unwrap();
+        DuplicateEntry {
+            run_id,
+            summary: PendingDuplicate::from_record::<NewHireProfile>(&record, Vec::new()),
+            record,
+        }
+    }
+
+    fn queue(dir: &Path, files: &[(&str, u64)]) -> ImportQueue {
+        let mut queue = ImportQueue::default();
+        for (name, age) in files {
+            let path = dir.join(name);
+            let file = std::fs::File::create(&path).unwrap();
+            file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 + age))
+                .unwrap();
+            queue.push(path, ImportKind::NewHires);
+        }
+        queue
+    }
+
+    fn import(queue: &mut ImportQueue, numbers: &[i64]) -> PathBuf {
+        let (path, _) = queue.start_next().unwrap();
+        queue.track_numbers(numbers);
+        path
+    }
+
+    #[test]
+    fn newer_file_overwrites_ticket_an_older_file_wrote() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue = queue(dir.path(), &[("monday.csv", 0), ("tuesday.csv", 10)]);
+        let mut pending = VecDeque::new();
+
+        import(&mut queue, &[1]);
+        assert!(queue.settle_repeats(1, &mut pending).is_empty());
+        queue.finish(QueuedStatus::Cancelled);
+
+        import(&mut queue, &[1]);
+        assert!(queue.newer_versions::<RecordField>().is_empty());
+        pending.push_back(entry(2, 1));
+        let decisions = queue.settle_repeats(2, &mut pending);
+        assert_eq!(decisions.len(), 1);
+        assert_eq!(decisions[0].run_id, 2);
+        assert_eq!(decisions[0].decision, DuplicateDecision::Overwrite);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn newer_file_replaces_queued_change_of_older_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue = queue(dir.path(), &[("monday.csv", 0), ("tuesday.csv", 10)]);
+        let mut pending = VecDeque::from([entry(1, 1)]);
+
+        import(&mut queue, &[1]);
+        assert!(queue.settle_repeats(1, &mut pending).is_empty());
+        queue.finish(QueuedStatus::Cancelled);
+
+        import(&mut queue, &[1]);
+        pending.push_back(entry(2, 1));
+        let decisions = queue.settle_repeats(2, &mut pending);
+        assert_eq!(decisions.len(), 1);
+        assert_eq!(decisions[0].run_id, 1);
+        assert_eq!(decisions[0].decision, DuplicateDecision::Skip);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].run_id, 2);
+    }
+
+    #[test]
+    fn older_file_imported_later_cannot_replace_newer_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue = queue(dir.path(), &[("tuesday.csv", 10), ("monday.csv", 0)]);
+        let mut pending = VecDeque::new();
+
+        import(&mut queue, &[1, 2]);
+        assert!(queue.settle_repeats(1, &mut pending).is_empty());
+        queue.finish(QueuedStatus::Cancelled);
+
+        // Whatever the policies say, the older file is told to skip both.
+        let path = import(&mut queue, &[1, 2, 3]);
+        assert_eq!(file_name(&path), "monday.csv");
+        let presets = queue.newer_versions::<RecordField>();
+        assert_eq!(presets.len(), 2);
+        assert_eq!(presets[&1].0, DuplicateDecision::Skip);
+        assert_eq!(presets[&1].1, "Newer file wins (tuesday.csv was modified later)");
+
+        // A change the older file queued anyway is dropped.
+        pending.push_back(entry(2, 2));
+        let decisions = queue.settle_repeats(2, &mut pending);
+        assert_eq!(decisions.len(), 1);
+        assert_eq!(decisions[0].record.number, 2);
+        assert_eq!(decisions[0].decision, DuplicateDecision::Skip);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn tickets_of_another_dataset_are_not_repeats() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue = queue(dir.path(), &[("tuesday.csv", 10)]);
+        let incidents = dir.path().join("incidents.csv");
+        std::fs::File::create(&incidents)
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000))
+            .unwrap();
+        queue.push(incidents, ImportKind::Incidents);
+
+        import(&mut queue, &[1]);
+        assert!(queue.settle_repeats(1, &mut VecDeque::<DuplicateEntry>::new()).is_empty());
+        queue.finish(QueuedStatus::Cancelled);
+
+        import(&mut queue, &[1]);
+        assert!(queue.newer_versions::<IncidentField>().is_empty());
+    }
+
+    #[test]
+    fn dropped_files_wait_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue = queue(dir.path(), &[("monday.csv", 0), ("tuesday.xlsx", 10)]);
+        queue.push(dir.path().join("monday.csv"), ImportKind::NewHires);
+        assert_eq!(queue.waiting(), 2);
+
+        assert_eq!(file_name(&import(&mut queue, &[])), "monday.csv");
+        queue.finish(QueuedStatus::Cancelled);
+        assert_eq!(file_name(&import(&mut queue, &[])), "tuesday.xlsx");
+    }
+
+    #[test]
+    fn merge_keeps_latest_run_version_queued() {
+        let mut pending = VecDeque::from([entry(3, 1), entry(3, 2)]);
+        let (added, decisions) = merge_pending(
+            &mut pending,
+            vec![entry(2, 1), entry(3, 2), entry(4, 2), entry(4, 5)],
+        );
+
+        assert_eq!(added, 1);
+        let stale: Vec<(i64, i64)> = decisions
+            .iter()
+            .map(|decision| (decision.run_id, decision.record.number))
+            .collect();
+        assert_eq!(stale, [(2, 1), (3, 2)]);
+        assert!(decisions
+            .iter()
+            .all(|decision| decision.decision == DuplicateDecision::Skip));
+        assert_eq!(decisions[1].auto.rule, "Later import wins (run #4)");
+
+        let queued: Vec<(i64, i64)> = pending
+            .iter()
+            .map(|entry| (entry.run_id, entry.record.number))
+            .collect();
+        assert_eq!(queued, [(3, 1), (4, 2), (4, 5)]);
+    }
+}
@@ -0,0 +1,34 @@
+use sqlx::SqliteConnection;
+
+pub async fn load_setting(conn: &mut SqliteConnection, key: &str) -> Result<Option<String>, String> {
+    sqlx::query_scalar("SELECT value FROM app_settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(conn)
+        .await
+        .map_err(|err| format!("Failed to load setting \"{key}\": {err}"))
+}
+
+pub async fn store_setting(
+    conn: &mut SqliteConnection,
+    key: &str,
+    value: Option<&str>,
+) -> Result<(), String> {
+    let query = match value {
+        Some(value) => sqlx::query(
+            r#"
+            INSERT INTO app_settings (key, value) VALUES (?, ?)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value
+            "#,
+        )
+        .bind(key)
+        .bind(value),
+        None => sqlx::query("DELETE FROM app_settings WHERE key = ?").bind(key),
+    };
+
+    query
+        .execute(conn)
+        .await
+        .map_err(|err| format!("Failed to save setting \"{key}\": {err}"))?;
+
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqliteConnection};
+
+use super::history::{self, RunAction};
+use super::open_pool;
+use super::profile::{ImportProfile, ProfileField};
+
+/// Every report loader applies it.
+pub const ACTIVE_CONDITION: &str = "deleted_at IS NULL";
+
+#[derive(Debug, Clone)]
+pub struct MissingTicket {
+    pub number: i64,
+    pub title: Option<String>,
+    pub state: Option<String>,
+}
+
+pub async fn find_missing(
+    db_path: PathBuf,
+    present: Vec<i64>,
+) -> Result<Vec<MissingTicket>, String> {
+    let pool = open_pool(&db_path).await?;
+    let present: HashSet<i64> = present.into_iter().collect();
+
+    let rows = sqlx::query(&format!(
+        "SELECT number, title, state FROM new_hire_metrics WHERE {ACTIVE_CONDITION} ORDER BY number"
+    ))
+    .fetch_all(&pool)
+    .await
+    .map_err(|err| format!("Failed to load tickets: {err}"))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| MissingTicket {
+            number: row.get("number"),
+            title: row.get("title"),
+            state: row.get("state"),
+        })
+        .filter(|ticket| !present.contains(&ticket.number))
+        .collect())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Deletion {
+    deleted_at: String,
+    reason: Option<String>,
+}
+
+pub async fn soft_delete(
+    db_path: PathBuf,
+    run_id: i64,
+    numbers: Vec<i64>,
+    reason: String,
+) -> Result<usize, String> {
+    let pool = open_pool(&db_path).await?;
+    let reason = Some(reason.trim().to_owned()).filter(|reason| !reason.is_empty());
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|err| format!("Failed to start transaction: {err}"))?;
+
+    let deleted_at = chrono::Utc::now().to_rfc3339();
+    let mut deleted = 0;
+    for number in numbers {
+        let result = sqlx::query(&format!(
+            r#"
+            UPDATE new_hire_metrics SET deleted_at = ?, deleted_reason = ?
+            WHERE number = ? AND {ACTIVE_CONDITION}
+            "#
+        ))
+        .bind(&deleted_at)
+        .bind(&reason)
+        .bind(number)
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| format!("Failed to delete ticket {number}: {err}"))?;
+        if result.rows_affected() > 0 {
+            history::log_action(&mut tx, run_id, number, RunAction::Deleted, None).await?;
+            deleted += 1;
+        }
+    }
+
+    tx.commit()
+        .await
+        .map_err(|err| format!("Failed to commit transaction: {err}"))?;
+
+    Ok(deleted)
+}
+
+pub(super) async fn restore<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    run_id: i64,
+    number: i64,
+) -> Result<(), String> {
+    let row = sqlx::query(&format!(
+        "SELECT deleted_at, deleted_reason FROM {} WHERE {} = ? AND deleted_at IS NOT NULL",
+        P::TABLE,
+        P::KEY.column()
+    ))
+    .bind(number)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|err| format!("Failed to load ticket {number}: {err}"))?;
+    let Some(row) = row else {
+        return Ok(());
+    };
+
+    let deletion = serde_json::to_string(&Deletion {
+        deleted_at: row.get("deleted_at"),
+        reason: row.get("deleted_reason"),
+    })
+    .map_err(|err| format!("Failed to snapshot deletion of ticket {number}: {err}"))?;
+
+    undo_delete::<P>(conn, number).await?;
+    history::log_action(conn, run_id, number, RunAction::Restored, Some(deletion)).await
+}
+
+pub(super) async fn undo_delete<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    number: i64,
+) -> Result<(), String> {
+    sqlx::query(&format!(
+        r#"
+        UPDATE {} SET deleted_at = NULL, deleted_reason = NULL
+        WHERE {} = ? AND deleted_at IS NOT NULL
+        "#,
+        P::TABLE,
+        P::KEY.column()
+    ))
+    .bind(number)
+    .execute(conn)
+    .await
+    .map_err(|err| format!("Failed to restore ticket {number}: {err}"))?;
+
+    Ok(())
+}
+
+pub(super) async fn redo_delete<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    number: i64,
+    deletion: &str,
+) -> Result<(), String> {
+    let deletion: Deletion = serde_json::from_str(deletion)
+        .map_err(|err| format!("Failed to read deletion of ticket {number}: {err}"))?;
+
+    sqlx::query(&format!(
+        "UPDATE {} SET deleted_at = ?, deleted_reason = ? WHERE {} = ?",
+        P::TABLE,
+        P::KEY.column()
+    ))
+    .bind(deletion.deleted_at)
+    .bind(deletion.reason)
+    .bind(number)
+    .execute(conn)
+    .await
+    .map_err(|err| format!("Failed to delete ticket {number}: {err}"))?;
+
+    Ok(())
+}
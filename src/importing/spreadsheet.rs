@@ -0,0 +1,189 @@
+use std::path::{Path, PathBuf};
+
+use calamine::{open_workbook_auto, Data, ExcelDateTime, Reader};
+use csv_async::StringRecord;
+
+use super::mapping::ColumnMapping;
+use super::profile::ImportProfile;
+use super::{push_row, CsvReadOptions, CsvReadOutcome};
+
+pub const EXTENSIONS: [&str; 5] = ["xlsx", "xlsm", "xlsb", "xls", "ods"];
+
+pub fn is_spreadsheet(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            EXTENSIONS
+                .iter()
+                .any(|known| extension.eq_ignore_ascii_case(known))
+        })
+}
+
+pub async fn sheet_names(path: PathBuf) -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(move || {
+        let workbook = open_workbook_auto(&path)
+            .map_err(|err| format!("Failed to open spreadsheet: {err}"))?;
+        Ok(workbook.sheet_names())
+    })
+    .await
+    .map_err(|err| format!("Failed to read spreadsheet: {err}"))?
+}
+
+pub async fn read_headers(path: PathBuf, sheet: String) -> Result<Vec<String>, String> {
+    let (_, rows) = load_rows(path, sheet).await?;
+    Ok(rows
+        .first()
+        .map(|headers| headers.iter().map(str::to_owned).collect())
+        .unwrap_or_default())
+}
+
+/// The first non-empty row holds the headers.
+pub async fn read_mapped_sheet<P: ImportProfile>(
+    path: PathBuf,
+    sheet: String,
+    mapping: ColumnMapping<P>,
+    options: CsvReadOptions,
+) -> Result<CsvReadOutcome<P::Record>, String> {
+    let (first_line, rows) = load_rows(path, sheet).await?;
+    let mut rows = rows.into_iter();
+    let headers = rows.next().unwrap_or_default();
+    let resolved = mapping.resolve(&headers)?;
+
+    let mut outcome = CsvReadOutcome {
+        headers: headers.iter().map(str::to_owned).collect(),
+        ..CsvReadOutcome::default()
+    };
+
+    for (offset, row) in rows.enumerate() {
+        let line = first_line + 1 + offset as u64;
+        push_row::<P>(&resolved, &mut outcome, &options, line, &row)
+            .map_err(|err| format!("Spreadsheet parse error: {err}"))?;
+    }
+
+    Ok(outcome)
+}
+
+async fn load_rows(path: PathBuf, sheet: String) -> Result<(u64, Vec<StringRecord>), String> {
+    tokio::task::spawn_blocking(move || {
+        let mut workbook = open_workbook_auto(&path)
+            .map_err(|err| format!("Failed to open spreadsheet: {err}"))?;
+        let range = workbook
+            .worksheet_range(&sheet)
+            .map_err(|err| format!("Failed to read sheet \"{sheet}\": {err}"))?;
+
+        let first_line = range.start().map(|(row, _)| u64::from(row) + 1).unwrap_or(1);
+        let rows = range
+            .rows()
+            .map(|row| row.iter().map(cell_text).collect::<StringRecord>())
+            .collect();
+
+        Ok((first_line, rows))
+    })
+    .await
+    .map_err(|err| format!("Failed to read spreadsheet: {err}"))?
+}
+
+/// Matches what the CSV export would contain: whole numbers without a fraction,
+/// dates as `YYYY-MM-DD [HH:MM]` and durations as `1d 2h 30m`.
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty | Data::Error(_) => String::new(),
+        Data::String(value) | Data::DateTimeIso(value) | Data::DurationIso(value) => {
+            value.trim().to_owned()
+        }
+        Data::Int(value) => value.to_string(),
+        Data::Float(value) => float_text(*value),
+        Data::Bool(value) => if *value { "TRUE" } else { "FALSE" }.to_owned(),
+        Data::DateTime(value) => date_time_text(value),
+    }
+}
+
+fn float_text(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        (value as i64).to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn date_time_text(value: &ExcelDateTime) -> String {
+    if value.is_duration() {
+        return value
+            .as_duration()
+            .map(duration_text)
+            .unwrap_or_else(|| float_text(value.as_f64()));
+    }
+
+    match value.as_datetime() {
+        Some(timestamp) if value.as_f64().fract() == 0.0 => {
+            timestamp.format("%Y-%m-%d").to_string()
+        }
+        Some(timestamp) => timestamp.format("%Y-%m-%d %H:%M").to_string(),
+        None => float_text(value.as_f64()),
+    }
+}
+
+fn duration_text(duration: chrono::Duration) -> String {
+    let minutes = duration.num_minutes();
+    let parts = [
+        (minutes / (24 * 60), "d"),
+        (minutes / 60 % 24, "h"),
+        (minutes % 60, "m"),
+    ];
+
+    let text = parts
+        .iter()
+        .filter(|(amount, _)| *amount != 0)
+        .map(|(amount, unit)| format!("{amount}{unit}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if text.is_empty() {
+        "0m".to_owned()
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use calamine::ExcelDateTimeType;
+
+    use super::*;
+
+    #[test]
+    fn cells_read_like_the_csv_export() {
+        let date = |value| {
+            Data::DateTime(ExcelDateTime::new(
+                value,
+                ExcelDateTimeType::DateTime,
+                false,
+            ))
+        };
+        let duration = |value| {
+            Data::DateTime(ExcelDateTime::new(
+                value,
+                ExcelDateTimeType::TimeDelta,
+                false,
+            ))
+        };
+
+        assert_eq!(cell_text(&Data::String("  Laptop ".to_owned())), "Laptop");
+        assert_eq!(cell_text(&Data::Float(42.0)), "42");
+        assert_eq!(cell_text(&Data::Float(2.5)), "2.5");
+        assert_eq!(cell_text(&Data::Bool(true)), "TRUE");
+        assert_eq!(cell_text(&Data::Empty), "");
+        assert_eq!(cell_text(&date(45352.0)), "2024-03-01");
+        assert_eq!(cell_text(&date(45352.5)), "2024-03-01 12:00");
+        assert_eq!(cell_text(&duration(1.25)), "1d 6h");
+        assert_eq!(cell_text(&duration(0.0)), "0m");
+    }
+
+    #[test]
+    fn recognizes_spreadsheets_by_extension() {
+        assert!(is_spreadsheet(Path::new("tickets.XLSX")));
+        assert!(is_spreadsheet(Path::new("tickets.ods")));
+        assert!(!is_spreadsheet(Path::new("tickets.csv")));
+        assert!(!is_spreadsheet(Path::new("xlsx")));
+    }
+}
@@ -0,0 +1,268 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use sqlx::SqliteConnection;
+
+use super::settings::{load_setting, store_setting};
+use super::open_pool;
+
+/// Tried in order after RFC 3339. Formats without `%z` use the source timezone.
+pub const DEFAULT_FORMATS: [&str; 13] = [
+    "%Y-%m-%d %H:%M:%S %z",
+    "%Y-%m-%d %H:%M %z",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d",
+    "%m/%d/%Y %H:%M:%S",
+    "%m/%d/%Y %H:%M",
+    "%m/%d/%Y %I:%M %p",
+    "%m/%d/%Y",
+    "%b %d, %Y %I:%M %p",
+    "%b %d, %Y",
+    "%d.%m.%Y %H:%M",
+];
+
+const UTC_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+const TIMEZONE_KEY: &str = "timestamps.timezone";
+const FORMATS_KEY: &str = "timestamps.formats";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SourceTimezone {
+    #[default]
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl SourceTimezone {
+    /// Ambiguous local times resolve to the earlier instant; skipped ones are read
+    /// an hour later, the way a wall clock shows them.
+    fn to_utc(self, timestamp: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            SourceTimezone::Utc => Some(timestamp.and_utc()),
+            SourceTimezone::Local => Local
+                .from_local_datetime(&timestamp)
+                .earliest()
+                .or_else(|| {
+                    Local
+                        .from_local_datetime(&(timestamp + TimeDelta::hours(1)))
+                        .earliest()
+                })
+                .map(|local| local.with_timezone(&Utc)),
+            SourceTimezone::Fixed(offset) => offset
+                .from_local_datetime(&timestamp)
+                .single()
+                .map(|fixed| fixed.with_timezone(&Utc)),
+        }
+    }
+}
+
+impl fmt::Display for SourceTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceTimezone::Utc => f.write_str("UTC"),
+            SourceTimezone::Local => f.write_str("Local"),
+            SourceTimezone::Fixed(offset) => offset.fmt(f),
+        }
+    }
+}
+
+impl FromStr for SourceTimezone {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("utc") || value.is_empty() {
+            return Ok(SourceTimezone::Utc);
+        }
+        if value.eq_ignore_ascii_case("local") {
+            return Ok(SourceTimezone::Local);
+        }
+
+        value
+            .parse::<FixedOffset>()
+            .map(SourceTimezone::Fixed)
+            .map_err(|_| {
+                format!("Unknown timezone \"{value}\". Use UTC, Local or an offset like +02:00.")
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimestampSettings {
+    pub timezone: SourceTimezone,
+    pub formats: Vec<String>,
+}
+
+impl Default for TimestampSettings {
+    fn default() -> Self {
+        Self {
+            timezone: SourceTimezone::Utc,
+            formats: DEFAULT_FORMATS.map(str::to_owned).to_vec(),
+        }
+    }
+}
+
+impl TimestampSettings {
+    /// Dates without a time stay plain dates: shifting a calendar day to UTC could
+    /// change the day.
+    pub fn normalize(&self, value: &str) -> Option<String> {
+        let value = value.trim();
+        if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+            return Some(timestamp.with_timezone(&Utc).format(UTC_FORMAT).to_string());
+        }
+
+        self.formats.iter().find_map(|format| {
+            if let Ok(timestamp) = DateTime::parse_from_str(value, format) {
+                return Some(timestamp.with_timezone(&Utc).format(UTC_FORMAT).to_string());
+            }
+            if let Ok(timestamp) = NaiveDateTime::parse_from_str(value, format) {
+                return self
+                    .timezone
+                    .to_utc(timestamp)
+                    .map(|utc| utc.format(UTC_FORMAT).to_string());
+            }
+            NaiveDate::parse_from_str(value, format)
+                .ok()
+                .map(|date| date.format("%Y-%m-%d").to_string())
+        })
+    }
+}
+
+/// A date alone is taken as midnight UTC.
+pub fn parse_stored(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|timestamp| timestamp.and_utc())
+}
+
+pub async fn load_timestamp_settings(db_path: PathBuf) -> Result<TimestampSettings, String> {
+    let pool = open_pool(&db_path).await?;
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|err| format!("Failed to connect to database: {err}"))?;
+
+    stored_settings(&mut conn).await
+}
+
+pub async fn save_timestamp_settings(
+    db_path: PathBuf,
+    settings: TimestampSettings,
+) -> Result<TimestampSettings, String> {
+    let pool = open_pool(&db_path).await?;
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|err| format!("Failed to connect to database: {err}"))?;
+
+    let timezone = settings.timezone.to_string();
+    let formats = settings.formats.join("\n");
+    store_setting(&mut conn, TIMEZONE_KEY, Some(&timezone)).await?;
+    store_setting(&mut conn, FORMATS_KEY, Some(&formats)).await?;
+
+    Ok(settings)
+}
+
+pub(super) async fn stored_settings(conn: &mut SqliteConnection) -> Result<TimestampSettings, String> {
+    let defaults = TimestampSettings::default();
+    let timezone = match load_setting(&mut *conn, TIMEZONE_KEY).await? {
+        Some(timezone) => timezone.parse()?,
+        None => defaults.timezone,
+    };
+    let formats = match load_setting(&mut *conn, FORMATS_KEY).await? {
+        Some(formats) => formats.lines().map(str::to_owned).collect(),
+        None => defaults.formats,
+    };
+
+    Ok(TimestampSettings { timezone, formats })
+}
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+
+    use super::{SourceTimezone, TimestampSettings};
+
+    fn settings(timezone: SourceTimezone) -> TimestampSettings {
+        TimestampSettings {
+            timezone,
+            ..TimestampSettings::default()
+        }
+    }
+
+    #[test]
+    fn parses_timezones() {
+        assert_eq!("UTC".parse(), Ok(SourceTimezone::Utc));
+        assert_eq!("".parse(), Ok(SourceTimezone::Utc));
+        assert_eq!(" local ".parse(), Ok(SourceTimezone::Local));
+        assert_eq!(
+            "+02:00".parse(),
+            Ok(SourceTimezone::Fixed(FixedOffset::east_opt(2 * 3600).unwrap()))
+        );
+        assert!("Mars/Olympus".parse::<SourceTimezone>().is_err());
+    }
+
+    #[test]
+    fn timezone_round_trips_through_display() {
+        let offset = SourceTimezone::Fixed(FixedOffset::west_opt(5 * 3600).unwrap());
+        assert_eq!(offset.to_string().parse(), Ok(offset));
+        assert_eq!(SourceTimezone::Local.to_string().parse(), Ok(SourceTimezone::Local));
+    }
+
+    #[test]
+    fn normalizes_to_utc() {
+        let utc = settings(SourceTimezone::Utc);
+        assert_eq!(
+            utc.normalize("2024-03-05T10:15:00+02:00").as_deref(),
+            Some("2024-03-05T08:15:00Z")
+        );
+        assert_eq!(
+            utc.normalize("03/05/2024 10:15").as_deref(),
+            Some("2024-03-05T10:15:00Z")
+        );
+        assert_eq!(
+            utc.normalize("Mar 05, 2024 01:30 PM").as_deref(),
+            Some("2024-03-05T13:30:00Z")
+        );
+        assert_eq!(
+            utc.normalize("2024-03-05 10:15 -0100").as_deref(),
+            Some("2024-03-05T11:15:00Z")
+        );
+    }
+
+    #[test]
+    fn reads_naive_times_in_the_source_timezone() {
+        let fixed = settings("+02:00".parse().unwrap());
+        assert_eq!(
+            fixed.normalize("2024-03-05 01:00").as_deref(),
+            Some("2024-03-04T23:00:00Z")
+        );
+        // An explicit offset wins over the source timezone.
+        assert_eq!(
+            fixed.normalize("2024-03-05 01:00 +0000").as_deref(),
+            Some("2024-03-05T01:00:00Z")
+        );
+    }
+
+    #[test]
+    fn keeps_dates_as_calendar_days() {
+        let fixed = settings("+02:00".parse().unwrap());
+        assert_eq!(fixed.normalize("2024-03-05").as_deref(), Some("2024-03-05"));
+        assert_eq!(fixed.normalize("03/05/2024").as_deref(), Some("2024-03-05"));
+    }
+
+    #[test]
+    fn rejects_unknown_formats() {
+        let utc = settings(SourceTimezone::Utc);
+        assert_eq!(utc.normalize("next tuesday"), None);
+        assert_eq!(utc.normalize(""), None);
+    }
+}
@@ -0,0 +1,61 @@
+use sqlx::SqliteConnection;
+
+use super::profile::ImportProfile;
+
+/// Versions without a state are passed over. Later moves are timed by when the
+/// source last updated the ticket, or by the import if it does not say.
+pub(super) async fn record_transition<P: ImportProfile>(
+    conn: &mut SqliteConnection,
+    run_id: Option<i64>,
+    current: &P::Record,
+    observed_at: &str,
+) -> Result<(), String> {
+    let text = |field: Option<P::Field>| {
+        field.and_then(|field| P::value(current, field).into_text())
+    };
+    let Some(to_state) = text(P::STATE) else {
+        return Ok(());
+    };
+    let number = P::key(current);
+
+    let from_state: Option<Option<String>> = sqlx::query_scalar(
+        r#"
+        SELECT to_state FROM state_transitions
+        WHERE profile = ? AND number = ?
+        ORDER BY id DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(P::NAME)
+    .bind(number)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|err| format!("Failed to load state transitions: {err}"))?;
+
+    let transitioned_at = match &from_state {
+        None => text(Some(P::CREATED_AT)),
+        Some(from_state) if from_state.as_deref() == Some(to_state.as_str()) => return Ok(()),
+        Some(_) => text(P::UPDATED_AT),
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO state_transitions
+            (profile, number, run_id, from_state, to_state, transitioned_at, site, assignee_name)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(P::NAME)
+    .bind(number)
+    .bind(run_id)
+    .bind(from_state.flatten())
+    .bind(&to_state)
+    .bind(transitioned_at.as_deref().unwrap_or(observed_at))
+    .bind(text(P::SITE))
+    .bind(text(P::ASSIGNEE))
+    .execute(conn)
+    .await
+    .map_err(|err| format!("Failed to record state transition: {err}"))?;
+
+    Ok(())
+}
@@ -0,0 +1,402 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use futures::StreamExt;
+use tokio::fs;
+
+use super::csv_format::CsvFormat;
+use super::policy::PresetDecisions;
+use super::settings::{load_setting, store_setting};
+use super::timestamps::load_timestamp_settings;
+use super::{
+    export_rejected_rows, import_mapped_csv, open_pool, prepare_import, CsvReadOptions,
+    ImportCancel, ImportEvent, NewHireProfile, RejectedRow,
+};
+
+pub const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+pub const ARCHIVE_DIR: &str = "archive";
+pub const FAILED_DIR: &str = "failed";
+
+/// Files modified more recently than this may still be being written.
+const SETTLE_TIME: Duration = Duration::from_secs(30);
+
+const FOLDER_KEY: &str = "watch.folder";
+
+pub async fn load_watch_folder(db_path: PathBuf) -> Result<Option<PathBuf>, String> {
+    let pool = open_pool(&db_path).await?;
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|err| format!("Failed to connect to database: {err}"))?;
+
+    Ok(load_setting(&mut conn, FOLDER_KEY).await?.map(PathBuf::from))
+}
+
+pub async fn save_watch_folder(
+    db_path: PathBuf,
+    folder: Option<PathBuf>,
+) -> Result<Option<PathBuf>, String> {
+    let pool = open_pool(&db_path).await?;
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|err| format!("Failed to connect to database: {err}"))?;
+
+    let value = folder.as_ref().map(|folder| folder.to_string_lossy().into_owned());
+    store_setting(&mut conn, FOLDER_KEY, value.as_deref()).await?;
+
+    Ok(folder)
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchedFile {
+    pub file_name: String,
+    pub result: Result<WatchedImport, String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum WatchedImport {
+    Imported {
+        run_id: i64,
+        inserted: usize,
+        updated: usize,
+        skipped: usize,
+        duplicates: usize,
+        rejected: usize,
+        warning: Option<String>,
+    },
+    AlreadyImported { run_id: i64 },
+}
+
+impl WatchedFile {
+    pub fn describe(&self) -> String {
+        match &self.result {
+            Ok(WatchedImport::Imported {
+                run_id,
+                inserted,
+                updated,
+                skipped,
+                duplicates,
+                rejected,
+                warning,
+            }) => {
+                let mut line = format!(
+                    "{}: run #{run_id}, {inserted} inserted, {updated} updated, {skipped} skipped, \
+                     {duplicates} to review, {rejected} rejected",
+                    self.file_name
+                );
+                if let Some(warning) = warning {
+                    line.push_str(&format!(" ({warning})"));
+                }
+                line
+            }
+            Ok(WatchedImport::AlreadyImported { run_id }) => {
+                format!("{}: already imported by run #{run_id}", self.file_name)
+            }
+            Err(err) => format!("{}: {err}", self.file_name),
+        }
+    }
+
+    pub fn duplicates(&self) -> usize {
+        match &self.result {
+            Ok(WatchedImport::Imported { duplicates, .. }) => *duplicates,
+            _ => 0,
+        }
+    }
+}
+
+/// Duplicates are left queued for review.
+pub async fn import_new_files(
+    db_path: PathBuf,
+    folder: PathBuf,
+) -> Result<Vec<WatchedFile>, String> {
+    let mut files = Vec::new();
+    for (path, _) in settled_files(&folder).await? {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+
+        let result = import_file(&db_path, &folder, &path).await;
+        let target = if result.is_ok() { ARCHIVE_DIR } else { FAILED_DIR };
+        let result = match move_into(&folder, target, &path).await {
+            Ok(_) => result,
+            Err(err) => Err(match result {
+                Ok(_) => format!("imported, but {err}"),
+                Err(import_err) => format!("{import_err}; {err}"),
+            }),
+        };
+
+        files.push(WatchedFile { file_name, result });
+    }
+
+    Ok(files)
+}
+
+async fn settled_files(folder: &Path) -> Result<Vec<(PathBuf, SystemTime)>, String> {
+    let mut entries = fs::read_dir(folder)
+        .await
+        .map_err(|err| format!("Failed to read {}: {err}", folder.display()))?;
+
+    let now = SystemTime::now();
+    let mut files = Vec::new();
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|err| format!("Failed to read {}: {err}", folder.display()))?
+    {
+        let path = entry.path();
+        let is_csv = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+        let is_hidden = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with('.') || name.starts_with("~$"));
+        if !is_csv || is_hidden {
+            continue;
+        }
+
+        let Ok(metadata) = entry.metadata().await else {
+            continue;
+        };
+        let Ok(modified) = metadata.modified() else {
+            continue;
+        };
+        let settled = now
+            .duration_since(modified)
+            .is_ok_and(|age| age >= SETTLE_TIME);
+        if metadata.is_file() && settled {
+            files.push((path, modified));
+        }
+    }
+
+    files.sort_by_key(|(_, modified)| *modified);
+    Ok(files)
+}
+
+async fn import_file(db_path: &Path, folder: &Path, path: &Path) -> Result<WatchedImport, String> {
+    let prepared = prepare_import::<NewHireProfile>(
+        db_path.to_path_buf(),
+        path.to_path_buf(),
+        None,
+        CsvFormat::default(),
+    )
+    .await?;
+    if let Some(run) = &prepared.previous_run {
+        return Ok(WatchedImport::AlreadyImported { run_id: run.id });
+    }
+
+    let mapping = prepared
+        .matching_profile()
+        .cloned()
+        .ok_or_else(|| "no column mapping profile matches its headers".to_owned())?;
+    let options = CsvReadOptions {
+        lenient: true,
+        timestamps: load_timestamp_settings(db_path.to_path_buf()).await?,
+        ..CsvReadOptions::default()
+    };
+    let mut events = Box::pin(import_mapped_csv(
+        db_path.to_path_buf(),
+        path.to_path_buf(),
+        mapping,
+        options,
+        prepared.source,
+        PresetDecisions::new(),
+        ImportCancel::default(),
+    ));
+    let (mut inserted, mut updated, mut skipped, mut duplicates) = (0, 0, 0, 0);
+    let (mut headers, mut rejected) = (Vec::new(), Vec::new());
+    let mut finished = Err("import ended unexpectedly".to_owned());
+    while let Some(event) = events.next().await {
+        match event {
+            ImportEvent::Started { headers: row, .. } => headers = row,
+            ImportEvent::Progress(progress) => {
+                inserted += progress.inserted;
+                updated += progress.updated;
+                skipped += progress.skipped;
+                duplicates += progress.duplicates.len();
+                rejected.extend(progress.rejected);
+            }
+            ImportEvent::Finished(result) => finished = result,
+            ImportEvent::Cancelled(_) => finished = Err("import was cancelled".to_owned()),
+        }
+    }
+
+    let run_id = finished?;
+    let rejected_count = rejected.len();
+    let warning = if rejected_count > 0 {
+        export_rejected(folder, path, headers, rejected)
+            .await
+            .err()
+            .map(|err| format!("rejected rows were not exported: {err}"))
+    } else {
+        None
+    };
+
+    Ok(WatchedImport::Imported {
+        run_id,
+        inserted,
+        updated,
+        skipped,
+        duplicates,
+        rejected: rejected_count,
+        warning,
+    })
+}
+
+async fn export_rejected(
+    folder: &Path,
+    path: &Path,
+    headers: Vec<String>,
+    rejected: Vec<RejectedRow>,
+) -> Result<PathBuf, String> {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let target_dir = folder.join(ARCHIVE_DIR);
+    fs::create_dir_all(&target_dir)
+        .await
+        .map_err(|err| format!("Failed to create {}: {err}", target_dir.display()))?;
+    let target = free_path(&target_dir, &format!("{name}.rejected.csv")).await;
+    export_rejected_rows(target, headers, rejected).await
+}
+
+async fn move_into(folder: &Path, subfolder: &str, path: &Path) -> Result<PathBuf, String> {
+    let target_dir = folder.join(subfolder);
+    fs::create_dir_all(&target_dir)
+        .await
+        .map_err(|err| format!("failed to create {}: {err}", target_dir.display()))?;
+
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let target = free_path(&target_dir, &name).await;
+
+    fs::rename(path, &target)
+        .await
+        .map_err(|err| format!("failed to move it to {subfolder}: {err}"))?;
+
+    Ok(target)
+}
+
+async fn free_path(dir: &Path, name: &str) -> PathBuf {
+    let taken = async |path: &Path| fs::try_exists(path).await.unwrap_or(false);
+    let target = dir.join(name);
+    if !taken(&target).await {
+        return target;
+    }
+
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let mut target = dir.join(format!("{stamp}-{name}"));
+    let mut counter = 1;
+    while taken(&target).await {
+        counter += 1;
+        target = dir.join(format!("{stamp}-{counter}-{name}"));
+    }
+    target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importing::mapping::RecordField;
+    use crate::importing::migrations::prepare_database;
+
+    fn settled_file(folder: &Path, name: &str, content: &str) {
+        let path = folder.join(name);
+        std::fs::write(&path, content).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(120))
+            .unwrap();
+    }
+
+    fn export(rows: &[i64]) -> String {
+        let headers: Vec<&str> = RecordField::ALL
+            .iter()
+            .map(|field| field.default_header())
+            .collect();
+        let mut csv = headers.join(",");
+        let empty = ",".repeat(headers.len() - 1);
+        for number in rows {
+            csv.push_str(&format!("\n{number}{empty}"));
+        }
+        csv.push_str("\n99,too,short\n");
+        csv
+    }
+
+    fn names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn archives_imported_files_and_skips_unsettled_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("watch.sqlite");
+        prepare_database(db_path.clone()).await.unwrap();
+        let folder = dir.path().join("inbox");
+        std::fs::create_dir(&folder).unwrap();
+
+        settled_file(&folder, "daily.csv", &export(&[1, 2]));
+        settled_file(&folder, "other.csv", "Foo,Bar\n1,2\n");
+        settled_file(&folder, "notes.txt", "not an export");
+        std::fs::write(folder.join("writing.csv"), export(&[3])).unwrap();
+
+        let files = import_new_files(db_path.clone(), folder.clone())
+            .await
+            .unwrap();
+        let mut results: Vec<(&str, bool)> = files
+            .iter()
+            .map(|file| (file.file_name.as_str(), file.result.is_ok()))
+            .collect();
+        results.sort();
+        assert_eq!(results, [("daily.csv", true), ("other.csv", false)]);
+        assert!(matches!(
+            files
+                .iter()
+                .find(|file| file.file_name == "daily.csv")
+                .unwrap()
+                .result,
+            Ok(WatchedImport::Imported {
+                inserted: 2,
+                rejected: 1,
+                ..
+            })
+        ));
+
+        assert_eq!(
+            names(&folder),
+            [ARCHIVE_DIR, FAILED_DIR, "notes.txt", "writing.csv"]
+        );
+        assert_eq!(
+            names(&folder.join(ARCHIVE_DIR)),
+            ["daily.csv", "daily.rejected.csv"]
+        );
+        assert_eq!(names(&folder.join(FAILED_DIR)), ["other.csv"]);
+
+        // A later export of the same name keeps the earlier files.
+        settled_file(&folder, "daily.csv", &export(&[1, 2, 4]));
+        import_new_files(db_path, folder.clone()).await.unwrap();
+        let archived = names(&folder.join(ARCHIVE_DIR));
+        assert_eq!(archived.len(), 4);
+        assert_eq!(
+            archived
+                .iter()
+                .filter(|name| name.ends_with("daily.rejected.csv"))
+                .count(),
+            2
+        );
+    }
+}
@@ -14,6 +14,7 @@ use lucide_icons::LUCIDE_FONT_BYTES;
 fn main() -> iced::Result{
     iced::application(App::new, App::update, App::view)
         .theme(App::theme)
+        .subscription(App::subscription)
         .settings(Settings {
             fonts: vec![LUCIDE_FONT_BYTES.into()],
             ..Default::default()
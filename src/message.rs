@@ -1,18 +1,146 @@
+use crate::importing::incidents::IncidentProfile;
+use crate::importing::profile::ImportProfile;
+use crate::importing::{ImportKind, NewHireProfile};
+
 #[derive(Debug, Clone)]
 pub enum Message {
     ToggleSidebar,
     Navigate(crate::screens::Page),
-    Noop,
     StartImport,
-    ImportPrepared(Result<Vec<crate::importing::NewHireRecord>, String>),
-    ProcessedRecord(Result<crate::importing::ImportStep, String>),
-    DecideDuplicate { number: i64, overwrite: bool },
-    DecideAll { overwrite: bool },
-    DecisionApplied(Result<crate::importing::ImportStep, String>),
+    StartIncidentImport,
+    NewHires(ProfileMessage<NewHireProfile>),
+    Incidents(ProfileMessage<IncidentProfile>),
+    DatabasePrepared(Result<(), String>),
+    WatchFolderLoaded(Result<Option<std::path::PathBuf>, String>),
+    WatchTick,
+    WatchFolderScanned(Result<Vec<crate::importing::watch::WatchedFile>, String>),
+    ChooseWatchFolder,
+    ClearWatchFolder,
+    WatchFolderSaved(Result<Option<std::path::PathBuf>, String>),
+    FilesHovered,
+    FilesHoverLeft,
+    FileDropped(std::path::PathBuf),
+    RemoveQueuedFile(usize),
+    ClearFinishedFiles,
+    StartApiSync,
+    ApiFetched(Result<crate::importing::api::ApiFetch, String>),
+    SyncCursorSaved(Result<Option<String>, String>),
+    TogglePreview(bool),
+    ToggleLenient(bool),
+    ToggleFullSnapshot(bool),
+    MissingTicketsLoaded(Result<Vec<crate::importing::snapshot::MissingTicket>, String>),
+    DeleteReasonChanged(String),
+    SoftDeleteMissing,
+    DismissMissing,
+    MissingDeleted(Result<usize, String>),
+    CsvEncodingSelected(crate::importing::csv_format::EncodingChoice),
+    CsvDelimiterSelected(crate::importing::csv_format::DelimiterChoice),
+    ExportRejected,
+    RejectedExported(Result<std::path::PathBuf, String>),
+    CancelImport,
+    HistoryLoaded(Result<Vec<crate::importing::history::ImportRun>, String>),
+    SelectImportRun(i64),
+    ImportRunRecordsLoaded(
+        Result<(i64, Vec<crate::importing::history::RunRecord>), String>,
+    ),
+    RequestRollback(i64),
+    CancelRollback,
+    ConfirmRollback,
+    RollbackFinished(Result<crate::importing::history::RollbackOutcome, String>),
+    TimelineNumberChanged(String),
+    ShowTimeline,
+    ShowTicketTimeline(i64),
+    TimelineLoaded(
+        Result<(i64, Vec<crate::importing::history::RecordVersion>), String>,
+    ),
+    ApiSettingsLoaded(Result<crate::importing::api::ApiSettings, String>),
+    ApiBaseUrlChanged(String),
+    ApiTokenChanged(String),
+    SaveApiSettings,
+    ApiSettingsSaved(Result<crate::importing::api::ApiSettings, String>),
+    ResetApiSync,
+    TimestampSettingsLoaded(Result<crate::importing::timestamps::TimestampSettings, String>),
+    TimestampTimezoneChanged(String),
+    TimestampFormatsChanged(String),
+    SaveTimestampSettings,
+    TimestampSettingsSaved(Result<crate::importing::timestamps::TimestampSettings, String>),
     ReportSeriesLoaded(Result<Vec<(f32, f32)>, String>),
     ReportStateCountsLoaded(Result<Vec<(String, f32)>, String>),
     ReportEmployeeTypeLoaded(Result<Vec<(String, f32)>, String>),
-    ReportHeatmapLoaded(Result<(Vec<String>, Vec<String>, Vec<Vec<f32>>), String>),
+    ReportHeatmapLoaded(Result<crate::data::heatmap_site_employee::SiteEmployeeGrid, String>),
     ReportRadarLoaded(Result<Vec<(String, f32)>, String>),
     ReportBreachRateLoaded(Result<(f32, f32), String>),
+    ReportOpenTicketsLoaded(Result<Vec<(String, f32)>, String>),
+    ReportTicketFlowLoaded(Result<Vec<(String, f32, f32)>, String>),
+    ReportBacklogLoaded(Result<Vec<(String, f32)>, String>),
+    ReportStateTimeLoaded(
+        crate::data::state_durations::StateTimeGroup,
+        Result<crate::data::state_durations::StateGrid, String>,
+    ),
+    ReportCustomKeysLoaded(Result<Vec<String>, String>),
+    ReportCustomFieldLoaded(Result<Vec<(String, f32)>, String>),
+    CustomGroupByChanged(String),
+    CustomFilterKeyChanged(String),
+    CustomFilterValueChanged(String),
+    ApplyCustomFilter,
+    ClearCustomFilter,
+}
+
+#[derive(Debug, Clone)]
+pub enum ProfileMessage<P: ImportProfile> {
+    HeadersLoaded(Result<crate::importing::PreparedImport<P>, String>),
+    SheetSelected(String),
+    CancelSheet,
+    ConfirmReimport,
+    CancelReimport,
+    MappingProfileSelected(String),
+    MappingHeaderSelected(P::Field, crate::importing::mapping::HeaderChoice),
+    MappingNameChanged(String),
+    ConfirmMapping,
+    CancelMapping,
+    MappingSaved(Result<crate::importing::mapping::ColumnMapping<P>, String>),
+    Prepared(Result<crate::importing::CsvReadOutcome<P::Record>, String>),
+    PreviewLoaded(Result<crate::importing::ImportPreview<P>, String>),
+    CommitPreview,
+    DiscardPreview,
+    Progressed(crate::importing::ImportEvent<P>),
+    PendingDuplicatesLoaded(Result<Vec<crate::importing::DuplicateEntry<P>>, String>),
+    DecideDuplicate { number: i64, overwrite: bool },
+    DecideAll { overwrite: bool },
+    OpenMerge(i64),
+    MergeFieldChosen { field: P::Field, take_new: bool },
+    ApplyMerge,
+    CancelMerge,
+    DecisionApplied(Result<crate::importing::DecisionOutcome, String>),
+    PoliciesLoaded(Result<Vec<crate::importing::policy::DuplicatePolicy<P::Field>>, String>),
+    PolicyDraftKindSelected(crate::importing::policy::PolicyKind),
+    PolicyDraftFieldAdded(P::Field),
+    PolicyDraftCleared,
+    AddPolicy,
+    TogglePolicy(usize, bool),
+    MovePolicyUp(usize),
+    RemovePolicy(usize),
+    PoliciesSaved(Result<Vec<crate::importing::policy::DuplicatePolicy<P::Field>>, String>),
+}
+
+pub trait ScreenProfile: ImportProfile {
+    const KIND: ImportKind;
+
+    fn message(message: ProfileMessage<Self>) -> Message;
+}
+
+impl ScreenProfile for NewHireProfile {
+    const KIND: ImportKind = ImportKind::NewHires;
+
+    fn message(message: ProfileMessage<Self>) -> Message {
+        Message::NewHires(message)
+    }
+}
+
+impl ScreenProfile for IncidentProfile {
+    const KIND: ImportKind = ImportKind::Incidents;
+
+    fn message(message: ProfileMessage<Self>) -> Message {
+        Message::Incidents(message)
+    }
 }
@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use iced::Color;
+
+use crate::charts::{BarChart, BarPoint, BarSeries, LineChartConfig};
+use crate::data::backlog_by_category;
+
+pub struct BacklogCategoryBarReport;
+
+impl BacklogCategoryBarReport {
+    pub fn title() -> &'static str {
+        "Backlog by Category"
+    }
+
+    pub fn subtitle() -> &'static str {
+        "Top 10 categories by unresolved incidents"
+    }
+
+    pub async fn load(db_path: PathBuf) -> Result<Vec<(String, f32)>, String> {
+        backlog_by_category::load_series(db_path).await
+    }
+
+    pub fn chart(points: &[(String, f32)]) -> BarChart {
+        let values = points
+            .iter()
+            .map(|(label, value)| BarPoint {
+                label: label.clone(),
+                value: *value,
+            })
+            .collect();
+
+        BarChart::new(BarSeries {
+            name: "Backlog".to_string(),
+            color: Color::from_rgb(0.62, 0.45, 0.92),
+            values,
+        })
+        .with_config(LineChartConfig {
+            padding: 40.0,
+            grid_lines: 4,
+        })
+    }
+}
@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+use iced::Color;
+
+use crate::charts::{BarChart, BarPoint, BarSeries, LineChartConfig};
+use crate::data::custom_fields::{self, CustomFilter};
+
+pub struct CustomFieldBarReport;
+
+impl CustomFieldBarReport {
+    pub fn title() -> &'static str {
+        "Requests by Custom Field"
+    }
+
+    pub fn subtitle() -> &'static str {
+        "Top 10 values of a column kept from the imported files"
+    }
+
+    pub async fn load(
+        db_path: PathBuf,
+        group_by: String,
+        filter: Option<CustomFilter>,
+    ) -> Result<Vec<(String, f32)>, String> {
+        custom_fields::load_series(db_path, group_by, filter).await
+    }
+
+    pub fn chart(points: &[(String, f32)]) -> BarChart {
+        let values = points
+            .iter()
+            .map(|(label, value)| BarPoint {
+                label: label.clone(),
+                value: *value,
+            })
+            .collect();
+
+        BarChart::new(BarSeries {
+            name: "Requests".to_string(),
+            color: Color::from_rgb(0.55, 0.45, 0.9),
+            values,
+        })
+        .with_config(LineChartConfig {
+            padding: 40.0,
+            grid_lines: 4,
+        })
+    }
+}
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use crate::charts::{HeatmapChart, HeatmapGrid, LineChartConfig};
-use crate::data::heatmap_site_employee;
+use crate::data::heatmap_site_employee::{self, SiteEmployeeGrid};
 
 pub struct SiteEmployeeHeatmapReport;
 
@@ -14,9 +14,7 @@ impl SiteEmployeeHeatmapReport {
         "Counts per site and employee type"
     }
 
-    pub async fn load(
-        db_path: PathBuf,
-    ) -> Result<(Vec<String>, Vec<String>, Vec<Vec<f32>>), String> {
+    pub async fn load(db_path: PathBuf) -> Result<SiteEmployeeGrid, String> {
         heatmap_site_employee::load_grid(db_path).await
     }
 
@@ -1,6 +1,11 @@
+pub mod backlog_category_bar;
+pub mod custom_field_bar;
 pub mod employee_type_pie;
 pub mod heatmap_site_employee;
 pub mod it_lead_time;
+pub mod open_tickets_bar;
 pub mod radar_lead_time;
 pub mod sla_breach_circle;
 pub mod state_counts_bar;
+pub mod state_time_heatmap;
+pub mod ticket_flow_line;
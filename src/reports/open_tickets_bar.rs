@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use iced::Color;
+
+use crate::charts::{BarChart, BarPoint, BarSeries, LineChartConfig};
+use crate::data::open_by_assignee;
+
+pub struct OpenTicketsBarReport;
+
+impl OpenTicketsBarReport {
+    pub fn title() -> &'static str {
+        "Open Tickets by Assignee"
+    }
+
+    pub fn subtitle() -> &'static str {
+        "Top 10 agents by unresolved incidents"
+    }
+
+    pub async fn load(db_path: PathBuf) -> Result<Vec<(String, f32)>, String> {
+        open_by_assignee::load_series(db_path).await
+    }
+
+    pub fn chart(points: &[(String, f32)]) -> BarChart {
+        let values = points
+            .iter()
+            .map(|(label, value)| BarPoint {
+                label: label.clone(),
+                value: *value,
+            })
+            .collect();
+
+        BarChart::new(BarSeries {
+            name: "Open tickets".to_string(),
+            color: Color::from_rgb(0.96, 0.62, 0.35),
+            values,
+        })
+        .with_config(LineChartConfig {
+            padding: 40.0,
+            grid_lines: 4,
+        })
+    }
+}
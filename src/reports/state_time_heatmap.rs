@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+
+use crate::charts::{HeatmapChart, HeatmapGrid, LineChartConfig};
+use crate::data::state_durations::{self, StateGrid, StateTimeGroup};
+
+pub struct StateTimeHeatmapReport;
+
+impl StateTimeHeatmapReport {
+    pub fn title(group: StateTimeGroup) -> &'static str {
+        match group {
+            StateTimeGroup::Site => "Time in State by Site",
+            StateTimeGroup::Assignee => "Time in State by Assignee",
+        }
+    }
+
+    pub fn subtitle() -> &'static str {
+        "Average hours per state, inferred from successive imports"
+    }
+
+    pub async fn load(
+        db_path: PathBuf,
+        group: StateTimeGroup,
+    ) -> Result<StateGrid, String> {
+        state_durations::load_grid(db_path, group).await
+    }
+
+    pub fn chart(grid: HeatmapGrid) -> HeatmapChart {
+        HeatmapChart::new(grid).with_config(LineChartConfig {
+            padding: 50.0,
+            grid_lines: 4,
+        })
+    }
+}
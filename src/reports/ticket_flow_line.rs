@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+use iced::Color;
+
+use crate::charts::{LineChart, LineChartConfig, LineSeries};
+use crate::data::daily_ticket_flow;
+
+pub struct TicketFlowLineReport;
+
+impl TicketFlowLineReport {
+    pub fn title() -> &'static str {
+        "Tickets Created vs Closed"
+    }
+
+    pub fn subtitle() -> &'static str {
+        "Incidents per day over the last 30 days with activity"
+    }
+
+    pub async fn load(db_path: PathBuf) -> Result<Vec<(String, f32, f32)>, String> {
+        daily_ticket_flow::load_series(db_path).await
+    }
+
+    pub fn chart(points: &[(String, f32, f32)]) -> LineChart {
+        let created = points
+            .iter()
+            .enumerate()
+            .map(|(index, (_, created, _))| (index as f32, *created))
+            .collect();
+        let closed = points
+            .iter()
+            .enumerate()
+            .map(|(index, (_, _, closed))| (index as f32, *closed))
+            .collect();
+
+        let series = vec![
+            LineSeries::new("Created", Color::from_rgb(0.35, 0.62, 0.96), created),
+            LineSeries::new("Closed", Color::from_rgb(0.35, 0.82, 0.55), closed),
+        ];
+
+        LineChart::new(series).with_config(LineChartConfig {
+            padding: 40.0,
+            grid_lines: 5,
+        })
+    }
+}
@@ -0,0 +1,277 @@
+use iced::widget::{button, column, container, row, scrollable, text, text_input, Space};
+use iced::{Background, Element, Length, Padding};
+
+use crate::importing::history::{ImportRun, RecordVersion, RunRecord, TIMELINE_FIELDS};
+use crate::message::Message;
+use crate::screens::import::secondary_button_style;
+use crate::theme::{
+    accent_button_style, DRAWER_BG, DRAWER_TEXT_ACTIVE, DRAWER_TEXT_INACTIVE,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct HistoryState {
+    pub runs: Vec<ImportRun>,
+    pub loading: bool,
+    pub error: Option<String>,
+    pub selected_run: Option<(i64, Vec<RunRecord>)>,
+    pub confirm_rollback: Option<i64>,
+    pub message: Option<String>,
+    pub timeline_input: String,
+    pub timeline: Option<(i64, Vec<RecordVersion>)>,
+    pub timeline_error: Option<String>,
+}
+
+pub fn view<'a>(history: &HistoryState) -> Element<'a, Message> {
+    let mut content = column![
+        text("Import History").size(22),
+        text("Every import run with the file it came from and what it changed.")
+            .size(14)
+            .style(|_| text::Style {
+                color: Some(DRAWER_TEXT_INACTIVE),
+            }),
+    ]
+    .spacing(12);
+
+    if let Some(message) = &history.message {
+        content = content.push(text(message.clone()).size(14));
+    }
+
+    if history.loading {
+        content = content.push(text("Loading import history...").size(14));
+    } else if let Some(message) = &history.error {
+        content = content.push(text(message.clone()).size(14));
+    } else if history.runs.is_empty() {
+        content = content.push(text("No imports yet.").size(14));
+    } else {
+        content = content.push(runs_table(
+            &history.runs,
+            history.selected_run.as_ref().map(|(id, _)| *id),
+        ));
+    }
+
+    if let Some((run_id, records)) = &history.selected_run {
+        let run = history.runs.iter().find(|run| run.id == *run_id);
+        content = content.push(run_actions(*run_id, run, history.confirm_rollback));
+        content = content.push(run_records(*run_id, records));
+    }
+
+    content = content.push(timeline_view(history));
+
+    let panel = container(scrollable(content.padding(Padding::new(0.0).right(16.0))))
+        .padding(24)
+        .width(Length::Fill)
+        .max_width(1100)
+        .style(|_| container::background(Background::Color(DRAWER_BG)));
+
+    container(panel)
+        .padding(24)
+        .center_x(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
+
+fn runs_table<'a>(runs: &[ImportRun], selected: Option<i64>) -> Element<'a, Message> {
+    let header = row![
+        text("Run").size(14).width(Length::Fixed(60.0)),
+        text("File").size(14).width(Length::Fill),
+        text("Imported At").size(14).width(Length::Fixed(150.0)),
+        text("Inserted").size(14).width(Length::Fixed(80.0)),
+        text("Updated").size(14).width(Length::Fixed(80.0)),
+        text("Skipped").size(14).width(Length::Fixed(80.0)),
+        Space::new().width(Length::Fixed(90.0)),
+    ]
+    .spacing(16);
+
+    let rows = runs.iter().map(|run| {
+        let label = if selected == Some(run.id) {
+            "Showing"
+        } else {
+            "Details"
+        };
+        let mut file = format!("{} ({})", run.file_name, run.short_hash());
+        if run.is_incident_export() {
+            file = format!("Incidents: {file}");
+        }
+        if run.is_rolled_back() {
+            file.push_str(" - rolled back");
+        } else if run.is_cancelled() {
+            file.push_str(" - cancelled, partial");
+        }
+
+        row![
+            text(format!("#{}", run.id)).size(14).width(Length::Fixed(60.0)),
+            text(file).size(14).width(Length::Fill),
+            text(run.imported_at_display())
+                .size(14)
+                .width(Length::Fixed(150.0)),
+            text(run.inserted).size(14).width(Length::Fixed(80.0)),
+            text(run.updated).size(14).width(Length::Fixed(80.0)),
+            text(run.skipped).size(14).width(Length::Fixed(80.0)),
+            button(text(label).size(13))
+                .style(secondary_button_style)
+                .on_press(Message::SelectImportRun(run.id))
+                .width(Length::Fixed(90.0)),
+        ]
+        .spacing(16)
+        .into()
+    });
+
+    let table = column![header]
+        .push(column(rows).spacing(6))
+        .spacing(8)
+        .padding(Padding::new(0.0).right(16.0));
+
+    scrollable(table).height(Length::Fixed(320.0)).into()
+}
+
+fn run_actions<'a>(
+    run_id: i64,
+    run: Option<&ImportRun>,
+    confirm_rollback: Option<i64>,
+) -> Element<'a, Message> {
+    if let Some(run) = run.filter(|run| run.is_rolled_back()) {
+        let when = run.rolled_back_at_display().unwrap_or_default();
+        return text(format!("Run #{run_id} was rolled back ({when})."))
+            .size(14)
+            .into();
+    }
+
+    if confirm_rollback == Some(run_id) {
+        return row![
+            text(format!(
+                "Remove the tickets run #{run_id} inserted and restore the ones it overwrote?"
+            ))
+            .size(14),
+            button("Confirm Roll Back")
+                .style(accent_button_style)
+                .on_press(Message::ConfirmRollback),
+            button("Cancel")
+                .style(secondary_button_style)
+                .on_press(Message::CancelRollback),
+        ]
+        .spacing(12)
+        .align_y(iced::Alignment::Center)
+        .into();
+    }
+
+    button("Roll Back Run")
+        .style(secondary_button_style)
+        .on_press(Message::RequestRollback(run_id))
+        .into()
+}
+
+fn run_records<'a>(run_id: i64, records: &[RunRecord]) -> Element<'a, Message> {
+    if records.is_empty() {
+        return text(format!("Run #{run_id} did not insert or update any tickets."))
+            .size(14)
+            .into();
+    }
+
+    let rows = records.iter().map(|record| {
+        row![
+            button(text(record.number).size(14))
+                .style(secondary_button_style)
+                .on_press(Message::ShowTicketTimeline(record.number))
+                .width(Length::Fixed(100.0)),
+            text(record.action.label()).size(14),
+        ]
+        .spacing(16)
+        .into()
+    });
+
+    column![
+        text(format!("Run #{run_id}: {} affected ticket(s)", records.len())).size(16),
+        scrollable(column(rows).spacing(4).padding(Padding::new(0.0).right(16.0)))
+            .height(Length::Fixed(240.0)),
+    ]
+    .spacing(12)
+    .into()
+}
+
+fn timeline_view<'a>(history: &HistoryState) -> Element<'a, Message> {
+    let search = row![
+        text_input("Ticket number", &history.timeline_input)
+            .on_input(Message::TimelineNumberChanged)
+            .on_submit(Message::ShowTimeline)
+            .size(14)
+            .width(Length::Fixed(160.0)),
+        button(text("Show Timeline").size(14))
+            .style(secondary_button_style)
+            .on_press(Message::ShowTimeline),
+    ]
+    .spacing(12)
+    .align_y(iced::Alignment::Center);
+
+    let mut content = column![text("Ticket Timeline").size(16), search].spacing(12);
+
+    if let Some(message) = &history.timeline_error {
+        content = content.push(text(message.clone()).size(14));
+    } else if let Some((number, versions)) = &history.timeline {
+        content = content.push(timeline_table(*number, versions));
+    }
+
+    content.into()
+}
+
+fn timeline_table<'a>(number: i64, versions: &[RecordVersion]) -> Element<'a, Message> {
+    if versions.is_empty() {
+        return text(format!("No history for ticket {number}."))
+            .size(14)
+            .into();
+    }
+
+    let mut header = row![
+        text("Recorded").size(14).width(Length::Fixed(150.0)),
+        text("Run").size(14).width(Length::Fill),
+    ]
+    .spacing(16);
+    for field in TIMELINE_FIELDS {
+        header = header.push(
+            text(field.default_header())
+                .size(14)
+                .width(Length::Fixed(170.0)),
+        );
+    }
+
+    let rows = versions.iter().enumerate().map(|(index, version)| {
+        let previous = index.checked_sub(1).map(|index| &versions[index]);
+        let run = match (version.run_id, &version.file_name) {
+            (Some(run_id), Some(file_name)) => format!("#{run_id} {file_name}"),
+            (Some(run_id), None) => format!("#{run_id}"),
+            (None, _) => "Before history was kept".to_owned(),
+        };
+
+        let mut line = row![
+            text(version.recorded_at_display())
+                .size(14)
+                .width(Length::Fixed(150.0)),
+            text(run).size(14).width(Length::Fill),
+        ]
+        .spacing(16);
+        for field in TIMELINE_FIELDS {
+            let value = version.value(field);
+            let changed = previous.is_none_or(|previous| previous.value(field) != value);
+            let color = if changed {
+                DRAWER_TEXT_ACTIVE
+            } else {
+                DRAWER_TEXT_INACTIVE
+            };
+            line = line.push(
+                text(value.to_string())
+                    .size(14)
+                    .width(Length::Fixed(170.0))
+                    .style(move |_| text::Style { color: Some(color) }),
+            );
+        }
+        line.into()
+    });
+
+    column![
+        text(format!("Ticket {number}: {} version(s)", versions.len())).size(14),
+        header,
+        scrollable(column(rows).spacing(4).padding(Padding::new(0.0).right(16.0)))
+            .height(Length::Fixed(240.0)),
+    ]
+    .spacing(8)
+    .into()
+}
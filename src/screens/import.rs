@@ -1,15 +1,111 @@
-use iced::widget::{button, column, container, row, scrollable, text, Space};
+use iced::widget::{
+    button, checkbox, column, container, pick_list, progress_bar, radio, row, scrollable, text,
+    text_input, Space,
+};
 use iced::{Background, Element, Length, Padding};
-use lucide_icons::iced::icon_file_plus;
+use std::collections::VecDeque;
+use std::path::Path;
+use lucide_icons::iced::{icon_file_plus, icon_refresh_cw};
 
-use crate::importing::{ImportState, ImportStatus};
-use crate::message::Message;
+use crate::importing::csv_format::{CsvFormat, DelimiterChoice, DetectedFormat, EncodingChoice};
+use crate::importing::incidents::IncidentProfile;
+use crate::importing::mapping::{mapped_fields, HeaderChoice, MappingEditor};
+use crate::importing::profile::{ImportProfile, ProfileField};
+use crate::importing::queue::{ImportQueue, QueuedStatus};
+use crate::importing::snapshot::MissingTicket;
+use crate::importing::{
+    AutoDecision, DuplicateDecision, DuplicateEntry, ImportPreview, ImportState, ImportStatus,
+    MergeSelection, NewHireProfile, PendingDuplicate, PreparedImport,
+};
+use crate::message::{Message, ProfileMessage, ScreenProfile};
 use crate::theme::{accent_button_style, DRAWER_BG, DRAWER_ITEM_BG, DRAWER_TEXT_ACTIVE, DRAWER_TEXT_INACTIVE};
 
-pub fn view<'a>(import_state: &ImportState) -> Element<'a, Message> {
+#[derive(Debug, Clone)]
+pub struct ProfileImport<P: ImportProfile> {
+    pub reimport_pending: Option<PreparedImport<P>>,
+    pub sheet_pending: Option<PreparedImport<P>>,
+    pub mapping_editor: Option<MappingEditor<P>>,
+    pub preview: Option<ImportPreview<P>>,
+    pub pending_duplicates: VecDeque<DuplicateEntry<P>>,
+    pub decision_queue: VecDeque<(i64, P::Record, DuplicateDecision<P::Field>)>,
+    pub merge_selection: Option<MergeSelection<P::Field>>,
+}
+
+impl<P: ImportProfile> Default for ProfileImport<P> {
+    fn default() -> Self {
+        Self {
+            reimport_pending: None,
+            sheet_pending: None,
+            mapping_editor: None,
+            preview: None,
+            pending_duplicates: VecDeque::new(),
+            decision_queue: VecDeque::new(),
+            merge_selection: None,
+        }
+    }
+}
+
+impl<P: ImportProfile> ProfileImport<P> {
+    pub fn reset_setup(&mut self) {
+        self.reimport_pending = None;
+        self.sheet_pending = None;
+        self.mapping_editor = None;
+        self.preview = None;
+    }
+
+    pub fn waits_on(&self, run_id: i64) -> bool {
+        self.pending_duplicates
+            .iter()
+            .any(|entry| entry.run_id == run_id)
+            || self
+                .decision_queue
+                .iter()
+                .any(|(decision_run, _, _)| *decision_run == run_id)
+    }
+}
+
+pub struct ImportView<'a> {
+    pub import_state: &'a ImportState,
+    pub new_hires: &'a ProfileImport<NewHireProfile>,
+    pub incidents: &'a ProfileImport<IncidentProfile>,
+    pub preview_enabled: bool,
+    pub lenient_import: bool,
+    pub full_snapshot: bool,
+    pub missing_tickets: &'a [MissingTicket],
+    pub delete_reason: &'a str,
+    pub csv_format: CsvFormat,
+    pub detected_format: Option<DetectedFormat>,
+    pub files_hovered: bool,
+    pub import_queue: &'a ImportQueue,
+    pub watch_folder: Option<&'a Path>,
+    pub watch_log: &'a [String],
+}
+
+pub fn view<'a>(screen: ImportView<'_>) -> Element<'a, Message> {
+    let ImportView {
+        import_state,
+        new_hires,
+        incidents,
+        preview_enabled,
+        lenient_import,
+        full_snapshot,
+        missing_tickets,
+        delete_reason,
+        csv_format,
+        detected_format,
+        files_hovered,
+        import_queue,
+        watch_folder,
+        watch_log,
+    } = screen;
+
     let status_text = match import_state.status {
         ImportStatus::Idle => "No import in progress.".to_owned(),
-        ImportStatus::Loading => "Reading CSV...".to_owned(),
+        ImportStatus::Loading => "Reading file...".to_owned(),
+        ImportStatus::AwaitingConfirmation => "This file has been imported before.".to_owned(),
+        ImportStatus::AwaitingSheet => "Choose a sheet to continue.".to_owned(),
+        ImportStatus::AwaitingMapping => "Map the file's columns to continue.".to_owned(),
+        ImportStatus::AwaitingCommit => "Preview ready. Commit or discard the import.".to_owned(),
         ImportStatus::Importing => "Importing records...".to_owned(),
         ImportStatus::AwaitingDecision => "Duplicate found. Choose overwrite or skip.".to_owned(),
         ImportStatus::Done => "Import complete.".to_owned(),
@@ -24,114 +120,77 @@ pub fn view<'a>(import_state: &ImportState) -> Element<'a, Message> {
         import_state.skipped
     );
 
-    let duplicates_table = if import_state.pending_duplicates.is_empty() {
-        None
-    } else {
-        let header = container(
-            row![
-                text("Number").size(14),
-                text("Title").size(14),
-                text("Created At").size(14),
-                text("Changes").size(14).width(Length::Fill),
-                Space::new().width(Length::Fixed(16.0)),
-            ]
-            .spacing(16),
-        )
-        .padding(Padding::new(0.0).right(16.0));
+    let import_button = button(row![icon_file_plus().size(16), text("Upload CSV or Excel")].spacing(8))
+        .style(accent_button_style)
+        .on_press(Message::StartImport);
 
-        let rows = import_state.pending_duplicates.iter().map(|pending| {
-            let title = pending.title.clone().unwrap_or_else(|| "Unknown Title".to_owned());
-            let created_at = pending
-                .created_at
-                .clone()
-                .unwrap_or_else(|| "Unknown timestamp".to_owned());
+    let incident_button = button(text("Import Incident Export"))
+        .style(secondary_button_style)
+        .on_press(Message::StartIncidentImport);
 
-            let changes = if pending.changes.is_empty() {
-                "no changes".to_owned()
-            } else {
-                pending.changes.join(", ")
-            };
+    let sync_button = button(row![icon_refresh_cw().size(16), text("Sync from SolarWinds")].spacing(8))
+        .style(secondary_button_style)
+        .on_press_maybe(
+            (import_state.status.is_ready() && !import_queue.is_busy())
+                .then_some(Message::StartApiSync),
+        );
 
-            container(
-                row![
-                    text(pending.number).size(14),
-                    text(title).size(14).width(Length::Fill),
-                    text(created_at).size(14),
-                    text(changes).size(13).width(Length::Fill),
-                    row![
-                        button("Overwrite")
-                            .style(accent_button_style)
-                            .on_press(Message::DecideDuplicate {
-                                number: pending.number,
-                                overwrite: true
-                            }),
-                        button("Skip")
-                            .style(secondary_button_style)
-                            .on_press(Message::DecideDuplicate {
-                                number: pending.number,
-                                overwrite: false
-                            })
-                    ]
-                    .spacing(8),
-                    Space::new().width(Length::Fixed(16.0)),
-                ]
-                .spacing(16),
-            )
-            .padding(Padding::new(0.0).right(16.0))
-            .into()
-        });
+    let preview_toggle = checkbox(preview_enabled)
+        .label("Preview before importing (reads the whole file first)")
+        .on_toggle(Message::TogglePreview)
+        .text_size(14);
 
-        let table = column![header, Space::new().height(Length::Fixed(8.0))]
-            .push(column(rows).spacing(6))
-            .spacing(8);
+    let lenient_toggle = checkbox(lenient_import)
+        .label("Skip invalid rows")
+        .on_toggle(Message::ToggleLenient)
+        .text_size(14);
 
-        Some(scrollable(table).height(Length::Fixed(260.0)))
-    };
-
-    let import_button = button(row![icon_file_plus().size(16), text("Upload CSV")].spacing(8))
-        .style(accent_button_style)
-        .on_press(Message::StartImport);
+    let snapshot_toggle = checkbox(full_snapshot)
+        .label("Full snapshot (list missing tickets)")
+        .on_toggle(Message::ToggleFullSnapshot)
+        .text_size(14);
 
     let info_panel = container(
         column![
             text("New Hire Reports").size(22),
-            text("Import the SolarWinds Service Desk CSV export to update metrics.")
+            text("Import the SolarWinds Service Desk export (CSV or Excel) to update metrics.")
                 .size(14)
                 .style(|_| text::Style {
                     color: Some(DRAWER_TEXT_INACTIVE),
                 }),
             Space::new().height(Length::Fixed(8.0)),
-            import_button,
+            row![
+                import_button,
+                incident_button,
+                sync_button,
+                preview_toggle,
+                lenient_toggle,
+                snapshot_toggle
+            ]
+            .spacing(16)
+            .align_y(iced::Alignment::Center),
+            format_view(csv_format, detected_format),
+            drop_hint(files_hovered, import_queue.waiting()),
+            queue_view(import_queue),
+            watch_folder
+                .map(|folder| watch_view(folder, watch_log))
+                .unwrap_or_else(|| column![].into()),
             Space::new().height(Length::Fixed(8.0)),
             text(status_text).size(14),
             text(counts).size(14),
+            progress_view(import_state),
             import_state
                 .message
                 .as_ref()
                 .map(|message| text(message.clone()).size(14))
                 .unwrap_or_else(|| text("")),
-            duplicates_table
-                .map(|table| {
-                    column![
-                        text(format!(
-                            "{} duplicate record(s)",
-                            import_state.pending_duplicates.len()
-                        ))
-                        .size(16),
-                        row![
-                            button("Overwrite All")
-                                .style(accent_button_style)
-                                .on_press(Message::DecideAll { overwrite: true }),
-                            button("Skip All")
-                                .style(secondary_button_style)
-                                .on_press(Message::DecideAll { overwrite: false })
-                        ]
-                        .spacing(12),
-                        table
-                    ]
-                    .spacing(12)
-                })
-                .unwrap_or_else(|| column![].into())
+            setup_view(new_hires),
+            setup_view(incidents),
+            rejected_view(import_state),
+            missing_view(missing_tickets, delete_reason, import_state.rejected_rows.len()),
+            auto_decisions_view("Resolved by policy", &import_state.auto_decisions),
+            review_view(new_hires),
+            review_view(incidents),
         ]
         .spacing(12),
     )
@@ -147,7 +206,703 @@ pub fn view<'a>(import_state: &ImportState) -> Element<'a, Message> {
         .into()
 }
 
-fn secondary_button_style(_theme: &iced::Theme, status: button::Status) -> button::Style {
+fn setup_view<'a, P: ScreenProfile>(profile: &ProfileImport<P>) -> Element<'a, Message> {
+    let mut setup = column![].spacing(12);
+    if profile.reimport_pending.is_some() {
+        setup = setup.push(reimport_view::<P>());
+    }
+    if let Some(prepared) = &profile.sheet_pending {
+        setup = setup.push(sheet_view::<P>(&prepared.sheets));
+    }
+    if let Some(editor) = &profile.mapping_editor {
+        setup = setup.push(mapping_view(editor));
+    }
+    if let Some(preview) = &profile.preview {
+        setup = setup.push(preview_view(preview));
+    }
+    setup.into()
+}
+
+fn review_view<'a, P: ScreenProfile>(profile: &ProfileImport<P>) -> Element<'a, Message> {
+    if profile.pending_duplicates.is_empty() {
+        return column![].into();
+    }
+
+    let header = container(
+        row![
+            text("Number").size(14),
+            text("Title").size(14),
+            text("Created At").size(14),
+            text("Changes").size(14).width(Length::Fill),
+            Space::new().width(Length::Fixed(16.0)),
+        ]
+        .spacing(16),
+    )
+    .padding(Padding::new(0.0).right(16.0));
+
+    let rows = profile.pending_duplicates.iter().map(|entry| {
+        let pending = &entry.summary;
+        let number = pending.number;
+        let title = pending.title.clone().unwrap_or_else(|| "Unknown Title".to_owned());
+        let created_at = pending
+            .created_at
+            .clone()
+            .unwrap_or_else(|| "Unknown timestamp".to_owned());
+
+        let changes = if pending.changes.is_empty() {
+            "no changes".to_owned()
+        } else {
+            pending.changes_summary()
+        };
+
+        container(
+            row![
+                text(number).size(14),
+                text(title).size(14).width(Length::Fill),
+                text(created_at).size(14),
+                text(changes).size(13).width(Length::Fill),
+                row![
+                    button("Overwrite")
+                        .style(accent_button_style)
+                        .on_press(P::message(ProfileMessage::DecideDuplicate {
+                            number,
+                            overwrite: true
+                        })),
+                    button("Skip")
+                        .style(secondary_button_style)
+                        .on_press(P::message(ProfileMessage::DecideDuplicate {
+                            number,
+                            overwrite: false
+                        })),
+                    button("Merge")
+                        .style(secondary_button_style)
+                        .on_press(P::message(ProfileMessage::OpenMerge(number)))
+                ]
+                .spacing(8),
+                Space::new().width(Length::Fixed(16.0)),
+            ]
+            .spacing(16),
+        )
+        .padding(Padding::new(0.0).right(16.0))
+        .into()
+    });
+
+    let table = column![header, Space::new().height(Length::Fixed(8.0))]
+        .push(column(rows).spacing(6))
+        .spacing(8);
+
+    column![
+        text(format!(
+            "{}: {} duplicate record(s)",
+            P::KIND.label(),
+            profile.pending_duplicates.len()
+        ))
+        .size(16),
+        row![
+            button("Overwrite All")
+                .style(accent_button_style)
+                .on_press(P::message(ProfileMessage::DecideAll { overwrite: true })),
+            button("Skip All")
+                .style(secondary_button_style)
+                .on_press(P::message(ProfileMessage::DecideAll { overwrite: false }))
+        ]
+        .spacing(12),
+        profile
+            .merge_selection
+            .as_ref()
+            .map(merge_view::<P>)
+            .unwrap_or_else(|| column![].into()),
+        scrollable(table).height(Length::Fixed(260.0))
+    ]
+    .spacing(12)
+    .into()
+}
+
+/// Rows listed per preview section; the rest are summarised as a count.
+const PREVIEW_ROW_LIMIT: usize = 200;
+
+fn preview_view<'a, P: ScreenProfile>(preview: &ImportPreview<P>) -> Element<'a, Message> {
+    let actions = row![
+        button("Commit Import")
+            .style(accent_button_style)
+            .on_press(P::message(ProfileMessage::CommitPreview)),
+        button("Discard")
+            .style(secondary_button_style)
+            .on_press(P::message(ProfileMessage::DiscardPreview)),
+    ]
+    .spacing(12);
+
+    let sections = column![
+        auto_decisions_view("Resolved by policy", &preview.auto_resolved),
+        preview_section("Would change", &preview.changed),
+        preview_section("New records", &preview.inserts),
+    ]
+    .spacing(16);
+
+    column![
+        text(format!("{} Preview", P::KIND.label())).size(16),
+        text(format!(
+            "Insert: {} | Unchanged: {} | Resolved by policy: {} | Changed: {}",
+            preview.inserts.len(),
+            preview.unchanged,
+            preview.auto_resolved.len(),
+            preview.changed.len()
+        ))
+        .size(14),
+        actions,
+        scrollable(sections).height(Length::Fixed(320.0)),
+    ]
+    .spacing(12)
+    .into()
+}
+
+fn preview_section<'a, F: ProfileField>(
+    title: &str,
+    rows: &[PendingDuplicate<F>],
+) -> Element<'a, Message> {
+    if rows.is_empty() {
+        return column![].into();
+    }
+
+    let lines = rows.iter().take(PREVIEW_ROW_LIMIT).map(|pending| {
+        let title = pending.title.clone().unwrap_or_else(|| "Unknown Title".to_owned());
+        let detail = if pending.changes.is_empty() {
+            pending
+                .created_at
+                .clone()
+                .unwrap_or_else(|| "Unknown timestamp".to_owned())
+        } else {
+            pending.changes_summary()
+        };
+
+        row![
+            text(pending.number).size(14).width(Length::Fixed(80.0)),
+            text(title).size(14).width(Length::FillPortion(2)),
+            text(detail).size(13).width(Length::FillPortion(3)),
+        ]
+        .spacing(16)
+        .into()
+    });
+
+    let mut section = column![text(format!("{title} ({})", rows.len())).size(14)]
+        .push(column(lines).spacing(6))
+        .spacing(8)
+        .padding(Padding::new(0.0).right(16.0));
+
+    if rows.len() > PREVIEW_ROW_LIMIT {
+        section = section.push(
+            text(format!("...and {} more", rows.len() - PREVIEW_ROW_LIMIT))
+                .size(13)
+                .style(|_| text::Style {
+                    color: Some(DRAWER_TEXT_INACTIVE),
+                }),
+        );
+    }
+
+    section.into()
+}
+
+fn auto_decisions_view<'a>(title: &str, decisions: &[AutoDecision]) -> Element<'a, Message> {
+    if decisions.is_empty() {
+        return column![].into();
+    }
+
+    let lines = decisions.iter().take(PREVIEW_ROW_LIMIT).map(|decision| {
+        let title = decision
+            .title
+            .clone()
+            .unwrap_or_else(|| "Unknown Title".to_owned());
+
+        row![
+            text(decision.number).size(14).width(Length::Fixed(80.0)),
+            text(title).size(14).width(Length::FillPortion(2)),
+            text(decision.outcome.clone())
+                .size(13)
+                .width(Length::FillPortion(2)),
+            text(decision.rule.clone())
+                .size(13)
+                .width(Length::FillPortion(3)),
+        ]
+        .spacing(16)
+        .into()
+    });
+
+    let mut list = column(lines)
+        .spacing(6)
+        .padding(Padding::new(0.0).right(16.0));
+    if decisions.len() > PREVIEW_ROW_LIMIT {
+        list = list.push(
+            text(format!("...and {} more", decisions.len() - PREVIEW_ROW_LIMIT))
+                .size(13)
+                .style(|_| text::Style {
+                    color: Some(DRAWER_TEXT_INACTIVE),
+                }),
+        );
+    }
+
+    column![
+        text(format!("{title} ({})", decisions.len())).size(16),
+        row![
+            text("Number").size(14).width(Length::Fixed(80.0)),
+            text("Title").size(14).width(Length::FillPortion(2)),
+            text("Outcome").size(14).width(Length::FillPortion(2)),
+            text("Rule").size(14).width(Length::FillPortion(3)),
+        ]
+        .spacing(16)
+        .padding(Padding::new(0.0).right(16.0)),
+        scrollable(list).height(Length::Fixed(200.0)),
+    ]
+    .spacing(8)
+    .into()
+}
+
+fn merge_view<'a, P: ScreenProfile>(selection: &MergeSelection<P::Field>) -> Element<'a, Message> {
+    let rows = selection.changes.iter().map(|change| {
+        let field = change.field;
+        let take_new = Some(selection.takes_new(field));
+
+        row![
+            text(field.header()).size(14).width(Length::Fixed(220.0)),
+            radio(
+                format!("Keep: {}", change.old),
+                false,
+                take_new,
+                move |take_new| P::message(ProfileMessage::MergeFieldChosen { field, take_new }),
+            )
+            .size(14)
+            .text_size(14)
+            .width(Length::FillPortion(1)),
+            radio(
+                format!("Take: {}", change.new),
+                true,
+                take_new,
+                move |take_new| P::message(ProfileMessage::MergeFieldChosen { field, take_new }),
+            )
+            .size(14)
+            .text_size(14)
+            .width(Length::FillPortion(1)),
+        ]
+        .spacing(16)
+        .into()
+    });
+
+    container(
+        column![
+            text(format!("Merge ticket {}", selection.number)).size(16),
+            column(rows).spacing(8),
+            row![
+                button("Apply Merge")
+                    .style(accent_button_style)
+                    .on_press(P::message(ProfileMessage::ApplyMerge)),
+                button("Cancel")
+                    .style(secondary_button_style)
+                    .on_press(P::message(ProfileMessage::CancelMerge)),
+            ]
+            .spacing(12),
+        ]
+        .spacing(12),
+    )
+    .padding(16)
+    .style(|_| container::background(Background::Color(DRAWER_ITEM_BG)))
+    .into()
+}
+
+fn reimport_view<'a, P: ScreenProfile>() -> Element<'a, Message> {
+    row![
+        button("Import Anyway")
+            .style(accent_button_style)
+            .on_press(P::message(ProfileMessage::ConfirmReimport)),
+        button("Cancel")
+            .style(secondary_button_style)
+            .on_press(P::message(ProfileMessage::CancelReimport)),
+    ]
+    .spacing(12)
+    .into()
+}
+
+fn format_view<'a>(format: CsvFormat, detected: Option<DetectedFormat>) -> Element<'a, Message> {
+    let detected = match detected {
+        Some(detected) => format!("Last CSV read as {}.", detected.describe()),
+        None => "CSV encoding and delimiter are detected automatically.".to_owned(),
+    };
+
+    row![
+        pick_list(
+            EncodingChoice::all(),
+            Some(EncodingChoice(format.encoding)),
+            Message::CsvEncodingSelected,
+        )
+        .width(Length::Fixed(200.0)),
+        pick_list(
+            DelimiterChoice::all(),
+            Some(DelimiterChoice(format.delimiter)),
+            Message::CsvDelimiterSelected,
+        )
+        .width(Length::Fixed(200.0)),
+        text(detected).size(13).style(|_| text::Style {
+            color: Some(DRAWER_TEXT_INACTIVE),
+        }),
+    ]
+    .spacing(12)
+    .align_y(iced::Alignment::Center)
+    .into()
+}
+
+fn drop_hint<'a>(files_hovered: bool, waiting: usize) -> Element<'a, Message> {
+    let hint = if files_hovered {
+        "Drop to import.".to_owned()
+    } else if waiting > 0 {
+        format!("{waiting} file(s) waiting to be imported.")
+    } else {
+        "Or drop CSV and Excel files anywhere on the window.".to_owned()
+    };
+
+    container(text(hint).size(14).style(move |_| text::Style {
+        color: Some(if files_hovered {
+            DRAWER_TEXT_ACTIVE
+        } else {
+            DRAWER_TEXT_INACTIVE
+        }),
+    }))
+    .padding(8)
+    .width(Length::Fill)
+    .style(move |_| {
+        if files_hovered {
+            container::background(Background::Color(DRAWER_ITEM_BG))
+        } else {
+            container::Style::default()
+        }
+    })
+    .into()
+}
+
+fn queue_view<'a>(queue: &ImportQueue) -> Element<'a, Message> {
+    if queue.files.is_empty() {
+        return column![].into();
+    }
+
+    let rows = queue.files.iter().enumerate().map(|(index, file)| {
+        let color = match file.status {
+            QueuedStatus::Importing => DRAWER_TEXT_ACTIVE,
+            _ => DRAWER_TEXT_INACTIVE,
+        };
+
+        row![
+            text(file.file_name.clone()).size(14).width(Length::Fill),
+            text(file.kind.label())
+                .size(13)
+                .width(Length::FillPortion(1)),
+            text(file.status.describe())
+                .size(13)
+                .width(Length::FillPortion(2))
+                .style(move |_| text::Style { color: Some(color) }),
+            button(text("Remove").size(13))
+                .style(secondary_button_style)
+                .on_press_maybe(
+                    (file.status == QueuedStatus::Waiting)
+                        .then_some(Message::RemoveQueuedFile(index)),
+                ),
+        ]
+        .spacing(16)
+        .align_y(iced::Alignment::Center)
+        .into()
+    });
+
+    let has_finished = queue.files.iter().any(|file| file.status.is_finished());
+
+    container(
+        column![
+            row![
+                text("Import Queue").size(16).width(Length::Fill),
+                button(text("Clear Finished").size(13))
+                    .style(secondary_button_style)
+                    .on_press_maybe(has_finished.then_some(Message::ClearFinishedFiles)),
+            ]
+            .align_y(iced::Alignment::Center),
+            text("When a ticket appears in more than one file, the newer file wins.")
+                .size(13)
+                .style(|_| text::Style {
+                    color: Some(DRAWER_TEXT_INACTIVE),
+                }),
+            scrollable(column(rows).spacing(6).padding(Padding::new(0.0).right(16.0)))
+                .height(Length::Shrink),
+        ]
+        .spacing(8),
+    )
+    .padding(16)
+    .width(Length::Fill)
+    .style(|_| container::background(Background::Color(DRAWER_ITEM_BG)))
+    .into()
+}
+
+fn watch_view<'a>(folder: &Path, log: &[String]) -> Element<'a, Message> {
+    let lines = log.iter().rev().map(|line| {
+        text(line.clone())
+            .size(13)
+            .style(|_| text::Style {
+                color: Some(DRAWER_TEXT_INACTIVE),
+            })
+            .into()
+    });
+
+    column![
+        text(format!("Watching {}", folder.display())).size(14),
+        column(lines).spacing(4),
+    ]
+    .spacing(6)
+    .into()
+}
+
+fn sheet_view<'a, P: ScreenProfile>(sheets: &[String]) -> Element<'a, Message> {
+    row![
+        text("Sheet").size(14),
+        pick_list(sheets.to_vec(), None::<String>, |sheet| {
+            P::message(ProfileMessage::SheetSelected(sheet))
+        })
+        .placeholder("Choose a sheet")
+        .width(Length::Fixed(260.0)),
+        button("Cancel")
+            .style(secondary_button_style)
+            .on_press(P::message(ProfileMessage::CancelSheet)),
+    ]
+    .spacing(12)
+    .align_y(iced::Alignment::Center)
+    .into()
+}
+
+fn rejected_view<'a>(import_state: &ImportState) -> Element<'a, Message> {
+    let rejected = &import_state.rejected_rows;
+    if rejected.is_empty() {
+        return column![].into();
+    }
+
+    let lines = rejected.iter().take(PREVIEW_ROW_LIMIT).map(|row| {
+        row![
+            text(format!("Line {}", row.line))
+                .size(14)
+                .width(Length::Fixed(80.0)),
+            text(row.column.clone().unwrap_or_else(|| "-".to_owned()))
+                .size(14)
+                .width(Length::FillPortion(2)),
+            text(row.reason.clone())
+                .size(13)
+                .width(Length::FillPortion(3)),
+        ]
+        .spacing(16)
+        .into()
+    });
+
+    let mut list = column(lines)
+        .spacing(6)
+        .padding(Padding::new(0.0).right(16.0));
+    if rejected.len() > PREVIEW_ROW_LIMIT {
+        list = list.push(
+            text(format!("...and {} more", rejected.len() - PREVIEW_ROW_LIMIT))
+                .size(13)
+                .style(|_| text::Style {
+                    color: Some(DRAWER_TEXT_INACTIVE),
+                }),
+        );
+    }
+
+    column![
+        row![
+            text(format!("{} rejected row(s)", rejected.len())).size(16),
+            button("Export Rejected Rows")
+                .style(secondary_button_style)
+                .on_press(Message::ExportRejected),
+        ]
+        .spacing(16)
+        .align_y(iced::Alignment::Center),
+        scrollable(list).height(Length::Fixed(200.0)),
+    ]
+    .spacing(12)
+    .into()
+}
+
+fn progress_view<'a>(import_state: &ImportState) -> Element<'a, Message> {
+    if import_state.status != ImportStatus::Importing || import_state.total == 0 {
+        return column![].into();
+    }
+
+    let mut details = format!(
+        "{} of {} records",
+        import_state.processed, import_state.total
+    );
+    if let Some(rate) = import_state.throughput() {
+        details.push_str(&format!(" | {rate:.0} records/s"));
+    }
+    if let Some(eta) = import_state.eta() {
+        details.push_str(&format!(" | about {} left", format_eta(eta.as_secs())));
+    }
+
+    column![
+        progress_bar(
+            0.0..=import_state.total as f32,
+            import_state.processed as f32
+        )
+        .girth(10),
+        row![
+            text(details).size(13).width(Length::Fill),
+            button("Cancel Import")
+                .style(secondary_button_style)
+                .on_press(Message::CancelImport),
+        ]
+        .spacing(16)
+        .align_y(iced::Alignment::Center),
+    ]
+    .spacing(8)
+    .into()
+}
+
+fn format_eta(seconds: u64) -> String {
+    match seconds {
+        0..60 => format!("{seconds}s"),
+        60..3600 => format!("{}m {}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+fn missing_view<'a>(
+    missing: &[MissingTicket],
+    delete_reason: &str,
+    rejected: usize,
+) -> Element<'a, Message> {
+    if missing.is_empty() {
+        return column![].into();
+    }
+
+    let lines = missing.iter().take(PREVIEW_ROW_LIMIT).map(|ticket| {
+        row![
+            text(ticket.number).size(14).width(Length::Fixed(80.0)),
+            text(ticket.title.clone().unwrap_or_else(|| "Unknown Title".to_owned()))
+                .size(14)
+                .width(Length::FillPortion(3)),
+            text(ticket.state.clone().unwrap_or_else(|| "-".to_owned()))
+                .size(13)
+                .width(Length::FillPortion(1)),
+        ]
+        .spacing(16)
+        .into()
+    });
+
+    let mut list = column(lines)
+        .spacing(6)
+        .padding(Padding::new(0.0).right(16.0));
+    if missing.len() > PREVIEW_ROW_LIMIT {
+        list = list.push(
+            text(format!("...and {} more", missing.len() - PREVIEW_ROW_LIMIT))
+                .size(13)
+                .style(|_| text::Style {
+                    color: Some(DRAWER_TEXT_INACTIVE),
+                }),
+        );
+    }
+
+    let warning = if rejected > 0 {
+        text(format!(
+            "{rejected} row(s) were rejected; their tickets may be listed here."
+        ))
+        .size(13)
+    } else {
+        text("")
+    };
+
+    column![
+        text(format!("{} ticket(s) missing from this export", missing.len())).size(16),
+        warning,
+        row![
+            text_input("Reason (e.g. merged in SolarWinds)", delete_reason)
+                .on_input(Message::DeleteReasonChanged)
+                .size(14)
+                .width(Length::Fixed(320.0)),
+            button("Remove from Reports")
+                .style(accent_button_style)
+                .on_press(Message::SoftDeleteMissing),
+            button("Keep")
+                .style(secondary_button_style)
+                .on_press(Message::DismissMissing),
+        ]
+        .spacing(12)
+        .align_y(iced::Alignment::Center),
+        scrollable(list).height(Length::Fixed(200.0)),
+    ]
+    .spacing(12)
+    .into()
+}
+
+fn mapping_view<'a, P: ScreenProfile>(editor: &MappingEditor<P>) -> Element<'a, Message> {
+    let choices = editor.header_choices();
+
+    let profile_row = row![
+        text("Profile").size(14).width(Length::Fixed(260.0)),
+        pick_list(editor.profile_names(), editor.selected_profile.clone(), |name| {
+            P::message(ProfileMessage::MappingProfileSelected(name))
+        })
+        .text_size(14),
+    ]
+    .spacing(16);
+
+    let unknown = editor.unknown_headers();
+    let unknown_text = if unknown.is_empty() {
+        text("")
+    } else {
+        text(format!("Unknown columns: {}", unknown.join(", ")))
+            .size(13)
+            .style(|_| text::Style {
+                color: Some(DRAWER_TEXT_INACTIVE),
+            })
+    };
+
+    let field_rows = mapped_fields::<P>().map(|field| {
+        let label = if field == P::KEY {
+            format!("{} *", field.header())
+        } else {
+            field.header().to_owned()
+        };
+        let selected = HeaderChoice(editor.mapping.header(field).map(str::to_owned));
+
+        row![
+            text(label).size(14).width(Length::Fixed(260.0)),
+            pick_list(choices.clone(), Some(selected), move |choice| {
+                P::message(ProfileMessage::MappingHeaderSelected(field, choice))
+            })
+            .text_size(14),
+        ]
+        .spacing(16)
+        .into()
+    });
+
+    let actions = row![
+        text_input("Save as profile (optional)", &editor.profile_name)
+            .on_input(|name| P::message(ProfileMessage::MappingNameChanged(name)))
+            .size(14)
+            .width(Length::Fixed(260.0)),
+        button("Import")
+            .style(accent_button_style)
+            .on_press_maybe(
+                editor
+                    .can_import()
+                    .then(|| P::message(ProfileMessage::ConfirmMapping)),
+            ),
+        button("Cancel")
+            .style(secondary_button_style)
+            .on_press(P::message(ProfileMessage::CancelMapping)),
+    ]
+    .spacing(12);
+
+    column![
+        text(format!("{} Column Mapping", P::KIND.label())).size(16),
+        profile_row,
+        unknown_text,
+        scrollable(column(field_rows).spacing(6)).height(Length::Fixed(260.0)),
+        actions,
+    ]
+    .spacing(12)
+    .into()
+}
+
+pub fn secondary_button_style(_theme: &iced::Theme, status: button::Status) -> button::Style {
     let mut background = DRAWER_ITEM_BG;
 
     if matches!(status, button::Status::Hovered) {
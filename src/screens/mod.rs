@@ -1,20 +1,26 @@
+pub mod history;
 pub mod home;
 pub mod import;
 pub mod reports;
+pub mod settings;
 
 #[derive(Debug, Hash, Eq, PartialEq, Clone, Copy)]
 pub enum Page {
     Import,
+    History,
     Home,
     Reports,
+    Settings,
 }
 
 impl Page {
     pub fn label(&self) -> &'static str {
         match self {
             Page::Import => "Import",
+            Page::History => "History",
             Page::Home => "Home",
             Page::Reports => "Reports",
+            Page::Settings => "Settings",
         }
     }
 }
@@ -1,15 +1,115 @@
 use iced::widget::canvas::Canvas;
-use iced::widget::{column, container, text};
-use iced::{Element, Fill};
+use iced::widget::{button, column, container, pick_list, row, text, text_input};
+use iced::{Alignment, Element, Fill};
 
+use crate::data::custom_fields::CustomFilter;
+use crate::data::heatmap_site_employee::SiteEmployeeGrid;
+use crate::data::state_durations::{StateGrid, StateTimeGroup};
 use crate::message::Message;
+use crate::reports::backlog_category_bar::BacklogCategoryBarReport;
+use crate::reports::custom_field_bar::CustomFieldBarReport;
 use crate::reports::employee_type_pie::EmployeeTypePieReport;
 use crate::reports::heatmap_site_employee::SiteEmployeeHeatmapReport;
 use crate::reports::it_lead_time::ItLeadTimeReport;
+use crate::reports::open_tickets_bar::OpenTicketsBarReport;
 use crate::reports::radar_lead_time::LeadTimeRadarReport;
 use crate::reports::sla_breach_circle::SlaBreachCircleReport;
 use crate::reports::state_counts_bar::StateCountsBarReport;
+use crate::reports::state_time_heatmap::StateTimeHeatmapReport;
+use crate::reports::ticket_flow_line::TicketFlowLineReport;
 
+#[derive(Debug, Clone, Default)]
+pub struct TicketLoadState {
+    pub open_tickets: Vec<(String, f32)>,
+    pub open_loading: bool,
+    pub open_error: Option<String>,
+    pub ticket_flow: Vec<(String, f32, f32)>,
+    pub flow_loading: bool,
+    pub flow_error: Option<String>,
+    pub backlog: Vec<(String, f32)>,
+    pub backlog_loading: bool,
+    pub backlog_error: Option<String>,
+}
+
+impl TicketLoadState {
+    pub fn start_loading(&mut self) {
+        self.open_loading = true;
+        self.open_error = None;
+        self.flow_loading = true;
+        self.flow_error = None;
+        self.backlog_loading = true;
+        self.backlog_error = None;
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StateTimeState {
+    pub by_site: Option<StateGrid>,
+    pub site_loading: bool,
+    pub site_error: Option<String>,
+    pub by_assignee: Option<StateGrid>,
+    pub assignee_loading: bool,
+    pub assignee_error: Option<String>,
+}
+
+impl StateTimeState {
+    pub fn start_loading(&mut self) {
+        self.site_loading = true;
+        self.site_error = None;
+        self.assignee_loading = true;
+        self.assignee_error = None;
+    }
+
+    pub fn finish_loading(
+        &mut self,
+        group: StateTimeGroup,
+        result: Result<StateGrid, String>,
+    ) {
+        let (grid, loading, error) = match group {
+            StateTimeGroup::Site => (&mut self.by_site, &mut self.site_loading, &mut self.site_error),
+            StateTimeGroup::Assignee => (
+                &mut self.by_assignee,
+                &mut self.assignee_loading,
+                &mut self.assignee_error,
+            ),
+        };
+        *loading = false;
+        match result {
+            Ok(loaded) => {
+                *grid = Some(loaded);
+                *error = None;
+            }
+            Err(err) => {
+                *grid = None;
+                *error = Some(err);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CustomFieldState {
+    pub keys: Vec<String>,
+    pub group_by: Option<String>,
+    pub filter_key: Option<String>,
+    pub filter_value: String,
+    pub points: Vec<(String, f32)>,
+    pub loading: bool,
+    pub error: Option<String>,
+}
+
+impl CustomFieldState {
+    pub fn filter(&self) -> Option<CustomFilter> {
+        let key = self.filter_key.clone()?;
+        let value = self.filter_value.trim();
+        (!value.is_empty()).then(|| CustomFilter {
+            key,
+            value: value.to_owned(),
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn view<'a>(
     _collapsed: bool,
     loading: bool,
@@ -23,7 +123,7 @@ pub fn view<'a>(
     employee_points: &[(String, f32)],
     heatmap_loading: bool,
     heatmap_error: Option<&'a str>,
-    heatmap_grid: Option<&(Vec<String>, Vec<String>, Vec<Vec<f32>>)>,
+    heatmap_grid: Option<&SiteEmployeeGrid>,
     radar_loading: bool,
     radar_error: Option<&'a str>,
     radar_metrics: &[(String, f32)],
@@ -117,6 +217,147 @@ pub fn view<'a>(
     container(content).padding(24).into()
 }
 
+pub fn ticket_load_view<'a>(state: &'a TicketLoadState) -> Element<'a, Message> {
+    let open_chart = OpenTicketsBarReport::chart(&state.open_tickets);
+    let flow_chart = TicketFlowLineReport::chart(&state.ticket_flow);
+    let backlog_chart = BacklogCategoryBarReport::chart(&state.backlog);
+
+    let flow_range = match (state.ticket_flow.first(), state.ticket_flow.last()) {
+        (Some((first, _, _)), Some((last, _, _))) => format!("{first} to {last}"),
+        _ => String::new(),
+    };
+
+    let content = column![
+        text("Service Desk Load").size(28),
+        chart_section(
+            OpenTicketsBarReport::title(),
+            OpenTicketsBarReport::subtitle(),
+            Canvas::new(open_chart).width(Fill).height(260),
+            state.open_loading,
+            state.open_error.as_deref(),
+            state.open_tickets.is_empty(),
+        ),
+        chart_section(
+            TicketFlowLineReport::title(),
+            TicketFlowLineReport::subtitle(),
+            column![
+                Canvas::new(flow_chart).width(Fill).height(260),
+                text(flow_range).size(12),
+            ]
+            .spacing(4),
+            state.flow_loading,
+            state.flow_error.as_deref(),
+            state.ticket_flow.is_empty(),
+        ),
+        chart_section(
+            BacklogCategoryBarReport::title(),
+            BacklogCategoryBarReport::subtitle(),
+            Canvas::new(backlog_chart).width(Fill).height(260),
+            state.backlog_loading,
+            state.backlog_error.as_deref(),
+            state.backlog.is_empty(),
+        ),
+    ]
+    .spacing(24);
+
+    container(content).padding(24).into()
+}
+
+pub fn state_time_view<'a>(state: &'a StateTimeState) -> Element<'a, Message> {
+    let sections = [
+        (
+            StateTimeGroup::Site,
+            state.by_site.as_ref(),
+            state.site_loading,
+            state.site_error.as_deref(),
+        ),
+        (
+            StateTimeGroup::Assignee,
+            state.by_assignee.as_ref(),
+            state.assignee_loading,
+            state.assignee_error.as_deref(),
+        ),
+    ];
+
+    let mut content = column![text("Time in State").size(28)].spacing(24);
+    for (group, grid, loading, error) in sections {
+        let empty = grid.is_none_or(|(states, _, _)| states.is_empty());
+        let chart = StateTimeHeatmapReport::chart(crate::charts::HeatmapGrid {
+            x_labels: grid.map(|grid| grid.0.clone()).unwrap_or_default(),
+            y_labels: grid.map(|grid| grid.1.clone()).unwrap_or_default(),
+            values: grid.map(|grid| grid.2.clone()).unwrap_or_default(),
+        });
+        content = content.push(chart_section(
+            StateTimeHeatmapReport::title(group),
+            StateTimeHeatmapReport::subtitle(),
+            Canvas::new(chart).width(Fill).height(320),
+            loading,
+            error,
+            empty,
+        ));
+    }
+
+    container(content).padding(24).into()
+}
+
+pub fn custom_field_view<'a>(state: &'a CustomFieldState) -> Element<'a, Message> {
+    let content: Element<'a, Message> = if state.keys.is_empty() {
+        column![
+            text(CustomFieldBarReport::title()).size(18),
+            text("No custom fields imported yet. Columns that no field is mapped to are kept here.")
+                .size(14),
+        ]
+        .spacing(8)
+        .into()
+    } else {
+        let controls = row![
+            text("Group by").size(14),
+            pick_list(
+                state.keys.as_slice(),
+                state.group_by.as_ref(),
+                Message::CustomGroupByChanged,
+            )
+            .text_size(14),
+            text("Only where").size(14),
+            pick_list(
+                state.keys.as_slice(),
+                state.filter_key.as_ref(),
+                Message::CustomFilterKeyChanged,
+            )
+            .placeholder("any field")
+            .text_size(14),
+            text("is").size(14),
+            text_input("value", &state.filter_value)
+                .on_input(Message::CustomFilterValueChanged)
+                .on_submit(Message::ApplyCustomFilter)
+                .size(14)
+                .width(160),
+            button(text("Apply").size(14)).on_press(Message::ApplyCustomFilter),
+            button(text("Clear").size(14)).on_press(Message::ClearCustomFilter),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+        column![
+            controls,
+            chart_section(
+                CustomFieldBarReport::title(),
+                CustomFieldBarReport::subtitle(),
+                Canvas::new(CustomFieldBarReport::chart(&state.points))
+                    .width(Fill)
+                    .height(260),
+                state.loading,
+                state.error.as_deref(),
+                state.points.is_empty(),
+            ),
+        ]
+        .spacing(12)
+        .into()
+    };
+
+    container(content).padding(24).into()
+}
+
 fn chart_section<'a>(
     title: &'static str,
     subtitle: &'static str,
@@ -139,6 +380,6 @@ fn chart_section<'a>(
 
     container(section)
         .padding(16)
-        .style(|theme| iced::widget::container::bordered_box(theme))
+        .style(iced::widget::container::bordered_box)
         .into()
 }
@@ -0,0 +1,356 @@
+use iced::widget::{
+    button, checkbox, column, container, pick_list, row, scrollable, text, text_input, Space,
+};
+use iced::{Background, Element, Length, Padding};
+use std::path::PathBuf;
+
+use crate::importing::api::ApiSettings;
+use crate::importing::incidents::{IncidentField, IncidentProfile};
+use crate::importing::mapping::RecordField;
+use crate::importing::policy::{DuplicatePolicy, PolicyKind};
+use crate::importing::profile::ProfileField;
+use crate::importing::timestamps::TimestampSettings;
+use crate::importing::NewHireProfile;
+use crate::message::{Message, ProfileMessage, ScreenProfile};
+use crate::screens::import::secondary_button_style;
+use crate::theme::{accent_button_style, DRAWER_BG, DRAWER_ITEM_BG, DRAWER_TEXT_INACTIVE};
+
+#[derive(Debug, Clone, Default)]
+pub struct SettingsState {
+    pub new_hire_policies: PolicyEditor<RecordField>,
+    pub incident_policies: PolicyEditor<IncidentField>,
+    pub api: ApiSettings,
+    pub watch_folder: Option<PathBuf>,
+    pub timestamps: TimestampSettings,
+    pub timezone_input: String,
+    pub formats_input: String,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PolicyEditor<F> {
+    pub policies: Vec<DuplicatePolicy<F>>,
+    pub draft_kind: Option<PolicyKind>,
+    pub draft_fields: Vec<F>,
+    pub loading: bool,
+    pub error: Option<String>,
+}
+
+impl<F> Default for PolicyEditor<F> {
+    fn default() -> Self {
+        Self {
+            policies: Vec::new(),
+            draft_kind: None,
+            draft_fields: Vec::new(),
+            loading: false,
+            error: None,
+        }
+    }
+}
+
+impl<F: ProfileField> PolicyEditor<F> {
+    /// Single-field rules keep only the latest choice.
+    pub fn add_draft_field(&mut self, field: F) {
+        if self.draft_kind.is_some_and(PolicyKind::is_single_field) {
+            self.draft_fields = vec![field];
+        } else if !self.draft_fields.contains(&field) {
+            self.draft_fields.push(field);
+        }
+    }
+
+    pub fn set_draft_kind(&mut self, kind: PolicyKind) {
+        self.draft_kind = Some(kind);
+        if kind.is_single_field() {
+            self.draft_fields.truncate(1);
+        }
+    }
+
+    pub fn take_draft(&mut self) -> Option<DuplicatePolicy<F>> {
+        if self.draft_fields.is_empty() {
+            return None;
+        }
+
+        let kind = self.draft_kind.take()?;
+        Some(DuplicatePolicy {
+            kind,
+            fields: std::mem::take(&mut self.draft_fields),
+            enabled: true,
+        })
+    }
+}
+
+impl SettingsState {
+    pub fn reset_timestamp_inputs(&mut self) {
+        self.timezone_input = self.timestamps.timezone.to_string();
+        self.formats_input = self.timestamps.formats.join("; ");
+    }
+
+    pub fn timestamp_draft(&self) -> Result<TimestampSettings, String> {
+        let formats: Vec<String> = self
+            .formats_input
+            .split(';')
+            .map(str::trim)
+            .filter(|format| !format.is_empty())
+            .map(str::to_owned)
+            .collect();
+        if formats.is_empty() {
+            return Err("Enter at least one timestamp format.".to_owned());
+        }
+
+        Ok(TimestampSettings {
+            timezone: self.timezone_input.parse()?,
+            formats,
+        })
+    }
+}
+
+pub fn view<'a>(settings: &SettingsState) -> Element<'a, Message> {
+    let mut content = column![
+        text("Settings").size(22),
+        text("Duplicate Policies").size(16),
+        text("Duplicate policies decide changed tickets during an import before they are sent to manual review. Rules are checked from top to bottom.")
+            .size(14)
+            .style(|_| text::Style {
+                color: Some(DRAWER_TEXT_INACTIVE),
+            }),
+    ]
+    .spacing(12);
+
+    if let Some(message) = &settings.message {
+        content = content.push(text(message.clone()).size(14));
+    }
+
+    content = content
+        .push(policy_view::<NewHireProfile>(&settings.new_hire_policies))
+        .push(policy_view::<IncidentProfile>(&settings.incident_policies))
+        .push(api_view(&settings.api))
+        .push(timestamp_view(settings))
+        .push(watch_view(settings.watch_folder.as_ref()));
+
+    let panel = container(content)
+        .padding(24)
+        .width(Length::Fill)
+        .max_width(1100)
+        .style(|_| container::background(Background::Color(DRAWER_BG)));
+
+    container(panel)
+        .padding(24)
+        .center_x(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
+
+fn policy_view<'a, P: ScreenProfile>(editor: &PolicyEditor<P::Field>) -> Element<'a, Message> {
+    let title = text(P::KIND.label()).size(15);
+    let body: Element<'a, Message> = if editor.loading {
+        text("Loading duplicate policies...").size(14).into()
+    } else if let Some(message) = &editor.error {
+        text(message.clone()).size(14).into()
+    } else {
+        column![policy_list::<P>(&editor.policies), draft_view::<P>(editor)]
+            .spacing(12)
+            .into()
+    };
+
+    column![title, body].spacing(12).into()
+}
+
+fn policy_list<'a, P: ScreenProfile>(
+    policies: &[DuplicatePolicy<P::Field>],
+) -> Element<'a, Message> {
+    if policies.is_empty() {
+        return text("No duplicate policies. Every changed ticket is reviewed by hand.")
+            .size(14)
+            .into();
+    }
+
+    let rows = policies.iter().enumerate().map(|(index, policy)| {
+        row![
+            text(format!("{}.", index + 1))
+                .size(14)
+                .width(Length::Fixed(30.0)),
+            text(policy.describe()).size(14).width(Length::Fill),
+            checkbox(policy.enabled)
+                .label("Enabled")
+                .on_toggle(move |enabled| P::message(ProfileMessage::TogglePolicy(index, enabled)))
+                .text_size(14),
+            button(text("Move Up").size(13))
+                .style(secondary_button_style)
+                .on_press_maybe(
+                    (index > 0).then(|| P::message(ProfileMessage::MovePolicyUp(index))),
+                ),
+            button(text("Remove").size(13))
+                .style(secondary_button_style)
+                .on_press(P::message(ProfileMessage::RemovePolicy(index))),
+        ]
+        .spacing(16)
+        .align_y(iced::Alignment::Center)
+        .into()
+    });
+
+    column![
+        scrollable(column(rows).spacing(8).padding(Padding::new(0.0).right(16.0)))
+            .height(Length::Fixed(260.0)),
+    ]
+    .spacing(12)
+    .into()
+}
+
+fn draft_view<'a, P: ScreenProfile>(editor: &PolicyEditor<P::Field>) -> Element<'a, Message> {
+    let fields: Vec<P::Field> = P::FIELDS
+        .iter()
+        .copied()
+        .filter(|field| *field != P::KEY)
+        .collect();
+
+    let chosen = if editor.draft_fields.is_empty() {
+        "No fields chosen".to_owned()
+    } else {
+        editor
+            .draft_fields
+            .iter()
+            .map(|field| field.header())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let can_add = editor.draft_kind.is_some() && !editor.draft_fields.is_empty();
+
+    container(
+        column![
+            text("New Rule").size(16),
+            row![
+                pick_list(PolicyKind::ALL, editor.draft_kind, |kind| {
+                    P::message(ProfileMessage::PolicyDraftKindSelected(kind))
+                })
+                .placeholder("Rule type")
+                .width(Length::Fixed(240.0)),
+                pick_list(fields, None::<P::Field>, |field| {
+                    P::message(ProfileMessage::PolicyDraftFieldAdded(field))
+                })
+                .placeholder("Add field")
+                .width(Length::Fixed(260.0)),
+                Space::new().width(Length::Fill),
+            ]
+            .spacing(12),
+            text(chosen).size(14),
+            row![
+                button("Add Rule")
+                    .style(accent_button_style)
+                    .on_press_maybe(can_add.then(|| P::message(ProfileMessage::AddPolicy))),
+                button("Clear")
+                    .style(secondary_button_style)
+                    .on_press(P::message(ProfileMessage::PolicyDraftCleared)),
+            ]
+            .spacing(12),
+        ]
+        .spacing(12),
+    )
+    .padding(16)
+    .style(|_| container::background(Background::Color(DRAWER_ITEM_BG)))
+    .into()
+}
+
+fn api_view<'a>(api: &ApiSettings) -> Element<'a, Message> {
+    let last_sync = match &api.last_updated_at {
+        Some(updated_at) => format!("Next sync fetches tickets updated after {updated_at}."),
+        None => "Next sync fetches every ticket.".to_owned(),
+    };
+
+    column![
+        text("SolarWinds API").size(16),
+        row![
+            text("Base URL").size(14).width(Length::Fixed(120.0)),
+            text_input(crate::importing::api::DEFAULT_BASE_URL, &api.base_url)
+                .on_input(Message::ApiBaseUrlChanged)
+                .width(Length::Fill),
+        ]
+        .spacing(12)
+        .align_y(iced::Alignment::Center),
+        row![
+            text("API Token").size(14).width(Length::Fixed(120.0)),
+            text_input("Kept in the system keyring", &api.token)
+                .secure(true)
+                .on_input(Message::ApiTokenChanged)
+                .width(Length::Fill),
+        ]
+        .spacing(12)
+        .align_y(iced::Alignment::Center),
+        row![
+            button("Save API Settings")
+                .style(accent_button_style)
+                .on_press(Message::SaveApiSettings),
+            button("Reset Sync")
+                .style(secondary_button_style)
+                .on_press_maybe(api.last_updated_at.is_some().then_some(Message::ResetApiSync)),
+            text(last_sync).size(14).style(|_| text::Style {
+                color: Some(DRAWER_TEXT_INACTIVE),
+            }),
+        ]
+        .spacing(12)
+        .align_y(iced::Alignment::Center),
+    ]
+    .spacing(12)
+    .into()
+}
+
+fn timestamp_view<'a>(settings: &SettingsState) -> Element<'a, Message> {
+    column![
+        text("Timestamps").size(16),
+        text("Created, start, resolved and SuccessFactors dates are stored as UTC. Values without an offset are read in the source timezone using the first matching format; rows that match none are flagged as rejected.")
+            .size(14)
+            .style(|_| text::Style {
+                color: Some(DRAWER_TEXT_INACTIVE),
+            }),
+        row![
+            text("Source Timezone").size(14).width(Length::Fixed(120.0)),
+            text_input("UTC, Local or +02:00", &settings.timezone_input)
+                .on_input(Message::TimestampTimezoneChanged)
+                .width(Length::Fixed(240.0)),
+        ]
+        .spacing(12)
+        .align_y(iced::Alignment::Center),
+        row![
+            text("Formats").size(14).width(Length::Fixed(120.0)),
+            text_input("%Y-%m-%d %H:%M:%S; %m/%d/%Y", &settings.formats_input)
+                .on_input(Message::TimestampFormatsChanged)
+                .width(Length::Fill),
+        ]
+        .spacing(12)
+        .align_y(iced::Alignment::Center),
+        button("Save Timestamp Settings")
+            .style(accent_button_style)
+            .on_press(Message::SaveTimestampSettings),
+    ]
+    .spacing(12)
+    .into()
+}
+
+fn watch_view<'a>(folder: Option<&PathBuf>) -> Element<'a, Message> {
+    let status = match folder {
+        Some(folder) => format!(
+            "New CSV files in {} are imported automatically and moved to its \"{}\" subfolder.",
+            folder.display(),
+            crate::importing::watch::ARCHIVE_DIR
+        ),
+        None => "No folder is watched.".to_owned(),
+    };
+
+    column![
+        text("Watch Folder").size(16),
+        text(status).size(14).style(|_| text::Style {
+            color: Some(DRAWER_TEXT_INACTIVE),
+        }),
+        row![
+            button("Choose Folder")
+                .style(accent_button_style)
+                .on_press(Message::ChooseWatchFolder),
+            button("Stop Watching")
+                .style(secondary_button_style)
+                .on_press_maybe(folder.is_some().then_some(Message::ClearWatchFolder)),
+        ]
+        .spacing(12),
+    ]
+    .spacing(12)
+    .into()
+}